@@ -342,6 +342,16 @@ where
     fn hash<H: Hasher>(&self, state: &mut H) { self.value.hash(state); }
 }
 
+#[cfg(feature = "serialize")]
+impl<'a, T> serde::Serialize for Ref<'a, T>
+where
+    T: 'a + serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serializer)
+    }
+}
+
 #[derive(Debug)]
 pub struct RefMut<'a, T: 'a> {
     #[allow(dead_code)]
@@ -464,6 +464,10 @@ impl CommandBuffer {
 
     /// Queues an insertion into the world. This command follows the same syntax as
     /// the normal `World::insert`, returning the entities created for this command.
+    ///
+    /// The returned entity IDs are reserved from the world's allocator when the command is
+    /// recorded, so they may be used immediately (e.g. stored in other components, or targeted
+    /// by further commands). Their components are only written when the buffer is flushed.
     pub fn insert<T, C>(&mut self, tags: T, components: C) -> &[Entity]
     where
         T: 'static + TagSet + TagLayout + for<'a> Filter<ChunksetFilterData<'a>>,
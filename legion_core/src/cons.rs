@@ -78,6 +78,23 @@ impl ConsFlatten for () {
     fn flatten(self) -> Self::Output { self }
 }
 
+/// Marks that a type is the head of a cons list, for use with `ConsContains`.
+pub struct Here;
+
+/// Marks that a type is found at index `I` within the tail of a cons list, for use with
+/// `ConsContains`.
+pub struct There<I>(std::marker::PhantomData<I>);
+
+/// Implemented for cons lists which contain the type `T`.
+///
+/// `I` describes the position of `T` within the list. It is always inferred, and only exists
+/// so that the impls for each position do not overlap.
+pub trait ConsContains<T, I> {}
+
+impl<T, Tail> ConsContains<T, Here> for (T, Tail) {}
+
+impl<T, I, H, Tail: ConsContains<T, I>> ConsContains<T, There<I>> for (H, Tail) {}
+
 macro_rules! cons {
     () => (
         ()
@@ -145,6 +162,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn cons_contains() {
+        fn contains<L: ConsContains<T, I>, T, I>() {}
+
+        contains::<cons!(u8), u8, _>();
+        contains::<cons!(u8, u16, u32), u8, _>();
+        contains::<cons!(u8, u16, u32), u32, _>();
+    }
+
     #[test]
     fn cons_flatten() {
         #![allow(clippy::unit_cmp)]
@@ -2,12 +2,17 @@ use crate::index::ArchetypeIndex;
 use crate::index::ChunkIndex;
 use crate::index::ComponentIndex;
 use crate::index::SetIndex;
+use crate::world::WorldId;
 use parking_lot::{Mutex, RwLock, RwLockWriteGuard};
 use std::fmt::Display;
+use std::num::ParseIntError;
 use std::num::Wrapping;
 use std::ops::Deref;
 use std::ops::DerefMut;
+use std::ops::Range;
+use std::str::FromStr;
 use std::sync::Arc;
+use thiserror::Error;
 
 pub type EntityIndex = u32;
 pub(crate) type EntityVersion = Wrapping<u32>;
@@ -25,6 +30,18 @@ impl Entity {
     }
 
     pub fn index(self) -> EntityIndex { self.index }
+
+    /// Packs the entity's index and version into a single integer.
+    ///
+    /// The version is stored in the high 32 bits and the index in the low 32 bits, independent
+    /// of the layout of `Entity`, so the representation is stable across builds and platforms.
+    /// `from_bits` performs the reverse conversion.
+    pub fn to_bits(self) -> u64 { (u64::from(self.version.0) << 32) | u64::from(self.index) }
+
+    /// Unpacks an entity previously packed with `to_bits`.
+    pub fn from_bits(bits: u64) -> Entity {
+        Entity::new(bits as EntityIndex, Wrapping((bits >> 32) as u32))
+    }
 }
 
 impl Display for Entity {
@@ -33,6 +50,27 @@ impl Display for Entity {
     }
 }
 
+/// An error returned when parsing an `Entity` from a string.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ParseEntityError {
+    #[error("expected an entity in the form `index#version`")]
+    MissingSeparator,
+    #[error("invalid entity index or version: {0}")]
+    InvalidInteger(#[from] ParseIntError),
+}
+
+impl FromStr for Entity {
+    type Err = ParseEntityError;
+
+    /// Parses an entity from the `index#version` form written by `Display`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (index, version) = s
+            .split_once('#')
+            .ok_or(ParseEntityError::MissingSeparator)?;
+        Ok(Entity::new(index.parse()?, Wrapping(version.parse()?)))
+    }
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct EntityLocation {
     archetype_index: ArchetypeIndex,
@@ -67,14 +105,24 @@ impl EntityLocation {
 
 pub(crate) struct Locations {
     blocks: Vec<Option<Vec<EntityLocation>>>,
+    #[cfg(test)]
+    pub(crate) reallocations: usize,
 }
 
 impl Locations {
-    pub fn new() -> Self { Locations { blocks: Vec::new() } }
+    pub fn new() -> Self {
+        Locations {
+            blocks: Vec::new(),
+            #[cfg(test)]
+            reallocations: 0,
+        }
+    }
+
+    const BLOCK_SIZE: usize = 1024;
 
     fn index(entity: EntityIndex) -> (usize, usize) {
-        let block = entity as usize / BlockAllocator::BLOCK_SIZE;
-        let index = entity as usize - block * BlockAllocator::BLOCK_SIZE;
+        let block = entity as usize / Locations::BLOCK_SIZE;
+        let index = entity as usize - block * Locations::BLOCK_SIZE;
         (block, index)
     }
 
@@ -87,9 +135,26 @@ impl Locations {
             .map(|b| b[index])
     }
 
+    /// Reserves capacity in the block list for the locations of all entity indices below `end`,
+    /// so that recording the locations of a batch of new entities does not repeatedly
+    /// reallocate it.
+    pub fn reserve(&mut self, end: usize) {
+        let blocks = end.div_ceil(Locations::BLOCK_SIZE);
+        if blocks > self.blocks.len() {
+            self.blocks.reserve(blocks - self.blocks.len());
+        }
+    }
+
     pub fn set(&mut self, entity: Entity, location: EntityLocation) {
         let (block_index, index) = Locations::index(entity.index());
         if self.blocks.len() <= block_index {
+            #[cfg(test)]
+            {
+                if self.blocks.capacity() <= block_index {
+                    self.reallocations += 1;
+                }
+            }
+
             let fill = block_index - self.blocks.len() + 1;
             self.blocks.extend((0..fill).map(|_| None));
         }
@@ -102,7 +167,7 @@ impl Locations {
                 ChunkIndex(0),
                 ComponentIndex(0),
             ))
-            .take(BlockAllocator::BLOCK_SIZE)
+            .take(Locations::BLOCK_SIZE)
             .collect()
         });
 
@@ -110,33 +175,173 @@ impl Locations {
     }
 }
 
+/// The block indices which an `EntityAllocator` may take blocks from.
+#[derive(Debug, Clone)]
+pub(crate) enum BlockRegion {
+    /// Any block which has not been reserved.
+    Unreserved,
+    /// The blocks within a range reserved with `BlockAllocator::reserve_range`.
+    Range(Range<usize>),
+    /// Every `stride`th unreserved block, starting from `offset`.
+    ///
+    /// The blocks taken by an allocator in a lane do not depend upon the allocations made by
+    /// allocators in other lanes.
+    Lane { offset: usize, stride: usize },
+}
+
+impl BlockRegion {
+    fn contains(&self, index: usize, reserved: &[Range<usize>]) -> bool {
+        match self {
+            BlockRegion::Unreserved => !reserved.iter().any(|r| r.contains(&index)),
+            BlockRegion::Range(range) => range.contains(&index),
+            BlockRegion::Lane { offset, stride } => {
+                index % stride == *offset && !reserved.iter().any(|r| r.contains(&index))
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct BlockAllocator {
-    allocated: usize,
+    block_size: usize,
+    next: usize,
+    created: Vec<bool>,
+    reserved: Vec<Range<usize>>,
     free: Vec<EntityBlock>,
+    owners: Vec<Option<WorldId>>,
+    #[cfg(test)]
+    allocations: usize,
 }
 
 impl BlockAllocator {
-    const BLOCK_SIZE: usize = 1024;
+    pub(crate) const DEFAULT_BLOCK_SIZE: usize = 1024;
+
+    /// Creates a block allocator which hands out blocks of `block_size` entity indices.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is not a power of two.
+    pub(crate) fn new(block_size: usize) -> Self {
+        assert!(
+            block_size.is_power_of_two(),
+            "entity block size must be a power of two, but was {}",
+            block_size
+        );
 
-    pub(crate) fn new() -> Self {
         BlockAllocator {
-            allocated: 0,
+            block_size,
+            next: 0,
+            created: Vec::new(),
+            reserved: Vec::new(),
             free: Vec::new(),
+            owners: Vec::new(),
+            #[cfg(test)]
+            allocations: 0,
         }
     }
 
-    pub fn allocate(&mut self) -> EntityBlock {
-        if let Some(block) = self.free.pop() {
-            block
+    pub(crate) fn block_size(&self) -> usize { self.block_size }
+
+    /// Gets the index of the block containing the given entity index.
+    fn index(&self, entity: EntityIndex) -> usize { entity as usize / self.block_size }
+
+    pub fn allocate(&mut self, owner: Option<WorldId>) -> EntityBlock {
+        self.allocate_in(&BlockRegion::Unreserved, owner).unwrap()
+    }
+
+    /// Allocates a block from within the given region of block indices.
+    ///
+    /// Returns `None` if every block in the region is already in use.
+    pub(crate) fn allocate_in(
+        &mut self,
+        region: &BlockRegion,
+        owner: Option<WorldId>,
+    ) -> Option<EntityBlock> {
+        #[cfg(test)]
+        {
+            self.allocations += 1;
+        }
+
+        let block_size = self.block_size;
+        let reserved = &self.reserved;
+        let reusable = self
+            .free
+            .iter()
+            .rposition(|b| region.contains(b.start as usize / block_size, reserved));
+
+        let block = if let Some(i) = reusable {
+            self.free.swap_remove(i)
         } else {
-            let block = EntityBlock::new(self.allocated as EntityIndex, BlockAllocator::BLOCK_SIZE);
-            self.allocated += BlockAllocator::BLOCK_SIZE;
-            block
+            let index = match region {
+                BlockRegion::Unreserved => {
+                    while self.is_created(self.next) || self.is_reserved(self.next) {
+                        self.next += 1;
+                    }
+                    self.next
+                }
+                BlockRegion::Range(range) => range.clone().find(|i| !self.is_created(*i))?,
+                BlockRegion::Lane { offset, stride } => (*offset..)
+                    .step_by(*stride)
+                    .find(|i| !self.is_created(*i) && !self.is_reserved(*i))?,
+            };
+            self.create(index)
+        };
+        self.set_owner(block.start, owner);
+        Some(block)
+    }
+
+    /// Reserves the blocks covering `len` entity indices from `start`, so that they are only
+    /// ever handed out to allocators constructed for the returned range.
+    ///
+    /// The range is widened to whole blocks of `block_size` indices. Returns `None` if any of
+    /// those blocks are already reserved or have already been allocated.
+    pub(crate) fn reserve_range(&mut self, start: EntityIndex, len: usize) -> Option<Range<usize>> {
+        let first = self.index(start);
+        let last = (start as usize + len).div_ceil(self.block_size);
+        let blocks = first..last.max(first + 1);
+
+        if blocks
+            .clone()
+            .any(|i| self.is_created(i) || self.is_reserved(i))
+        {
+            return None;
+        }
+
+        self.reserved.push(blocks.clone());
+        Some(blocks)
+    }
+
+    fn is_created(&self, index: usize) -> bool { self.created.get(index).copied().unwrap_or(false) }
+
+    fn is_reserved(&self, index: usize) -> bool { self.reserved.iter().any(|r| r.contains(&index)) }
+
+    fn create(&mut self, index: usize) -> EntityBlock {
+        if self.created.len() <= index {
+            self.created.resize(index + 1, false);
+            self.owners.resize(index + 1, None);
         }
+        self.created[index] = true;
+        EntityBlock::new((index * self.block_size) as EntityIndex, self.block_size)
     }
 
-    pub fn free(&mut self, block: EntityBlock) { self.free.push(block); }
+    pub fn free(&mut self, block: EntityBlock) {
+        self.set_owner(block.start, None);
+        self.free.push(block);
+    }
+
+    /// Gets the world which currently holds the block containing the given entity index.
+    pub(crate) fn owner(&self, index: EntityIndex) -> Option<WorldId> {
+        self.owners.get(self.index(index)).copied().flatten()
+    }
+
+    fn set_owner(&mut self, start: EntityIndex, owner: Option<WorldId>) {
+        let index = self.index(start);
+        self.owners[index] = owner;
+    }
+}
+
+impl Default for BlockAllocator {
+    fn default() -> Self { BlockAllocator::new(BlockAllocator::DEFAULT_BLOCK_SIZE) }
 }
 
 #[derive(Debug)]
@@ -145,6 +350,7 @@ pub struct EntityBlock {
     len: usize,
     versions: Vec<EntityVersion>,
     free: Vec<EntityIndex>,
+    exhausted: usize,
 }
 
 impl EntityBlock {
@@ -154,6 +360,7 @@ impl EntityBlock {
             len,
             versions: Vec::with_capacity(len),
             free: Vec::new(),
+            exhausted: 0,
         }
     }
 
@@ -185,11 +392,25 @@ impl EntityBlock {
         }
     }
 
+    /// Gets the number of entities which can be allocated from this block.
+    pub fn available(&self) -> usize { self.free.len() + self.len - self.versions.len() }
+
+    /// Gets the number of entity indices in this block which have been retired, because every
+    /// possible version of them has been used.
+    pub fn exhausted_slots(&self) -> usize { self.exhausted }
+
     pub fn free(&mut self, entity: Entity) -> bool {
         if let Some(true) = self.is_alive(entity) {
             let i = self.index(entity.index);
             self.versions[i] += Wrapping(1);
-            self.free.push(entity.index);
+
+            // versions start at 1, so a version of 0 means that every version has been issued;
+            // the slot is retired rather than reused, so that stale handles can never collide
+            if self.versions[i].0 == 0 {
+                self.exhausted += 1;
+            } else {
+                self.free.push(entity.index);
+            }
             true
         } else {
             false
@@ -199,26 +420,32 @@ impl EntityBlock {
 
 #[derive(Debug)]
 struct Blocks {
+    block_size: usize,
     blocks: Vec<Option<EntityBlock>>,
 }
 
 impl Blocks {
-    fn new() -> Self { Self { blocks: Vec::new() } }
+    fn new(block_size: usize) -> Self {
+        Self {
+            block_size,
+            blocks: Vec::new(),
+        }
+    }
 
-    pub fn index(entity: EntityIndex) -> usize { entity as usize / BlockAllocator::BLOCK_SIZE }
+    pub fn index(&self, entity: EntityIndex) -> usize { entity as usize / self.block_size }
 
     fn find(&self, entity: EntityIndex) -> Option<&EntityBlock> {
-        let i = Blocks::index(entity);
+        let i = self.index(entity);
         self.blocks.get(i).map(|b| b.as_ref()).flatten()
     }
 
     fn find_mut(&mut self, entity: EntityIndex) -> Option<&mut EntityBlock> {
-        let i = Blocks::index(entity);
+        let i = self.index(entity);
         self.blocks.get_mut(i).map(|b| b.as_mut()).flatten()
     }
 
     fn push(&mut self, block: EntityBlock) -> usize {
-        let i = Blocks::index(block.start);
+        let i = self.index(block.start);
         if self.blocks.len() > i {
             self.blocks[i] = Some(block);
         } else {
@@ -252,13 +479,66 @@ impl DerefMut for Blocks {
 pub struct EntityAllocator {
     allocator: Arc<Mutex<BlockAllocator>>,
     blocks: RwLock<Blocks>,
+    owner: Option<WorldId>,
+    region: BlockRegion,
 }
 
 impl EntityAllocator {
     pub(crate) fn new(allocator: Arc<Mutex<BlockAllocator>>) -> Self {
+        let block_size = allocator.lock().block_size();
+        EntityAllocator {
+            allocator,
+            blocks: RwLock::new(Blocks::new(block_size)),
+            owner: None,
+            region: BlockRegion::Unreserved,
+        }
+    }
+
+    /// Creates an allocator which registers the blocks it takes from the shared
+    /// block allocator as owned by the given world.
+    pub(crate) fn with_owner(allocator: Arc<Mutex<BlockAllocator>>, owner: WorldId) -> Self {
+        let block_size = allocator.lock().block_size();
         EntityAllocator {
             allocator,
-            blocks: RwLock::new(Blocks::new()),
+            blocks: RwLock::new(Blocks::new(block_size)),
+            owner: Some(owner),
+            region: BlockRegion::Unreserved,
+        }
+    }
+
+    /// Creates an allocator which only allocates IDs from the given range of blocks, as
+    /// reserved with `BlockAllocator::reserve_range`.
+    pub(crate) fn with_range(
+        allocator: Arc<Mutex<BlockAllocator>>,
+        owner: Option<WorldId>,
+        range: Range<usize>,
+    ) -> Self {
+        let block_size = allocator.lock().block_size();
+        EntityAllocator {
+            allocator,
+            blocks: RwLock::new(Blocks::new(block_size)),
+            owner,
+            region: BlockRegion::Range(range),
+        }
+    }
+
+    /// Creates an allocator which only takes every `stride`th block from the shared block
+    /// allocator, starting from `offset`.
+    ///
+    /// Allocators in different lanes are given the same IDs regardless of the order in which
+    /// they take blocks, which allows several worlds to assign IDs deterministically.
+    pub(crate) fn with_lane(
+        allocator: Arc<Mutex<BlockAllocator>>,
+        owner: Option<WorldId>,
+        offset: usize,
+        stride: usize,
+    ) -> Self {
+        let block_size = allocator.lock().block_size();
+        EntityAllocator {
+            allocator,
+            blocks: RwLock::new(Blocks::new(block_size)),
+            owner,
+            region: BlockRegion::Lane { offset, stride },
         }
     }
 
@@ -272,14 +552,80 @@ impl EntityAllocator {
             .unwrap_or(false)
     }
 
+    /// Determines if each of the given entities is considered alive.
+    ///
+    /// This takes the allocator's lock once for the whole slice.
+    pub fn are_alive(&self, entities: &[Entity]) -> Vec<bool> {
+        let blocks = self.blocks.read();
+        entities
+            .iter()
+            .map(|entity| {
+                blocks
+                    .find(entity.index())
+                    .and_then(|b| b.is_alive(*entity))
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+
+    /// Gets the number of entity indices held by this allocator which have been retired,
+    /// because every possible version of them has been used.
+    pub fn exhausted_slots(&self) -> usize {
+        self.blocks
+            .read()
+            .blocks
+            .iter()
+            .flatten()
+            .map(|block| block.exhausted_slots())
+            .sum()
+    }
+
+    /// Takes enough blocks from the shared block allocator for at least `additional` more
+    /// entities to be created without needing to take its lock.
+    pub fn reserve(&self, additional: usize) {
+        let mut blocks = self.blocks.write();
+
+        // the most recently taken blocks are the most likely to have space, so search from the
+        // back and stop as soon as enough has been found
+        let mut available = 0;
+        for block in blocks.iter().rev().flatten() {
+            if available >= additional {
+                break;
+            }
+            available += block.available();
+        }
+
+        if available >= additional {
+            return;
+        }
+
+        let mut allocator = self.allocator.lock();
+        while available < additional {
+            let block = allocator
+                .allocate_in(&self.region, self.owner)
+                .expect("reserved entity id range exhausted");
+            available += block.available();
+            blocks.push(block);
+        }
+    }
+
     /// Allocates a new unused `Entity` ID.
     pub fn create_entity(&self) -> Entity { self.create_entities().next().unwrap() }
 
+    /// Gets the end of the range of entity indices which can be allocated without taking more
+    /// blocks from the shared block allocator.
+    pub(crate) fn index_bound(&self) -> usize {
+        let blocks = self.blocks.read();
+        blocks.len() * blocks.block_size
+    }
+
     /// Creates an iterator which allocates new `Entity` IDs.
     pub fn create_entities(&self) -> CreateEntityIter {
         CreateEntityIter {
             blocks: self.blocks.write(),
             allocator: &self.allocator,
+            owner: self.owner,
+            region: &self.region,
             current_block: None,
         }
     }
@@ -298,12 +644,22 @@ impl EntityAllocator {
                 // If any entity in the block is in an allocated state, clear
                 // and repopulate the free list. This forces all entities into an
                 // unallocated state. Bump versions of all entity indexes to
-                // ensure that we don't reuse the same entity.
-                if block.free.len() < block.versions.len() {
+                // ensure that we don't reuse the same entity. Retired slots
+                // (version 0) stay retired, and slots whose version wraps
+                // around are retired.
+                if block.free.len() + block.exhausted < block.versions.len() {
                     block.free.clear();
                     for (i, version) in block.versions.iter_mut().enumerate() {
+                        if version.0 == 0 {
+                            continue;
+                        }
+
                         *version += Wrapping(1);
-                        block.free.push(i as u32 + block.start);
+                        if version.0 == 0 {
+                            block.exhausted += 1;
+                        } else {
+                            block.free.push(i as u32 + block.start);
+                        }
                     }
                 }
 
@@ -314,7 +670,14 @@ impl EntityAllocator {
 
     pub(crate) fn merge(&self, other: EntityAllocator) {
         assert!(Arc::ptr_eq(&self.allocator, &other.allocator));
-        self.blocks.write().append(&mut *other.blocks.write());
+        let mut other_blocks = other.blocks.write();
+        {
+            let mut allocator = self.allocator.lock();
+            for block in other_blocks.iter().flatten() {
+                allocator.set_owner(block.start, self.owner);
+            }
+        }
+        self.blocks.write().append(&mut *other_blocks);
     }
 }
 
@@ -326,6 +689,8 @@ pub struct CreateEntityIter<'a> {
     current_block: Option<usize>,
     blocks: RwLockWriteGuard<'a, Blocks>,
     allocator: &'a Mutex<BlockAllocator>,
+    owner: Option<WorldId>,
+    region: &'a BlockRegion,
 }
 
 impl<'a> Iterator for CreateEntityIter<'a> {
@@ -355,7 +720,11 @@ impl<'a> Iterator for CreateEntityIter<'a> {
         }
 
         // allocate a new block
-        let mut block = self.allocator.lock().allocate();
+        let mut block = self
+            .allocator
+            .lock()
+            .allocate_in(self.region, self.owner)
+            .expect("reserved entity id range exhausted");
         let entity = block.allocate().unwrap();
         self.current_block = Some(self.blocks.push(block));
         Some(entity)
@@ -367,15 +736,52 @@ mod tests {
     use crate::entity::*;
     use std::collections::HashSet;
 
+    #[test]
+    fn entity_bits_round_trip() {
+        let indices = [0, 1, 42, 1023, 1024, 65_536, u32::MAX - 1, u32::MAX];
+        let versions = [
+            Wrapping(1),
+            Wrapping(3),
+            Wrapping(u32::MAX),
+            Wrapping(u32::MAX) + Wrapping(1),
+        ];
+        for index in indices.iter() {
+            for version in versions.iter() {
+                let entity = Entity::new(*index, *version);
+                assert_eq!(entity, Entity::from_bits(entity.to_bits()));
+                assert_eq!(entity, entity.to_string().parse().unwrap());
+            }
+        }
+
+        assert_eq!((3 << 32) | 42, Entity::new(42, Wrapping(3)).to_bits());
+        assert_eq!("42#3", Entity::new(42, Wrapping(3)).to_string());
+    }
+
+    #[test]
+    fn entity_parse_errors() {
+        assert_eq!(
+            Err(ParseEntityError::MissingSeparator),
+            "42".parse::<Entity>()
+        );
+        assert!(matches!(
+            "42#x".parse::<Entity>(),
+            Err(ParseEntityError::InvalidInteger(_))
+        ));
+        assert!(matches!(
+            "-1#3".parse::<Entity>(),
+            Err(ParseEntityError::InvalidInteger(_))
+        ));
+    }
+
     #[test]
     fn create_entity() {
-        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::new())));
+        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::default())));
         allocator.create_entity();
     }
 
     #[test]
     fn create_entity_many() {
-        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::new())));
+        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::default())));
 
         for _ in 0..512 {
             allocator.create_entity();
@@ -384,7 +790,7 @@ mod tests {
 
     #[test]
     fn create_entity_many_blocks() {
-        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::new())));
+        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::default())));
 
         for _ in 0..3000 {
             allocator.create_entity();
@@ -393,7 +799,7 @@ mod tests {
 
     #[test]
     fn create_entity_recreate() {
-        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::new())));
+        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::default())));
 
         for _ in 0..3 {
             let entities: Vec<Entity> = (0..512).map(|_| allocator.create_entity()).collect();
@@ -403,9 +809,111 @@ mod tests {
         }
     }
 
+    #[test]
+    fn create_entities_beyond_u16() {
+        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::default())));
+        let entities: Vec<Entity> = allocator.create_entities().take(100_000).collect();
+
+        let unique = entities.iter().copied().collect::<HashSet<_>>();
+        assert_eq!(entities.len(), unique.len());
+        assert!(entities.iter().any(|e| e.index() > u16::MAX as EntityIndex));
+        assert!(allocator
+            .are_alive(&entities)
+            .into_iter()
+            .all(|alive| alive));
+    }
+
+    #[test]
+    fn free_same_index_repeatedly() {
+        let mut block = EntityBlock::new(0, 1);
+        let mut issued = Vec::with_capacity(70_000);
+        for _ in 0..70_000 {
+            let entity = block.allocate().unwrap();
+            assert_eq!(0, entity.index());
+            assert!(block.free(entity));
+            issued.push(entity);
+        }
+
+        let current = block.allocate().unwrap();
+        assert!(issued.iter().all(|e| *e != current));
+        assert!(issued.iter().all(|e| block.is_alive(*e) == Some(false)));
+        assert_eq!(0, block.exhausted_slots());
+    }
+
+    #[test]
+    fn retire_exhausted_slot() {
+        let mut block = EntityBlock::new(0, 2);
+        let first = block.allocate().unwrap();
+        block.free(first);
+
+        // skip ahead to just before the version wraps around
+        block.versions[0] = Wrapping(u32::MAX - 1);
+        let mut issued = vec![first];
+        for _ in 0..2 {
+            let entity = block.allocate().unwrap();
+            assert_eq!(0, entity.index());
+            block.free(entity);
+            issued.push(entity);
+        }
+
+        // the slot is retired instead of reissuing version 1
+        assert_eq!(1, block.exhausted_slots());
+        let next = block.allocate().unwrap();
+        assert_eq!(1, next.index());
+        assert!(issued.iter().all(|e| block.is_alive(*e) == Some(false)));
+        assert_eq!(None, block.allocate());
+    }
+
+    #[test]
+    fn delete_all_keeps_exhausted_slot_retired() {
+        let universe = crate::world::Universe::new();
+        let mut world = universe.create_world();
+        let first = world.insert((), vec![(1usize,)])[0];
+        assert!(world.delete(first));
+
+        // skip ahead so that the slot is reissued with its last version
+        {
+            let mut blocks = world.entity_allocator.blocks.write();
+            let i = blocks.index(first.index());
+            let block = blocks.blocks[i].as_mut().unwrap();
+            let slot = block.index(first.index());
+            block.versions[slot] = Wrapping(u32::MAX);
+        }
+        let exhausted = world.insert((), vec![(2usize,)])[0];
+        assert_eq!(first.index(), exhausted.index());
+        world.insert((), vec![(3usize,)]);
+        assert!(world.delete(exhausted));
+        assert_eq!(1, world.entity_allocator.exhausted_slots());
+
+        world.delete_all();
+        let block_size = BlockAllocator::DEFAULT_BLOCK_SIZE;
+        let reissued = world.insert((), (0..block_size * 2).map(|i| (i,)));
+        assert!(reissued.iter().all(|e| e.index() != exhausted.index()));
+    }
+
+    #[test]
+    fn reserve_avoids_block_allocation() {
+        let blocks = Arc::from(Mutex::new(BlockAllocator::default()));
+        let allocator = EntityAllocator::new(blocks.clone());
+
+        allocator.reserve(4096);
+        let allocations = blocks.lock().allocations;
+        assert_eq!(4, allocations);
+
+        let entities: Vec<Entity> = allocator.create_entities().take(4096).collect();
+        assert_eq!(4096, entities.iter().collect::<HashSet<_>>().len());
+        assert_eq!(allocations, blocks.lock().allocations);
+
+        // reserving what is already available does not take more blocks
+        allocator.reserve(0);
+        assert_eq!(allocations, blocks.lock().allocations);
+        allocator.create_entity();
+        assert_eq!(allocations + 1, blocks.lock().allocations);
+    }
+
     #[test]
     fn is_alive_allocated() {
-        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::new())));
+        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::default())));
         let entity = allocator.create_entity();
 
         assert_eq!(true, allocator.is_alive(entity));
@@ -413,7 +921,7 @@ mod tests {
 
     #[test]
     fn is_alive_unallocated() {
-        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::new())));
+        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::default())));
         let entity = Entity::new(10 as EntityIndex, Wrapping(10));
 
         assert_eq!(false, allocator.is_alive(entity));
@@ -421,7 +929,7 @@ mod tests {
 
     #[test]
     fn is_alive_killed() {
-        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::new())));
+        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::default())));
         let entity = allocator.create_entity();
         allocator.delete_entity(entity);
 
@@ -430,7 +938,7 @@ mod tests {
 
     #[test]
     fn delete_entity_was_alive() {
-        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::new())));
+        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::default())));
         let entity = allocator.create_entity();
 
         assert_eq!(true, allocator.delete_entity(entity));
@@ -438,7 +946,7 @@ mod tests {
 
     #[test]
     fn delete_entity_was_dead() {
-        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::new())));
+        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::default())));
         let entity = allocator.create_entity();
         allocator.delete_entity(entity);
 
@@ -447,7 +955,7 @@ mod tests {
 
     #[test]
     fn delete_entity_was_unallocated() {
-        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::new())));
+        let allocator = EntityAllocator::new(Arc::from(Mutex::new(BlockAllocator::default())));
         let entity = Entity::new(10 as EntityIndex, Wrapping(10));
 
         assert_eq!(false, allocator.delete_entity(entity));
@@ -455,7 +963,7 @@ mod tests {
 
     #[test]
     fn multiple_allocators_unique_ids() {
-        let blocks = Arc::from(Mutex::new(BlockAllocator::new()));
+        let blocks = Arc::from(Mutex::new(BlockAllocator::default()));
         let allocator_a = EntityAllocator::new(blocks.clone());
         let allocator_b = EntityAllocator::new(blocks);
 
@@ -479,4 +987,87 @@ mod tests {
             assert_eq!(true, allocator_b.is_alive(e));
         }
     }
+
+    #[test]
+    fn reserved_ranges_disjoint() {
+        let blocks = Arc::from(Mutex::new(BlockAllocator::default()));
+        let range_a = blocks.lock().reserve_range(0, 2048).unwrap();
+        let range_b = blocks.lock().reserve_range(4096, 2048).unwrap();
+        assert_eq!(None, blocks.lock().reserve_range(1024, 4096));
+
+        let allocator_a = EntityAllocator::with_range(blocks.clone(), None, range_a);
+        let allocator_b = EntityAllocator::with_range(blocks.clone(), None, range_b);
+        let allocator_c = EntityAllocator::new(blocks);
+
+        let mut entities_a = HashSet::<Entity>::default();
+        let mut entities_b = HashSet::<Entity>::default();
+        let mut entities_c = HashSet::<Entity>::default();
+
+        entities_c.extend((0..1500).map(|_| allocator_c.create_entity()));
+        entities_b.extend((0..1000).map(|_| allocator_b.create_entity()));
+        entities_a.extend((0..2048).map(|_| allocator_a.create_entity()));
+        entities_b.extend((0..1048).map(|_| allocator_b.create_entity()));
+
+        assert!(entities_a.iter().all(|e| e.index() < 2048));
+        assert!(entities_b
+            .iter()
+            .all(|e| e.index() >= 4096 && e.index() < 6144));
+        assert!(entities_c
+            .iter()
+            .all(|e| e.index() >= 2048 && (e.index() < 4096 || e.index() >= 6144)));
+        assert!(entities_a.is_disjoint(&entities_b));
+        assert!(entities_a.is_disjoint(&entities_c));
+        assert!(entities_b.is_disjoint(&entities_c));
+    }
+
+    #[test]
+    fn is_alive_many_blocks() {
+        let blocks = Arc::from(Mutex::new(BlockAllocator::new(16)));
+        let allocator = EntityAllocator::new(blocks);
+        let entities = allocator
+            .create_entities()
+            .take(30 * 16)
+            .collect::<Vec<_>>();
+        assert_eq!(30, allocator.blocks.read().iter().flatten().count());
+
+        let mut alive = HashSet::<Entity>::default();
+        for (i, entity) in entities.iter().enumerate() {
+            if i % 3 == 0 {
+                assert!(allocator.delete_entity(*entity));
+            } else {
+                alive.insert(*entity);
+            }
+        }
+
+        // reference implementation checking every block in turn
+        let brute_force = |entity: Entity| {
+            allocator
+                .blocks
+                .read()
+                .iter()
+                .flatten()
+                .find_map(|block| block.is_alive(entity))
+                .unwrap_or(false)
+        };
+
+        let unallocated = (0..16).map(|i| Entity::new(30 * 16 + i, Wrapping(1)));
+        let stale = entities
+            .iter()
+            .map(|e| Entity::new(e.index(), e.version - Wrapping(1)));
+        for entity in entities.iter().copied().chain(unallocated).chain(stale) {
+            assert_eq!(brute_force(entity), allocator.is_alive(entity));
+            assert_eq!(alive.contains(&entity), allocator.is_alive(entity));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "range exhausted")]
+    fn reserved_range_exhausted() {
+        let blocks = Arc::from(Mutex::new(BlockAllocator::default()));
+        let range = blocks.lock().reserve_range(0, 1024).unwrap();
+        let allocator = EntityAllocator::with_range(blocks, None, range);
+        for _ in 0..1025 {
+            allocator.create_entity();
+        }
+    }
 }
@@ -1,6 +1,7 @@
 use crate::index::ArchetypeIndex;
 use crate::index::ChunkIndex;
 use crate::index::SetIndex;
+use crate::iterator::FissileEnumerate;
 use crate::iterator::FissileZip;
 use crate::iterator::SliceVecIter;
 use crate::storage::ArchetypeData;
@@ -52,8 +53,21 @@ pub mod filter_fns {
         EntityFilterTuple::new(TagFilter::new(), TagValueFilter::new(data), Passthrough)
     }
 
+    /// Creates a filter which only includes chunks within the archetype at the given index.
+    pub fn archetype(
+        index: ArchetypeIndex,
+    ) -> EntityFilterTuple<ArchetypeIndexFilter, Passthrough, Passthrough> {
+        EntityFilterTuple::new(ArchetypeIndexFilter::new(index), Passthrough, Passthrough)
+    }
+
     /// Creates a filter which includes chunks for which entity data components
     /// of type `T` have changed since the filter was last executed.
+    ///
+    /// Changes are tracked per chunk, not per entity: any mutable access to `T` within a chunk,
+    /// even of a single entity, and even if no value is actually modified, causes every entity
+    /// in that chunk to pass the filter on its next execution. The filter stores the versions it
+    /// has already seen, so it should be kept in a persistent query rather than recreated each
+    /// time it is run.
     pub fn changed<T: Component>(
     ) -> EntityFilterTuple<ComponentFilter<T>, Passthrough, ComponentChangedFilter<T>> {
         EntityFilterTuple::new(
@@ -1103,6 +1117,78 @@ impl<'a, T> std::ops::BitOr<Passthrough> for TagFilter<T> {
     fn bitor(self, _: Passthrough) -> Self::Output { self }
 }
 
+/// A filter which requires that all chunks belong to the archetype at a specific index.
+#[derive(Debug, Copy, Clone)]
+pub struct ArchetypeIndexFilter {
+    index: ArchetypeIndex,
+}
+
+impl ArchetypeIndexFilter {
+    fn new(index: ArchetypeIndex) -> Self { ArchetypeIndexFilter { index } }
+}
+
+impl ActiveFilter for ArchetypeIndexFilter {}
+
+impl<'a> Filter<ArchetypeFilterData<'a>> for ArchetypeIndexFilter {
+    type Iter = FissileEnumerate<Take<Repeat<()>>>;
+
+    #[inline]
+    fn init(&self) {}
+
+    #[inline]
+    fn collect(&self, source: ArchetypeFilterData<'a>) -> Self::Iter {
+        FissileEnumerate::new(Passthrough.collect(source))
+    }
+
+    #[inline]
+    fn is_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
+        Some(item.0 == self.index.0)
+    }
+}
+
+impl std::ops::Not for ArchetypeIndexFilter {
+    type Output = Not<Self>;
+
+    #[inline]
+    fn not(self) -> Self::Output { Not { filter: self } }
+}
+
+impl<Rhs: ActiveFilter> std::ops::BitAnd<Rhs> for ArchetypeIndexFilter {
+    type Output = And<(Self, Rhs)>;
+
+    #[inline]
+    fn bitand(self, rhs: Rhs) -> Self::Output {
+        And {
+            filters: (self, rhs),
+        }
+    }
+}
+
+impl std::ops::BitAnd<Passthrough> for ArchetypeIndexFilter {
+    type Output = Self;
+
+    #[inline]
+    fn bitand(self, _: Passthrough) -> Self::Output { self }
+}
+
+impl<Rhs: ActiveFilter> std::ops::BitOr<Rhs> for ArchetypeIndexFilter {
+    type Output = Or<(Self, Rhs)>;
+
+    #[inline]
+    fn bitor(self, rhs: Rhs) -> Self::Output {
+        Or {
+            filters: (self, rhs),
+        }
+    }
+}
+
+impl std::ops::BitOr<Passthrough> for ArchetypeIndexFilter {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, _: Passthrough) -> Self::Output { self }
+}
+
 /// A filter which requires that all chunks contain a specific tag value.
 #[derive(Debug)]
 pub struct TagValueFilter<'a, T> {
@@ -80,7 +80,8 @@ impl<'a, T> FissileIterator for SliceVecIter<'a, T> {
     }
 }
 
-pub(crate) struct FissileEnumerate<I: FissileIterator> {
+/// An enumerating iterator which can be split, yielding the original index of each item.
+pub struct FissileEnumerate<I: FissileIterator> {
     iter: I,
     count: usize,
 }
@@ -10,13 +10,17 @@ pub mod index;
 pub mod iterator;
 pub mod permission;
 pub mod query;
+pub mod stats;
 pub mod storage;
 pub mod subworld;
 pub mod world;
+pub mod worldcell;
+pub mod worldview;
 
 #[cfg(feature = "serialize")]
 pub mod serialize;
 
+mod sparse;
 mod tuple;
 mod zip;
 
@@ -26,7 +30,8 @@ pub mod prelude {
         entity::Entity,
         event::Event,
         filter::filter_fns::*,
-        query::{IntoQuery, Query, Read, Tagged, TryRead, TryWrite, Write},
+        query::{IntoQuery, Query, Read, Tagged, Tracked, TryRead, TryWrite, Write},
+        storage::DynamicTag,
         subworld::SubWorld,
         world::{EntityStore, Universe, World},
     };
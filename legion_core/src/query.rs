@@ -1,3 +1,4 @@
+use crate::borrow::Exclusive;
 use crate::borrow::RefIter;
 use crate::borrow::RefIterMut;
 use crate::borrow::RefMap;
@@ -6,9 +7,12 @@ use crate::borrow::RefMapMutSet;
 use crate::borrow::RefMapSet;
 use crate::borrow::TryRefIter;
 use crate::borrow::TryRefIterMut;
+use crate::borrow::UnsafeClone;
 use crate::entity::Entity;
+use crate::entity::EntityIndex;
 use crate::filter::And;
 use crate::filter::ArchetypeFilterData;
+use crate::filter::ArchetypeIndexFilter;
 use crate::filter::ChunkFilterData;
 use crate::filter::ChunksetFilterData;
 use crate::filter::ComponentFilter;
@@ -22,8 +26,11 @@ use crate::index::ChunkIndex;
 use crate::index::{ArchetypeIndex, SetIndex};
 #[cfg(feature = "par-iter")]
 use crate::iterator::{FissileEnumerate, FissileIterator};
+use crate::stats::AccessStats;
 use crate::storage::ArchetypeData;
+use crate::storage::ArchetypeId;
 use crate::storage::Component;
+use crate::storage::ComponentResourceSet;
 use crate::storage::ComponentStorage;
 use crate::storage::ComponentTypeId;
 use crate::storage::Tag;
@@ -36,6 +43,7 @@ use std::iter::Enumerate;
 use std::iter::Repeat;
 use std::iter::Take;
 use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut, Range};
 use std::slice::Iter;
 use std::slice::IterMut;
 
@@ -92,6 +100,30 @@ pub trait ViewElement {
     type Component;
 }
 
+/// A view which can borrow each of its components as a whole slice of a chunk's data.
+///
+/// Implemented for `Read<T>`, `Write<T>` and tuples of them. Used by `World::for_each_chunk_mut`.
+pub trait ViewSlices<'a> {
+    /// The slices borrowed from the chunk.
+    type Slices;
+
+    /// Borrows the view's component slices from a chunk.
+    ///
+    /// # Safety
+    ///
+    /// No runtime borrow is held on the returned slices. The caller must ensure that the
+    /// components are not borrowed elsewhere while the slices are alive, and that the view
+    /// contains no duplicate component types.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the chunk does not contain one of the view's component types.
+    unsafe fn slices(chunk: &'a ComponentStorage) -> Self::Slices;
+}
+
+/// The filter added by `Query::in_archetype`.
+pub type InArchetypeFilter = EntityFilterTuple<ArchetypeIndexFilter, Passthrough, Passthrough>;
+
 /// Converts a `View` into a `Query`.
 pub trait IntoQuery: DefaultFilter + for<'a> View<'a> {
     /// Converts the `View` type into a `Query`.
@@ -107,6 +139,7 @@ impl<T: DefaultFilter + for<'a> View<'a>> IntoQuery for T {
         Query {
             view: PhantomData,
             filter: Self::filter(),
+            include_disabled: false,
         }
     }
 }
@@ -173,6 +206,24 @@ impl<T: Component> ViewElement for Read<T> {
     type Component = T;
 }
 
+impl<'a, T: Component> ViewSlices<'a> for Read<T> {
+    type Slices = &'a [T];
+
+    unsafe fn slices(chunk: &'a ComponentStorage) -> Self::Slices {
+        let (_, slice) = chunk
+            .components(ComponentTypeId::of::<T>())
+            .unwrap_or_else(|| {
+                panic!(
+                    "Component of type {:?} not found in chunk when fetching Read view",
+                    std::any::type_name::<T>()
+                )
+            })
+            .data_slice::<T>()
+            .deconstruct();
+        slice
+    }
+}
+
 /// Reads a single entity data component type from a chunk, if it's present.
 #[derive(Derivative, Debug)]
 #[derivative(Default(bound = ""))]
@@ -297,6 +348,156 @@ impl<T: Component> ViewElement for Write<T> {
     type Component = T;
 }
 
+impl<'a, T: Component> ViewSlices<'a> for Write<T> {
+    type Slices = &'a mut [T];
+
+    unsafe fn slices(chunk: &'a ComponentStorage) -> Self::Slices {
+        let (_, slice) = chunk
+            .components(ComponentTypeId::of::<T>())
+            .unwrap_or_else(|| {
+                panic!(
+                    "Component of type {:?} not found in chunk when fetching Write view",
+                    std::any::type_name::<T>()
+                )
+            })
+            .data_slice_mut::<T>()
+            .deconstruct();
+        slice
+    }
+}
+
+/// Writes to a single entity data component type from a chunk, recording exactly which
+/// components were modified.
+///
+/// `Write` marks every component in a fetched chunk as modified. `Tracked` instead yields
+/// guards which stamp a new version onto a component only when it is mutably dereferenced,
+/// allowing `Query::changed_since` to find precisely the entities which were changed.
+///
+/// Components are only versioned individually in chunks which have been fetched through a
+/// `Tracked` view, so that storage which is never tracked pays no extra cost.
+#[derive(Derivative, Debug)]
+#[derivative(Default(bound = ""))]
+pub struct Tracked<T: Component>(PhantomData<T>);
+
+impl<T: Component> Copy for Tracked<T> {}
+impl<T: Component> Clone for Tracked<T> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<T: Component> DefaultFilter for Tracked<T> {
+    type Filter = EntityFilterTuple<ComponentFilter<T>, Passthrough, Passthrough>;
+
+    fn filter() -> Self::Filter { super::filter::filter_fns::component() }
+}
+
+impl<'a, T: Component> View<'a> for Tracked<T> {
+    type Iter = TrackedIter<'a, T>;
+
+    #[inline]
+    fn fetch(
+        _: &'a ArchetypeData,
+        chunk: &'a ComponentStorage,
+        _: ChunkIndex,
+        _: SetIndex,
+    ) -> Self::Iter {
+        let set = chunk
+            .components(ComponentTypeId::of::<T>())
+            .unwrap_or_else(|| {
+                panic!(
+                    "Component of type {:?} not found in chunk when fetching Tracked view",
+                    std::any::type_name::<T>()
+                )
+            });
+        let (borrow, slice) = unsafe { set.data_slice_mut_tracked::<T>().deconstruct() };
+        unsafe { set.track_elements() };
+        TrackedIter {
+            borrow,
+            set,
+            iter: slice.iter_mut().enumerate(),
+        }
+    }
+
+    #[inline]
+    fn validate() -> bool { true }
+
+    #[inline]
+    fn reads<D: Component>() -> bool { TypeId::of::<T>() == TypeId::of::<D>() }
+
+    #[inline]
+    fn writes<D: Component>() -> bool { TypeId::of::<T>() == TypeId::of::<D>() }
+
+    fn requires_permissions() -> Permissions<ComponentTypeId> {
+        let mut permissions = Permissions::new();
+        permissions.push(ComponentTypeId::of::<T>());
+        permissions
+    }
+
+    fn validate_access(access: &ComponentAccess) -> bool {
+        access.allows_write(ComponentTypeId::of::<T>())
+    }
+}
+
+impl<T: Component> ViewElement for Tracked<T> {
+    type Component = T;
+}
+
+/// Iterates through the components fetched by a `Tracked` view.
+pub struct TrackedIter<'a, T: Component> {
+    borrow: Exclusive<'a>,
+    set: &'a ComponentResourceSet,
+    iter: Enumerate<IterMut<'a, T>>,
+}
+
+impl<'a, T: Component> Iterator for TrackedIter<'a, T> {
+    type Item = TrackedMut<'a, T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, value) = self.iter.next()?;
+        Some(TrackedMut {
+            _borrow: unsafe { self.borrow.clone() },
+            set: self.set,
+            index,
+            value,
+            touched: false,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) { self.iter.size_hint() }
+}
+
+impl<'a, T: Component> ExactSizeIterator for TrackedIter<'a, T> {}
+
+/// A mutable borrow of a component yielded by a `Tracked` view.
+///
+/// The component is marked as modified when it is first mutably dereferenced.
+pub struct TrackedMut<'a, T: Component> {
+    _borrow: Exclusive<'a>,
+    set: &'a ComponentResourceSet,
+    index: usize,
+    value: &'a mut T,
+    touched: bool,
+}
+
+impl<'a, T: Component> Deref for TrackedMut<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T { self.value }
+}
+
+impl<'a, T: Component> DerefMut for TrackedMut<'a, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        if !self.touched {
+            // safe because we hold an exclusive borrow of the slice
+            unsafe { self.set.touch_element(self.index) };
+            self.touched = true;
+        }
+        self.value
+    }
+}
+
 /// Writes a single entity data component type from a chunk, if it's present.
 #[derive(Derivative, Debug)]
 #[derivative(Default(bound = ""))]
@@ -488,6 +689,14 @@ macro_rules! impl_view_tuple {
                 permissions
             }
         }
+
+        impl<'a, $( $ty: ViewSlices<'a> ),* > ViewSlices<'a> for ($( $ty, )*) {
+            type Slices = ($( $ty::Slices, )*);
+
+            unsafe fn slices(chunk: &'a ComponentStorage) -> Self::Slices {
+                ($( $ty::slices(chunk), )*)
+            }
+        }
     };
 }
 
@@ -510,6 +719,7 @@ pub struct Chunk<'a, V: for<'b> View<'b>> {
     components: &'a ComponentStorage,
     chunk_index: ChunkIndex,
     set_index: SetIndex,
+    stats: Option<&'a AccessStats>,
     view: PhantomData<V>,
 }
 
@@ -524,17 +734,44 @@ impl<'a, V: for<'b> View<'b>> Chunk<'a, V> {
             archetype,
             chunk_index,
             set_index,
+            stats: None,
             view: PhantomData,
         }
     }
 
+    fn with_stats(mut self, stats: Option<&'a AccessStats>) -> Self {
+        self.stats = stats;
+        self
+    }
+
+    #[inline]
+    fn record_access(&self) {
+        if let Some(stats) = self.stats {
+            stats.record_view::<V>(self.components);
+        }
+    }
+
     /// Get a slice of all entities contained within the chunk.
     #[inline]
     pub fn entities(&self) -> &'a [Entity] { self.components.entities() }
 
+    /// Borrows the view's component slices from the chunk.
+    ///
+    /// # Safety
+    ///
+    /// See `ViewSlices::slices`.
+    pub(crate) unsafe fn slices(&self) -> <V as ViewSlices<'a>>::Slices
+    where
+        V: ViewSlices<'a>,
+    {
+        self.record_access();
+        V::slices(self.components)
+    }
+
     /// Get an iterator of all data contained within the chunk.
     #[inline]
     pub fn iter_mut(&mut self) -> <V as View<'a>>::Iter {
+        self.record_access();
         V::fetch(
             self.archetype,
             self.components,
@@ -546,6 +783,7 @@ impl<'a, V: for<'b> View<'b>> Chunk<'a, V> {
     /// Get an iterator of all data and entity IDs contained within the chunk.
     #[inline]
     pub fn iter_entities_mut(&mut self) -> ZipEntities<'a, V> {
+        self.record_access();
         ZipEntities {
             entities: self.entities(),
             data: V::fetch(
@@ -559,6 +797,9 @@ impl<'a, V: for<'b> View<'b>> Chunk<'a, V> {
         }
     }
 
+    /// Gets the ID of the archetype which the chunk belongs to.
+    pub fn archetype_id(&self) -> ArchetypeId { self.archetype.id() }
+
     /// Get a tag value.
     pub fn tag<T: Tag>(&self) -> Option<&T> {
         self.archetype
@@ -570,6 +811,9 @@ impl<'a, V: for<'b> View<'b>> Chunk<'a, V> {
 
     /// Get a slice of component data.
     ///
+    /// The slice is aligned to at least `align_of::<T>()`, including for over-aligned
+    /// types such as `#[repr(align(32))]` SIMD vectors.
+    ///
     /// # Panics
     ///
     /// This method performs runtime borrow checking. It will panic if
@@ -585,6 +829,8 @@ impl<'a, V: for<'b> View<'b>> Chunk<'a, V> {
 
     /// Get a mutable slice of component data.
     ///
+    /// The slice has the same alignment guarantee as `components`.
+    ///
     /// # Panics
     ///
     /// This method performs runtime borrow checking. It will panic if
@@ -597,6 +843,84 @@ impl<'a, V: for<'b> View<'b>> Chunk<'a, V> {
             .components(ComponentTypeId::of::<T>())
             .map(|c| unsafe { c.data_slice_mut::<T>() })
     }
+
+    /// Get a mutable slice of component data, even if the view does not write to it.
+    ///
+    /// # Safety
+    ///
+    /// The slice must not alias any data borrowed through the view.
+    pub(crate) unsafe fn components_mut_unchecked<T: Component>(
+        &self,
+    ) -> Option<RefMapMut<'a, &'a mut [T]>> {
+        self.components
+            .components(ComponentTypeId::of::<T>())
+            .map(|c| c.data_slice_mut::<T>())
+    }
+
+    /// Get the raw bytes of a component slice, along with the size of each element.
+    ///
+    /// This allows generic tooling, such as serializers or network delta encoders, to access
+    /// component data without knowing its type.
+    ///
+    /// # Safety
+    ///
+    /// Any padding bytes within the component type are uninitialized, and must not be read.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component type is not readable via the query's view.
+    ///
+    /// This method performs runtime borrow checking. It will panic if
+    /// any other code is concurrently writing to the data slice.
+    pub unsafe fn component_bytes(
+        &self,
+        type_id: ComponentTypeId,
+    ) -> Option<RefMap<'a, (&'a [u8], usize)>> {
+        if !V::requires_permissions().reads().contains(&type_id) {
+            panic!("data type not readable via this query");
+        }
+        self.components.components(type_id).map(|c| {
+            let (ptr, element_size, count) = c.data_raw();
+            ptr.map_into(move |ptr| {
+                (
+                    std::slice::from_raw_parts(*ptr as *const u8, element_size * count),
+                    element_size,
+                )
+            })
+        })
+    }
+
+    /// Overwrites all of the chunk's `T` components with a copy of `data`.
+    ///
+    /// This is the bulk counterpart to `components`, allowing data which was copied out of the
+    /// chunk and processed elsewhere to be written back in a single copy.
+    ///
+    /// Returns `false`, without writing anything, if the chunk does not contain `T`
+    /// components or if `data` is not the same length as the chunk.
+    ///
+    /// # Panics
+    ///
+    /// This method performs runtime borrow checking. It will panic if
+    /// any other code is concurrently accessing the data slice.
+    pub fn overwrite_components<T: Component + Copy>(&self, data: &[T]) -> bool {
+        if !V::writes::<T>() {
+            panic!("data type not writable via this query");
+        }
+        self.components.overwrite_components(data)
+    }
+
+    /// Gets a copy of the user metadata of type `T` cached on the chunk.
+    ///
+    /// Metadata is invalidated whenever the chunk is written to, so this returns `None` if the
+    /// chunk has been modified since the metadata was stored with `set_metadata`.
+    pub fn metadata<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+        self.components.metadata::<T>()
+    }
+
+    /// Caches user metadata of type `T` on the chunk, such as data derived from its components.
+    pub fn set_metadata<T: Send + Sync + 'static>(&self, value: T) {
+        self.components.set_metadata(value)
+    }
 }
 
 /// An iterator which yields view data tuples and entity IDs from a `Chunk`.
@@ -642,6 +966,7 @@ where
     chunkset_filter: &'filter FChunkset,
     chunk_filter: &'filter FChunk,
     archetypes: Enumerate<FArch::Iter>,
+    include_disabled: bool,
     set_frontier: Option<(&'data ArchetypeData, Take<Enumerate<FChunkset::Iter>>)>,
     chunk_frontier: Option<(
         &'data ArchetypeData,
@@ -674,6 +999,18 @@ where
                 match self.archetypes.next() {
                     Some((arch_index, arch_data)) => {
                         if self.arch_filter.is_match(&arch_data).is_pass() {
+                            if !self.include_disabled
+                                && unsafe {
+                                    self.storage
+                                        .inner()
+                                        .archetypes()
+                                        .get_unchecked(arch_index)
+                                        .is_disabled()
+                                }
+                            {
+                                continue;
+                            }
+
                             // validate that we are allowed to access this archetype
                             if !self
                                 .storage
@@ -727,7 +1064,10 @@ where
             if let Some((ref arch, set_index, ref mut set)) = self.chunk_frontier {
                 for (chunk_index, filter_data) in set {
                     if self.chunk_filter.is_match(&filter_data).is_pass() {
-                        return Some(Chunk::new(arch, set_index, ChunkIndex(chunk_index)));
+                        return Some(
+                            Chunk::new(arch, set_index, ChunkIndex(chunk_index))
+                                .with_stats(self.storage.inner().access_stats()),
+                        );
                     }
                 }
             }
@@ -782,6 +1122,35 @@ where
             }
         }
     }
+
+    fn nth(&mut self, mut n: usize) -> Option<Self::Item> {
+        // exhaust the remainder of the current chunk
+        if let Some(ref mut inner) = self.frontier {
+            while n > 0 {
+                if inner.next().is_none() {
+                    break;
+                }
+                n -= 1;
+            }
+            if n == 0 {
+                return self.next();
+            }
+            self.frontier = None;
+        }
+
+        // skip over whole chunks without fetching their data
+        loop {
+            let mut inner = self.iter.next()?;
+            let len = inner.entities().len();
+            if n < len {
+                let mut data = inner.iter_mut();
+                let elt = data.nth(n);
+                self.frontier = Some(data);
+                return elt;
+            }
+            n -= len;
+        }
+    }
 }
 
 /// An iterator which iterates through all entity data in all chunks, zipped with entity ID.
@@ -816,6 +1185,86 @@ where
             }
         }
     }
+
+    fn nth(&mut self, mut n: usize) -> Option<Self::Item> {
+        // skip over the remainder of the current chunk
+        if let Some(ref mut inner) = self.frontier {
+            let (remaining, _) = inner.size_hint();
+            if n < remaining {
+                return inner.nth(n);
+            }
+            n -= remaining;
+            self.frontier = None;
+        }
+
+        // skip over whole chunks without fetching their data
+        loop {
+            let mut inner = self.iter.next()?;
+            let len = inner.entities().len();
+            if n < len {
+                let mut data = inner.iter_entities_mut();
+                let elt = data.nth(n);
+                self.frontier = Some(data);
+                return elt;
+            }
+            n -= len;
+        }
+    }
+}
+
+/// An iterator which iterates through all entity data in all chunks, zipped with entity ID.
+/// Entities within each chunk are yielded in ascending order of their `K` component.
+pub struct SortedChunkEntityIter<'data, V, K, I>
+where
+    V: for<'a> View<'a>,
+    K: Component + Ord,
+    I: Iterator<Item = Chunk<'data, V>>,
+{
+    iter: I,
+    frontier: Option<std::vec::IntoIter<(Entity, <<V as View<'data>>::Iter as Iterator>::Item)>>,
+    _key: PhantomData<K>,
+}
+
+impl<'data, V, K, I> Iterator for SortedChunkEntityIter<'data, V, K, I>
+where
+    V: for<'a> View<'a>,
+    K: Component + Ord,
+    I: Iterator<Item = Chunk<'data, V>>,
+{
+    type Item = (Entity, <<V as View<'data>>::Iter as Iterator>::Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(ref mut inner) = self.frontier {
+                if let elt @ Some(_) = inner.next() {
+                    return elt;
+                }
+            }
+            match self.iter.next() {
+                Some(mut inner) => {
+                    // gather the sort keys and find the permuted order before fetching the view,
+                    // so that the key borrow is released before any writes are borrowed
+                    let order = inner.components::<K>().map(|keys| {
+                        let mut order = (0..keys.len()).collect::<Vec<_>>();
+                        order.sort_by(|a, b| keys[*a].cmp(&keys[*b]));
+                        order
+                    });
+
+                    let mut items = inner.iter_entities_mut().map(Some).collect::<Vec<_>>();
+                    let sorted = match order {
+                        Some(order) => order
+                            .into_iter()
+                            .map(|i| items[i].take().unwrap())
+                            .collect::<Vec<_>>(),
+                        None => items.into_iter().map(Option::unwrap).collect::<Vec<_>>(),
+                    };
+
+                    self.frontier = Some(sorted.into_iter());
+                }
+                None => return None,
+            }
+        }
+    }
 }
 
 /// Queries for entities within a `World`.
@@ -937,6 +1386,22 @@ where
 /// }
 /// ```
 ///
+/// Skipping over query results with `Iterator::skip` or `Iterator::nth` skips whole chunks at a time,
+/// without fetching their data, which allows for efficient pagination:
+///
+/// ```rust
+/// # use legion_core::prelude::*;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Position;
+/// # let universe = Universe::new();
+/// # let mut world = universe.create_world();
+/// let query = Read::<Position>::query();
+///
+/// // the third page of 20 entities
+/// for (entity, pos) in query.iter_entities(&world).skip(40).take(20) {
+/// }
+/// ```
+///
 /// The `ChunkView` yielded from `iter_chunks_mut` allows access to all shared data in the chunk (queried for or not),
 /// but entity data slices can only be accessed if they were requested in the query's view. Attempting to access
 /// other data types, or attempting to write to components that were only requested via a `Read` will panic.
@@ -945,6 +1410,7 @@ where
 pub struct Query<V: for<'a> View<'a>, F: EntityFilter> {
     view: PhantomData<V>,
     pub filter: F,
+    include_disabled: bool,
 }
 
 impl<V, F> Query<V, F>
@@ -961,6 +1427,73 @@ where
         Query {
             view: self.view,
             filter: self.filter & filter,
+            include_disabled: self.include_disabled,
+        }
+    }
+
+    /// Restricts the query to only the entities within the archetype at the given index.
+    ///
+    /// An entity's archetype can be found with `World::get_entity_location`. The query's own
+    /// filter still applies, so the archetype must also contain the components in the view.
+    pub fn in_archetype(
+        self,
+        index: ArchetypeIndex,
+    ) -> Query<V, <F as std::ops::BitAnd<InArchetypeFilter>>::Output>
+    where
+        F: std::ops::BitAnd<InArchetypeFilter>,
+        <F as std::ops::BitAnd<InArchetypeFilter>>::Output: EntityFilter,
+    {
+        self.filter(super::filter::filter_fns::archetype(index))
+    }
+
+    /// Includes entities which have been disabled with `World::set_enabled` in the query's results.
+    ///
+    /// Disabled entities are excluded by default.
+    pub fn include_disabled(mut self) -> Self {
+        self.include_disabled = true;
+        self
+    }
+
+    /// Orders the entities yielded within each chunk by the value of their `K` component.
+    ///
+    /// Sorting is performed per-chunk, so there is no ordering between entities in different chunks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `K` is not read by the query's view.
+    pub fn sort_chunks_by<K: Component + Ord>(self) -> ChunkSortedQuery<V, F, K> {
+        if !V::reads::<K>() {
+            panic!("sort key type not readable via this query");
+        }
+
+        ChunkSortedQuery {
+            query: self,
+            _key: PhantomData,
+        }
+    }
+
+    /// Creates a query for the narrower view `S`, which matches exactly the same entities as this
+    /// query by reusing its filter.
+    ///
+    /// This allows a helper which only needs some of the queried components to be given its own
+    /// query, without re-specifying the original query's filters.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `S` accesses a component which is not accessed by `V`, or writes to a component
+    /// which is only read by `V`.
+    pub fn project<S: for<'a> View<'a>>(&self) -> Query<S, F>
+    where
+        F: Clone,
+    {
+        if !V::requires_permissions().is_superset(&S::requires_permissions()) {
+            panic!("projected view accesses components not accessible via this query");
+        }
+
+        Query {
+            view: PhantomData,
+            filter: self.filter.clone(),
+            include_disabled: self.include_disabled,
         }
     }
 
@@ -995,6 +1528,7 @@ where
             chunkset_filter,
             chunk_filter,
             archetypes,
+            include_disabled: self.include_disabled,
             set_frontier: None,
             chunk_frontier: None,
             _view: PhantomData,
@@ -1013,11 +1547,22 @@ where
         unsafe { self.iter_chunks_unchecked(world) }
     }
 
-    /// Gets an iterator which iterates through all chunks that match the query.
-    pub fn iter_chunks_mut<'a, 'data, T: EntityStore>(
-        &'a self,
-        world: &'data mut T,
-    ) -> ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter> {
+    /// Collects all chunks that match the query.
+    ///
+    /// Chunks of read-only views are `Send`, so the collected chunks may be distributed across
+    /// threads, such as to process them on a custom thread pool.
+    pub fn collect_chunks<'data, T: EntityStore>(&self, world: &'data T) -> Vec<Chunk<'data, V>>
+    where
+        V: ReadOnly,
+    {
+        self.iter_chunks(world).collect()
+    }
+
+    /// Gets an iterator which iterates through all chunks that match the query.
+    pub fn iter_chunks_mut<'a, 'data, T: EntityStore>(
+        &'a self,
+        world: &'data mut T,
+    ) -> ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter> {
         // safe because the &mut World ensures exclusivity
         unsafe { self.iter_chunks_unchecked(world) }
     }
@@ -1065,6 +1610,355 @@ where
         unsafe { self.iter_entities_unchecked(world) }
     }
 
+    /// Gets an iterator which yields the IDs of all matching entities whose `T` component has
+    /// been modified since the given version.
+    ///
+    /// Once a chunk's `T` components have been fetched through a `Tracked` view, components
+    /// modified through a `Tracked` view or `get_component_mut` are versioned individually.
+    /// Before then, any modification counts as modifying every `T` component in the chunk, as
+    /// does fetching a chunk through a `Write` view. Use `storage::current_version` to obtain a version to compare
+    /// against later.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not read by the query's view.
+    pub fn changed_since<'a, 'data: 'a, T: Component, W: EntityStore>(
+        &'a self,
+        world: &'data W,
+        version: u64,
+    ) -> impl Iterator<Item = Entity> + 'a
+    where
+        V: ReadOnly,
+    {
+        if !V::reads::<T>() {
+            panic!("data type not readable via this query");
+        }
+
+        // safe because the view can only read data immutably
+        unsafe { self.iter_chunks_unchecked(world) }.flat_map(move |chunk| {
+            let entities = chunk.entities();
+            let mut changed = Vec::new();
+            if let Some(set) = chunk.components.components(ComponentTypeId::of::<T>()) {
+                // hold a shared borrow so the versions are not modified while we read them
+                let _borrow = unsafe { set.data_slice::<T>() };
+                for (index, entity) in entities.iter().enumerate() {
+                    if set.element_version(index).unwrap_or(0) > version {
+                        changed.push(*entity);
+                    }
+                }
+            }
+            changed
+        })
+    }
+
+    /// Determines if no entities match the query.
+    ///
+    /// This stops at the first non-empty matching chunk, and does not borrow any component data.
+    ///
+    /// Chunk filters are evaluated just as they are during iteration, so stateful filters such as
+    /// `changed` advance their version threshold past the chunks visited here. A later `iter` of
+    /// the same query may then skip chunks that changed before this call, including ones this
+    /// call never reached.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_core::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Enemy;
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// let query = <Read<Enemy>>::query();
+    /// assert!(query.is_empty(&world));
+    ///
+    /// world.insert((), vec![(Enemy,)]);
+    /// assert!(!query.is_empty(&world));
+    /// ```
+    pub fn is_empty<T: EntityStore>(&self, world: &T) -> bool {
+        // safe because no component data is accessed
+        unsafe { self.iter_chunks_unchecked(world) }.all(|chunk| chunk.entities().is_empty())
+    }
+
+    /// Counts the entities which match the query.
+    ///
+    /// This sums the lengths of the matching chunks, and does not borrow or fetch any component
+    /// data, so it is cheap even for views which write to components.
+    ///
+    /// Chunk filters are evaluated just as they are during iteration, so stateful filters such as
+    /// `changed` advance their version threshold, and a later `iter` of the same query will not
+    /// see the counted chunks as changed.
+    pub fn count<T: EntityStore>(&self, world: &T) -> usize {
+        // safe because no component data is accessed
+        unsafe { self.iter_chunks_unchecked(world) }
+            .map(|chunk| chunk.entities().len())
+            .sum()
+    }
+
+    /// Gets an iterator which yields the IDs of all entities that match the query.
+    ///
+    /// This reads each matching chunk's entity slice directly, and does not borrow or fetch any
+    /// of the view's component data.
+    pub fn entities<'a, 'data: 'a, T: EntityStore>(
+        &'a self,
+        world: &'data T,
+    ) -> impl Iterator<Item = Entity> + 'a {
+        // safe because no component data is accessed
+        unsafe { self.iter_chunks_unchecked(world) }
+            .flat_map(|chunk| chunk.entities().iter().copied())
+    }
+
+    /// Gets an iterator which pairs the data of each matching entity with the element of `data`
+    /// at the entity's index.
+    ///
+    /// This allows iterating external arrays indexed by `Entity::index` alongside a query.
+    /// Entities whose index is out of bounds of `data` are skipped.
+    pub fn zip_indexed<'a, 'data, 'b, T: EntityStore, U>(
+        &'a self,
+        world: &'data T,
+        data: &'b [U],
+    ) -> impl Iterator<Item = (<<V as View<'data>>::Iter as Iterator>::Item, &'b U)> + 'a
+    where
+        V: ReadOnly,
+        'data: 'a,
+        'b: 'a,
+    {
+        self.iter_entities(world)
+            .filter_map(move |(entity, item)| Some((item, data.get(entity.index() as usize)?)))
+    }
+
+    /// Gets an iterator which pairs the data of each matching entity with the element of `data`
+    /// at the entity's index.
+    ///
+    /// This allows iterating external arrays indexed by `Entity::index` alongside a query.
+    /// Entities whose index is out of bounds of `data` are skipped.
+    pub fn zip_indexed_mut<'a, 'data, 'b, T: EntityStore, U>(
+        &'a self,
+        world: &'data mut T,
+        data: &'b [U],
+    ) -> impl Iterator<Item = (<<V as View<'data>>::Iter as Iterator>::Item, &'b U)> + 'a
+    where
+        'data: 'a,
+        'b: 'a,
+    {
+        self.iter_entities_mut(world)
+            .filter_map(move |(entity, item)| Some((item, data.get(entity.index() as usize)?)))
+    }
+
+    /// Gets an iterator which pairs the data of each matching entity with the data of another
+    /// entity which it refers to through its `R` component, fetched via the view `O`.
+    ///
+    /// `f` extracts the referenced entity from each `R` component. Entities without an `R`
+    /// component are skipped, as are those whose referenced entity has been deleted or does not
+    /// match `O`. Nothing is yielded if the world does not allow access to the data read by `O`,
+    /// such as a `SubWorld` which does not declare access to it.
+    ///
+    /// Each referenced entity is looked up individually, so this performs a random access per
+    /// matching entity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_core::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Parent(Entity);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// let parent = world.insert((), vec![(Position(1.0),)])[0];
+    /// world.insert((), vec![(Position(2.0), Parent(parent))]);
+    ///
+    /// let query = <(Read<Position>, Read<Parent>)>::query();
+    /// for ((child, _), parent) in query.join_on::<_, Parent, Read<Position>, _>(&world, |p| p.0) {
+    ///     assert_eq!(Position(2.0), *child);
+    ///     assert_eq!(Position(1.0), *parent);
+    /// }
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn join_on<'a, 'data: 'a, T, R, O, J>(
+        &'a self,
+        world: &'data T,
+        f: J,
+    ) -> impl Iterator<
+        Item = (
+            <<V as View<'data>>::Iter as Iterator>::Item,
+            <<O as View<'data>>::Iter as Iterator>::Item,
+        ),
+    > + 'a
+    where
+        T: EntityStore,
+        R: Component,
+        O: for<'v> View<'v> + DefaultFilter + ReadOnly,
+        J: Fn(&R) -> Entity + 'a,
+        V: ReadOnly,
+    {
+        let storage = world.get_component_storage::<O>().ok();
+
+        // determine which archetypes the referenced entities' view can be fetched from
+        let filter = O::filter();
+        let (arch_filter, _, _) = filter.filters();
+        let matches = storage
+            .as_ref()
+            .map(|storage| {
+                arch_filter
+                    .collect(ArchetypeFilterData {
+                        component_types: storage.inner().component_types(),
+                        tag_types: storage.inner().tag_types(),
+                    })
+                    .map(|data| arch_filter.is_match(&data).is_pass())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        self.iter_entities(world).filter_map(move |(entity, item)| {
+            let storage = storage.as_ref()?;
+            let target = f(&*world.get_component::<R>(entity)?);
+            let location = world.get_entity_location(target)?;
+            if !matches
+                .get(location.archetype().0)
+                .copied()
+                .unwrap_or(false)
+                || !storage.can_access_archetype(location.archetype())
+            {
+                return None;
+            }
+
+            let storage = storage.inner();
+            let archetype = storage.archetype(location.archetype())?;
+            let chunk = storage.chunk(location)?;
+            let mut data = O::fetch(archetype, chunk, location.chunk(), location.set());
+            Some((item, data.nth(*location.component())?))
+        })
+    }
+
+    /// Gets an iterator which yields the data and IDs of all matching entities whose index lies
+    /// within `range`.
+    ///
+    /// This allows work to be split deterministically by entity ID, such as sharding entities
+    /// across machines.
+    pub fn iter_entities_in_range<'a, 'data: 'a, T: EntityStore>(
+        &'a self,
+        world: &'data T,
+        range: Range<EntityIndex>,
+    ) -> impl Iterator<Item = (Entity, <<V as View<'data>>::Iter as Iterator>::Item)> + 'a
+    where
+        V: ReadOnly,
+    {
+        self.iter_entities(world)
+            .filter(move |(entity, _)| range.contains(&entity.index()))
+    }
+
+    /// Gets an iterator which yields the data and IDs of all matching entities whose index lies
+    /// within `range`.
+    ///
+    /// This allows work to be split deterministically by entity ID, such as sharding entities
+    /// across machines.
+    pub fn iter_entities_in_range_mut<'a, 'data: 'a, T: EntityStore>(
+        &'a self,
+        world: &'data mut T,
+        range: Range<EntityIndex>,
+    ) -> impl Iterator<Item = (Entity, <<V as View<'data>>::Iter as Iterator>::Item)> + 'a {
+        self.iter_entities_mut(world)
+            .filter(move |(entity, _)| range.contains(&entity.index()))
+    }
+
+    /// Gets an iterator which yields the data of each pair of adjacent matching entities.
+    ///
+    /// Pairs are chunk-local: the last entity in one chunk is not paired with the first entity
+    /// of the next chunk, and chunks containing a single entity yield no pairs.
+    pub fn pairwise<'a, 'data: 'a, T: EntityStore>(
+        &'a self,
+        world: &'data T,
+    ) -> impl Iterator<
+        Item = (
+            <<V as View<'data>>::Iter as Iterator>::Item,
+            <<V as View<'data>>::Iter as Iterator>::Item,
+        ),
+    > + 'a
+    where
+        V: ReadOnly,
+    {
+        self.iter_chunks(world).flat_map(|mut chunk| {
+            let mut next = chunk.iter_mut();
+            next.next();
+            chunk.iter_mut().zip(next)
+        })
+    }
+
+    /// Gets an iterator which iterates through all entity data that matches the query, and also
+    /// yields the ID of the archetype each entity belongs to.
+    ///
+    /// This allows code to branch on the archetype of each item without a separate loop over
+    /// `iter_chunks`.
+    pub fn iter_with_archetype<'a, 'data: 'a, T: EntityStore>(
+        &'a self,
+        world: &'data T,
+    ) -> impl Iterator<Item = (ArchetypeId, <<V as View<'data>>::Iter as Iterator>::Item)> + 'a
+    where
+        V: ReadOnly,
+    {
+        self.iter_chunks(world).flat_map(|mut chunk| {
+            let archetype = chunk.archetype_id();
+            chunk.iter_mut().map(move |item| (archetype, item))
+        })
+    }
+
+    /// Gets an iterator which iterates through all entity data that matches the query, and also
+    /// yields the ID of the archetype each entity belongs to.
+    ///
+    /// This allows code to branch on the archetype of each item without a separate loop over
+    /// `iter_chunks_mut`.
+    pub fn iter_with_archetype_mut<'a, 'data: 'a, T: EntityStore>(
+        &'a self,
+        world: &'data mut T,
+    ) -> impl Iterator<Item = (ArchetypeId, <<V as View<'data>>::Iter as Iterator>::Item)> + 'a
+    {
+        self.iter_chunks_mut(world).flat_map(|mut chunk| {
+            let archetype = chunk.archetype_id();
+            chunk.iter_mut().map(move |item| (archetype, item))
+        })
+    }
+
+    /// Gets an iterator which iterates through all entity data that matches this query, followed
+    /// by all entity data that matches `other`.
+    ///
+    /// This allows queries with the same view but different filters, such as over players and
+    /// enemies, to be processed by the same code. Entities which match both queries are yielded
+    /// twice.
+    pub fn chain<'a, 'data: 'a, T: EntityStore, G: EntityFilter>(
+        &'a self,
+        other: &'a Query<V, G>,
+        world: &'data T,
+    ) -> impl Iterator<Item = <<V as View<'data>>::Iter as Iterator>::Item> + 'a
+    where
+        V: ReadOnly,
+    {
+        self.iter(world).chain(other.iter(world))
+    }
+
+    /// Collects the data and IDs of all matching entities, sorted by the key extracted from
+    /// each entity's data.
+    ///
+    /// Unlike `sort_chunks_by`, which only orders entities within each chunk, this sorts all
+    /// matching entities across all chunks, such as to draw sprites in depth order. The key
+    /// function is called once per entity, and entities with equal keys are kept in iteration
+    /// order.
+    pub fn collect_sorted_by_key<'data, T, K, G>(
+        &self,
+        world: &'data T,
+        mut f: G,
+    ) -> Vec<(Entity, <<V as View<'data>>::Iter as Iterator>::Item)>
+    where
+        T: EntityStore,
+        K: Ord,
+        G: FnMut(&<<V as View<'data>>::Iter as Iterator>::Item) -> K,
+        V: ReadOnly,
+    {
+        let mut items = self.iter_entities(world).collect::<Vec<_>>();
+        items.sort_by_cached_key(|(_, item)| f(item));
+        items
+    }
+
     /// Gets an iterator which iterates through all entity data that matches the query, and also yields the the `Entity` IDs.
     pub fn iter_entities_mut<'a, 'data, T: EntityStore>(
         &'a self,
@@ -1134,6 +2028,48 @@ where
         unsafe { self.iter_unchecked(world) }
     }
 
+    /// Gets the data of the only entity which matches the query.
+    ///
+    /// This is useful for singleton components, which are only ever attached to one entity.
+    /// Returns `None` if no entities match the query.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than one entity matches the query.
+    pub fn single<'data, T: EntityStore>(
+        &self,
+        world: &'data T,
+    ) -> Option<<<V as View<'data>>::Iter as Iterator>::Item>
+    where
+        V: ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        Self::only(unsafe { self.iter_unchecked(world) })
+    }
+
+    /// Mutably gets the data of the only entity which matches the query.
+    ///
+    /// Returns `None` if no entities match the query.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than one entity matches the query.
+    pub fn single_mut<'data, T: EntityStore>(
+        &self,
+        world: &'data mut T,
+    ) -> Option<<<V as View<'data>>::Iter as Iterator>::Item> {
+        // safe because the &mut World ensures exclusivity
+        Self::only(unsafe { self.iter_unchecked(world) })
+    }
+
+    fn only<I: Iterator>(mut iter: I) -> Option<I::Item> {
+        let item = iter.next()?;
+        if iter.next().is_some() {
+            panic!("query matched more than one entity");
+        }
+        Some(item)
+    }
+
     /// Iterates through all entity data that matches the query.
     /// Does not perform static borrow checking.
     ///
@@ -1177,6 +2113,61 @@ where
         unsafe { self.for_each_entities_unchecked(world, f) };
     }
 
+    /// Iterates through all entity data that matches the query, along with each entity's index
+    /// within its chunk.
+    /// Does not perform static borrow checking.
+    ///
+    /// # Safety
+    ///
+    /// The normal borrowing restrictions apply for the duration of the iteration:
+    /// * Components borrowed with `Read` access must not be borrowed mutably elsewhere.
+    /// * Components borrowed with `Write` access must not be borrowed elsewhere at all.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    pub unsafe fn for_each_with_index_unchecked<'a, 'data, T, W>(&'a self, world: &'data W, f: T)
+    where
+        T: Fn((Entity, usize, <<V as View<'data>>::Iter as Iterator>::Item)),
+        W: EntityStore,
+    {
+        for mut chunk in self.iter_chunks_unchecked(world) {
+            chunk
+                .iter_entities_mut()
+                .enumerate()
+                .for_each(|(index, (entity, item))| f((entity, index, item)))
+        }
+    }
+
+    /// Iterates through all entity data that matches the query, along with each entity's index
+    /// within its chunk.
+    ///
+    /// Indices count up from zero within each chunk, which allows chunk-local data structures to
+    /// refer to entities by their position in the chunk.
+    pub fn for_each_with_index<'a, 'data, T, W>(&'a self, world: &'data W, f: T)
+    where
+        T: Fn((Entity, usize, <<V as View<'data>>::Iter as Iterator>::Item)),
+        V: ReadOnly,
+        W: EntityStore,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.for_each_with_index_unchecked(world, f) };
+    }
+
+    /// Iterates through all entity data that matches the query, along with each entity's index
+    /// within its chunk.
+    ///
+    /// Indices count up from zero within each chunk, which allows chunk-local data structures to
+    /// refer to entities by their position in the chunk.
+    pub fn for_each_with_index_mut<'a, 'data, T, W>(&'a self, world: &'data mut W, f: T)
+    where
+        T: Fn((Entity, usize, <<V as View<'data>>::Iter as Iterator>::Item)),
+        W: EntityStore,
+    {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.for_each_with_index_unchecked(world, f) };
+    }
+
     /// Iterates through all entity data that matches the query.
     /// Does not perform static borrow checking.
     ///
@@ -1220,6 +2211,93 @@ where
         unsafe { self.for_each_unchecked(world, f) };
     }
 
+    /// Calls `f` for all entity data that matches the query, and partitions the results into
+    /// successes and the entities for which `f` failed.
+    /// Does not perform static borrow checking.
+    ///
+    /// # Safety
+    ///
+    /// The normal borrowing restrictions apply for the duration of the iteration:
+    /// * Components borrowed with `Read` access must not be borrowed mutably elsewhere.
+    /// * Components borrowed with `Write` access must not be borrowed elsewhere at all.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    pub unsafe fn try_map_unchecked<'a, 'data, R, E, T, W>(
+        &'a self,
+        world: &'data W,
+        mut f: T,
+    ) -> (Vec<R>, Vec<(Entity, E)>)
+    where
+        T: FnMut(Entity, <<V as View<'data>>::Iter as Iterator>::Item) -> Result<R, E>,
+        W: EntityStore,
+    {
+        let mut ok = Vec::new();
+        let mut err = Vec::new();
+        for (entity, data) in self.iter_entities_unchecked(world) {
+            match f(entity, data) {
+                Ok(result) => ok.push(result),
+                Err(error) => err.push((entity, error)),
+            }
+        }
+        (ok, err)
+    }
+
+    /// Calls `f` for all entity data that matches the query, and partitions the results into
+    /// successes and the entities for which `f` failed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_core::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Health(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.insert((), vec![(Health(10.0),), (Health(-5.0),)]);
+    ///
+    /// let query = Read::<Health>::query();
+    /// let (valid, invalid) = query.try_map(&world, |_, health| {
+    ///     if health.0 >= 0.0 {
+    ///         Ok(health.0)
+    ///     } else {
+    ///         Err("negative health")
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(valid, vec![10.0]);
+    /// assert_eq!(invalid.len(), 1);
+    /// ```
+    pub fn try_map<'a, 'data, R, E, T, W>(
+        &'a self,
+        world: &'data W,
+        f: T,
+    ) -> (Vec<R>, Vec<(Entity, E)>)
+    where
+        T: FnMut(Entity, <<V as View<'data>>::Iter as Iterator>::Item) -> Result<R, E>,
+        V: ReadOnly,
+        W: EntityStore,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.try_map_unchecked(world, f) }
+    }
+
+    /// Calls `f` for all entity data that matches the query, and partitions the results into
+    /// successes and the entities for which `f` failed.
+    pub fn try_map_mut<'a, 'data, R, E, T, W>(
+        &'a self,
+        world: &'data mut W,
+        f: T,
+    ) -> (Vec<R>, Vec<(Entity, E)>)
+    where
+        T: FnMut(Entity, <<V as View<'data>>::Iter as Iterator>::Item) -> Result<R, E>,
+        W: EntityStore,
+    {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.try_map_unchecked(world, f) }
+    }
+
     /// Returns a RefMapSet of all components of a given type. This simplifies getting a slice of
     /// references to all components of type T that match the filter. This can be useful for passing
     /// to other libraries or FFI.
@@ -1238,6 +2316,13 @@ where
         unsafe {
             self.filter
                 .iter_archetype_indexes(storage)
+                .filter(|archetype_index| {
+                    self.include_disabled
+                        || !storage
+                            .archetypes()
+                            .get_unchecked(archetype_index.0)
+                            .is_disabled()
+                })
                 .flat_map(|archetype_index| {
                     storage
                         .archetypes()
@@ -1271,6 +2356,13 @@ where
         unsafe {
             self.filter
                 .iter_archetype_indexes(storage)
+                .filter(|archetype_index| {
+                    self.include_disabled
+                        || !storage
+                            .archetypes()
+                            .get_unchecked(archetype_index.0)
+                            .is_disabled()
+                })
                 .flat_map(|archetype_index| {
                     storage
                         .archetypes()
@@ -1323,6 +2415,7 @@ where
             chunkset_filter,
             chunk_filter,
             archetypes,
+            include_disabled: self.include_disabled,
             set_frontier: None,
             chunk_frontier: None,
             _view: PhantomData,
@@ -1476,6 +2569,188 @@ where
         unsafe { self.par_for_each_unchecked(world, f) };
     }
 
+    /// Iterates through all entity data that matches the query in parallel, with a fixed
+    /// assignment of chunks to logical threads. Does not perform static borrow checking.
+    ///
+    /// # Safety
+    ///
+    /// The normal borrowing restrictions apply for the duration of the iteration:
+    /// * Components borrowed with `Read` access must not be borrowed mutably elsewhere.
+    /// * Components borrowed with `Write` access must not be borrowed elsewhere at all.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    #[cfg(feature = "par-iter")]
+    pub unsafe fn par_for_each_deterministic_unchecked<'a, T, W>(
+        &'a self,
+        world: &'a W,
+        num_threads: usize,
+        f: T,
+    ) where
+        T: Fn(usize, <<V as View<'a>>::Iter as Iterator>::Item) + Send + Sync,
+        W: EntityStore,
+    {
+        let chunks = self.iter_chunks_unchecked(world).collect::<Vec<_>>();
+        let num_threads = num_threads.max(1);
+        let count = chunks.len();
+
+        let mut groups = (0..num_threads).map(|_| Vec::new()).collect::<Vec<_>>();
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            groups[i * num_threads / count].push(chunk);
+        }
+
+        groups
+            .into_par_iter()
+            .enumerate()
+            .for_each(|(thread, group)| {
+                for mut chunk in group {
+                    for data in chunk.iter_mut() {
+                        f(thread, data);
+                    }
+                }
+            });
+    }
+
+    /// Iterates through all entity data that matches the query in parallel, with a fixed
+    /// assignment of chunks to logical threads.
+    ///
+    /// Matching chunks are split, in iteration order, into `num_threads` contiguous groups of
+    /// near equal size. Each group is processed sequentially by a single rayon task, and its
+    /// index is passed to `f` alongside each entity's data. Given the same world layout, each
+    /// chunk is always processed by the same logical thread, which makes the results of
+    /// parallel iteration reproducible for debugging. The OS thread which runs each group is
+    /// still chosen by rayon.
+    ///
+    /// This does not balance load as well as `par_for_each`.
+    #[cfg(feature = "par-iter")]
+    pub fn par_for_each_deterministic<'a, T, W>(&'a self, world: &'a W, num_threads: usize, f: T)
+    where
+        T: Fn(usize, <<V as View<'a>>::Iter as Iterator>::Item) + Send + Sync,
+        V: ReadOnly,
+        W: EntityStore,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.par_for_each_deterministic_unchecked(world, num_threads, f) };
+    }
+
+    /// Iterates through all entity data that matches the query in parallel, with a fixed
+    /// assignment of chunks to logical threads.
+    #[cfg(feature = "par-iter")]
+    pub fn par_for_each_deterministic_mut<'a, T, W>(
+        &'a self,
+        world: &'a mut W,
+        num_threads: usize,
+        f: T,
+    ) where
+        T: Fn(usize, <<V as View<'a>>::Iter as Iterator>::Item) + Send + Sync,
+        W: EntityStore,
+    {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.par_for_each_deterministic_unchecked(world, num_threads, f) };
+    }
+
+    /// Iterates through all entity data that matches the query in parallel, accumulating into
+    /// per-thread state. Does not perform static borrow checking.
+    ///
+    /// `init` is called to create a new accumulator whenever rayon begins work on a new batch of
+    /// chunks, so each accumulator is only ever accessed by one thread at a time. The accumulators
+    /// are returned for the caller to merge.
+    ///
+    /// # Safety
+    ///
+    /// The normal borrowing restrictions apply for the duration of the iteration:
+    /// * Components borrowed with `Read` access must not be borrowed mutably elsewhere.
+    /// * Components borrowed with `Write` access must not be borrowed elsewhere at all.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    #[cfg(feature = "par-iter")]
+    pub unsafe fn par_for_each_init_unchecked<'a, S, I, T, W>(
+        &'a self,
+        world: &'a W,
+        init: I,
+        f: T,
+    ) -> Vec<S>
+    where
+        S: Send,
+        I: Fn() -> S + Send + Sync,
+        T: Fn(&mut S, <<V as View<'a>>::Iter as Iterator>::Item) + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+        W: EntityStore,
+    {
+        let par_iter = self.par_iter_chunks_unchecked(world);
+        let states = ParallelIterator::fold(par_iter, &init, |mut state, mut chunk| {
+            for data in chunk.iter_mut() {
+                f(&mut state, data);
+            }
+            state
+        });
+        states.collect()
+    }
+
+    /// Iterates through all entity data that matches the query in parallel, accumulating into
+    /// per-thread state.
+    ///
+    /// `init` is called to create a new accumulator whenever rayon begins work on a new batch of
+    /// chunks, so each accumulator is only ever accessed by one thread at a time. The accumulators
+    /// are returned for the caller to merge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_core::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.insert((), (0..100).map(|i| (Position(i as f32),)));
+    ///
+    /// let query = Read::<Position>::query();
+    /// let sums = query.par_for_each_init(&world, || 0.0, |sum, pos| *sum += pos.0);
+    /// assert_eq!(4950.0, sums.iter().sum::<f32>());
+    /// ```
+    #[cfg(feature = "par-iter")]
+    pub fn par_for_each_init<'a, S, I, T, W>(&'a self, world: &'a W, init: I, f: T) -> Vec<S>
+    where
+        S: Send,
+        I: Fn() -> S + Send + Sync,
+        T: Fn(&mut S, <<V as View<'a>>::Iter as Iterator>::Item) + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+        V: ReadOnly,
+        W: EntityStore,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.par_for_each_init_unchecked(world, init, f) }
+    }
+
+    /// Iterates through all entity data that matches the query in parallel, accumulating into
+    /// per-thread state.
+    #[cfg(feature = "par-iter")]
+    pub fn par_for_each_init_mut<'a, S, I, T, W>(
+        &'a self,
+        world: &'a mut W,
+        init: I,
+        f: T,
+    ) -> Vec<S>
+    where
+        S: Send,
+        I: Fn() -> S + Send + Sync,
+        T: Fn(&mut S, <<V as View<'a>>::Iter as Iterator>::Item) + Send + Sync,
+        <F::ArchetypeFilter as Filter<ArchetypeFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunksetFilter as Filter<ChunksetFilterData<'a>>>::Iter: FissileIterator,
+        <F::ChunkFilter as Filter<ChunkFilterData<'a>>>::Iter: FissileIterator,
+        W: EntityStore,
+    {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.par_for_each_init_unchecked(world, init, f) }
+    }
+
     /// Iterates through all chunks that match the query in parallel.
     /// Does not perform static borrow checking.
     ///
@@ -1533,6 +2808,84 @@ where
     }
 }
 
+/// A query which yields the entities within each chunk ordered by their `K` component.
+/// See `Query::sort_chunks_by`.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "F: Clone"))]
+pub struct ChunkSortedQuery<V: for<'a> View<'a>, F: EntityFilter, K: Component + Ord> {
+    query: Query<V, F>,
+    _key: PhantomData<K>,
+}
+
+impl<V, F, K> ChunkSortedQuery<V, F, K>
+where
+    V: for<'a> View<'a>,
+    F: EntityFilter,
+    K: Component + Ord,
+{
+    /// Gets an iterator which iterates through all entity data that matches the query, and also yields the the `Entity` IDs.
+    /// Does not perform static borrow checking.
+    ///
+    /// # Safety
+    ///
+    /// The normal borrowing restrictions apply for the duration of the iteration:
+    /// * Components borrowed with `Read` access must not be borrowed mutably elsewhere.
+    /// * Components borrowed with `Write` access must not be borrowed elsewhere at all.
+    ///
+    /// # Panics
+    ///
+    /// This function may panic if other code is concurrently accessing the same components.
+    pub unsafe fn iter_entities_unchecked<'a, 'data, T: EntityStore>(
+        &'a self,
+        world: &'data T,
+    ) -> SortedChunkEntityIter<
+        'data,
+        V,
+        K,
+        ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>,
+    > {
+        SortedChunkEntityIter {
+            iter: self.query.iter_chunks_unchecked(world),
+            frontier: None,
+            _key: PhantomData,
+        }
+    }
+
+    /// Gets an iterator which iterates through all entity data that matches the query, and also yields the the `Entity` IDs.
+    pub fn iter_entities<'a, 'data, T: EntityStore>(
+        &'a self,
+        world: &'data T,
+    ) -> SortedChunkEntityIter<
+        'data,
+        V,
+        K,
+        ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>,
+    >
+    where
+        V: ReadOnly,
+    {
+        // safe because the view can only read data immutably
+        unsafe { self.iter_entities_unchecked(world) }
+    }
+
+    /// Gets an iterator which iterates through all entity data that matches the query, and also yields the the `Entity` IDs.
+    pub fn iter_entities_mut<'a, 'data, T: EntityStore>(
+        &'a self,
+        world: &'data mut T,
+    ) -> SortedChunkEntityIter<
+        'data,
+        V,
+        K,
+        ChunkViewIter<'data, 'a, V, F::ArchetypeFilter, F::ChunksetFilter, F::ChunkFilter>,
+    > {
+        // safe because the &mut World ensures exclusivity
+        unsafe { self.iter_entities_unchecked(world) }
+    }
+
+    /// Converts this back into an unsorted query.
+    pub fn into_inner(self) -> Query<V, F> { self.query }
+}
+
 /// An iterator over all chunks that match a given query.
 #[cfg(feature = "par-iter")]
 pub struct ChunkViewParIter<'data, 'filter, V, FArch, FChunkset, FChunk>
@@ -1551,6 +2904,7 @@ where
     chunkset_filter: &'filter FChunkset,
     chunk_filter: &'filter FChunk,
     archetypes: FissileEnumerate<FArch::Iter>,
+    include_disabled: bool,
     set_frontier: Option<(
         &'data ArchetypeData,
         FissileEnumerate<FChunkset::Iter>,
@@ -1594,6 +2948,18 @@ where
                 match self.archetypes.next() {
                     Some((arch_index, arch_data)) => {
                         if self.arch_filter.is_match(&arch_data).is_pass() {
+                            if !self.include_disabled
+                                && unsafe {
+                                    self.storage
+                                        .inner()
+                                        .archetypes()
+                                        .get_unchecked(arch_index)
+                                        .is_disabled()
+                                }
+                            {
+                                continue;
+                            }
+
                             // validate that we are allowed to access this archetype
                             if !self
                                 .storage
@@ -1651,7 +3017,10 @@ where
                     if chunk_index < index_bound
                         && self.chunk_filter.is_match(&filter_data).is_pass()
                     {
-                        return Some(Chunk::new(arch, set_index, ChunkIndex(chunk_index)));
+                        return Some(
+                            Chunk::new(arch, set_index, ChunkIndex(chunk_index))
+                                .with_stats(self.storage.inner().access_stats()),
+                        );
                     }
                 }
             }
@@ -1716,6 +3085,7 @@ where
             chunkset_filter,
             chunk_filter,
             archetypes,
+            include_disabled,
             set_frontier,
             chunk_frontier,
         } = self;
@@ -1752,6 +3122,7 @@ where
             chunkset_filter,
             chunk_filter,
             archetypes: right_archetypes,
+            include_disabled,
             set_frontier: right_set,
             chunk_frontier: right_chunk,
         };
@@ -1767,6 +3138,7 @@ where
                     chunkset_filter,
                     chunk_filter,
                     archetypes: left_archetypes,
+                    include_disabled,
                     set_frontier: left_set,
                     chunk_frontier: left_chunk,
                 },
@@ -1,6 +1,6 @@
 use crate::{
-    entity::{Entity, EntityAllocator},
-    index::{ArchetypeIndex, ChunkIndex, SetIndex},
+    entity::{Entity, EntityAllocator, EntityLocation},
+    index::{ArchetypeIndex, ChunkIndex, ComponentIndex, SetIndex},
     storage::{
         ArchetypeData, ArchetypeDescription, Chunkset, ComponentMeta, ComponentTypeId, TagMeta,
         TagStorage, TagTypeId,
@@ -29,6 +29,10 @@ pub fn deserializable<'a, 'b, WD: WorldDeserializer>(
 
 /// Deserializes data into the provided World using the provided `serde::Deserializer`.
 /// The caller must provide an implementation for `WorldDeserializer`.
+///
+/// The world's entity location index is not part of the serialized data. Each entity's location
+/// is recorded as it is placed into its chunk, so the index is rebuilt during the load without
+/// a separate pass over the world.
 pub fn deserialize<'dd, 'a, 'b, WD: WorldDeserializer, D: Deserializer<'dd>>(
     world: &'a mut World,
     deserialize_impl: &'b WD,
@@ -632,6 +636,7 @@ impl<'de, 'a, 'b, WD: WorldDeserializer> DeserializeSeed<'de> for EntitiesDeseri
         )?;
         let archetype = &mut self.world.storage_mut().archetypes_mut()[self.archetype_idx];
         let mut chunk_ranges = Vec::new();
+        let mut locations = Vec::with_capacity(entities.len());
         let mut chunk_idx = archetype.get_free_chunk(self.chunkset_idx, entities.len());
         let mut entities_in_chunk = 0;
         for entity in entities {
@@ -649,10 +654,22 @@ impl<'de, 'a, 'b, WD: WorldDeserializer> DeserializeSeed<'de> for EntitiesDeseri
             };
             chunk.writer().get().0.push(entity);
             entities_in_chunk += 1;
+
+            // record the entity's location directly, rather than re-deriving it after load
+            locations.push((
+                entity,
+                EntityLocation::new(
+                    self.archetype_idx,
+                    self.chunkset_idx,
+                    chunk_idx,
+                    ComponentIndex(chunk.len() - 1),
+                ),
+            ));
         }
         if entities_in_chunk > 0 {
             chunk_ranges.push((chunk_idx, entities_in_chunk));
         }
+        self.world.set_entity_locations(locations);
         Ok(chunk_ranges)
     }
 }
@@ -747,3 +764,63 @@ impl<'de, 'a, 'b, WD: WorldDeserializer> DeserializeSeed<'de>
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::serialize::{
+        fixture::Fixture, registry::ComponentRegistry, ser::serializable_world,
+    };
+    use serde::Serialize;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    struct Id(u32);
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    struct Vel(f32, f32, f32);
+
+    fn fixture() -> Fixture<Id, Vel> {
+        let mut registry = ComponentRegistry::new();
+        registry.register::<Id>(1).unwrap();
+        registry.register::<Vel>(2).unwrap();
+        Fixture::new(registry)
+    }
+
+    fn derived_locations(world: &World) -> Vec<(Entity, EntityLocation)> {
+        world
+            .storage()
+            .archetypes()
+            .iter()
+            .enumerate()
+            .flat_map(|(i, archetype)| archetype.iter_entity_locations(ArchetypeIndex(i)))
+            .collect()
+    }
+
+    #[test]
+    fn deserialize_entity_locations() {
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+        world.insert((), (0..20_000).map(|i| (Id(i),)));
+        world.insert((), (20_000..30_000).map(|i| (Id(i), Vel(i as f32, 0., 0.))));
+
+        let json = serde_json::to_string(&serializable_world(&world, &fixture())).unwrap();
+
+        let mut loaded = universe.create_world();
+        let mut deserializer = serde_json::Deserializer::from_str(&json);
+        deserialize(&mut loaded, &fixture(), &mut deserializer).unwrap();
+
+        let locations = derived_locations(&loaded);
+        assert_eq!(30_000, locations.len());
+        for (entity, location) in locations {
+            assert_eq!(Some(location), loaded.get_entity_location(entity));
+
+            let id = loaded.get_component::<Id>(entity).unwrap().0;
+            if id >= 20_000 {
+                let vel = loaded.get_component::<Vel>(entity).unwrap();
+                assert_eq!(Vel(id as f32, 0., 0.), *vel);
+            } else {
+                assert!(loaded.get_component::<Vel>(entity).is_none());
+            }
+        }
+    }
+}
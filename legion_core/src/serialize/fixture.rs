@@ -0,0 +1,147 @@
+use crate::{
+    entity::{Entity, EntityAllocator},
+    serialize::{de::WorldDeserializer, registry::ComponentRegistry, ser::WorldSerializer},
+    storage::{
+        ArchetypeDescription, Component, ComponentMeta, ComponentResourceSet, ComponentTypeId,
+        TagMeta, TagStorage, TagTypeId,
+    },
+};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use std::{marker::PhantomData, ptr::NonNull};
+
+/// A `WorldSerializer` and `WorldDeserializer` for worlds containing the component types `A` and
+/// `B`, which are identified by their stable IDs in a `ComponentRegistry`.
+///
+/// Components which are not registered are skipped. Tags are not supported.
+pub(crate) struct Fixture<A, B> {
+    registry: ComponentRegistry,
+    components: PhantomData<fn() -> (A, B)>,
+}
+
+impl<A, B> Fixture<A, B> {
+    pub(crate) fn new(registry: ComponentRegistry) -> Self {
+        Self {
+            registry,
+            components: PhantomData,
+        }
+    }
+}
+
+fn write_components<T: Copy>(
+    data: Vec<T>,
+    get_next_storage_fn: &mut dyn FnMut() -> Option<(NonNull<u8>, usize)>,
+) {
+    let mut data = data.into_iter();
+    while let Some((ptr, len)) = get_next_storage_fn() {
+        for i in 0..len {
+            unsafe { (ptr.as_ptr() as *mut T).add(i).write(data.next().unwrap()) };
+        }
+    }
+}
+
+impl<A, B> WorldSerializer for Fixture<A, B>
+where
+    A: Component + Copy + Serialize,
+    B: Component + Copy + Serialize,
+{
+    fn can_serialize_tag(&self, _: &TagTypeId, _: &TagMeta) -> bool { false }
+
+    fn can_serialize_component(&self, ty: &ComponentTypeId, _: &ComponentMeta) -> bool {
+        self.registry.stable_id(*ty).is_some()
+    }
+
+    fn serialize_archetype_description<S: Serializer>(
+        &self,
+        serializer: S,
+        archetype_desc: &ArchetypeDescription,
+    ) -> Result<S::Ok, S::Error> {
+        self.registry
+            .serialize_archetype_components(serializer, archetype_desc)
+    }
+
+    fn serialize_components<S: Serializer>(
+        &self,
+        serializer: S,
+        component_type: &ComponentTypeId,
+        _: &ComponentMeta,
+        components: &ComponentResourceSet,
+    ) -> Result<S::Ok, S::Error> {
+        unsafe {
+            if *component_type == ComponentTypeId::of::<A>() {
+                components.data_slice::<A>().serialize(serializer)
+            } else {
+                components.data_slice::<B>().serialize(serializer)
+            }
+        }
+    }
+
+    fn serialize_tags<S: Serializer>(
+        &self,
+        serializer: S,
+        _: &TagTypeId,
+        _: &TagMeta,
+        _: &TagStorage,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_unit()
+    }
+
+    fn serialize_entities<S: Serializer>(
+        &self,
+        serializer: S,
+        entities: &[Entity],
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(entities.len() as u64)
+    }
+}
+
+impl<A, B> WorldDeserializer for Fixture<A, B>
+where
+    A: Component + Copy + DeserializeOwned,
+    B: Component + Copy + DeserializeOwned,
+{
+    fn deserialize_archetype_description<'de, D: Deserializer<'de>>(
+        &self,
+        deserializer: D,
+    ) -> Result<ArchetypeDescription, <D as Deserializer<'de>>::Error> {
+        let mut desc = ArchetypeDescription::default();
+        self.registry
+            .deserialize_archetype_components(deserializer, &mut desc)?;
+        Ok(desc)
+    }
+
+    fn deserialize_components<'de, D: Deserializer<'de>>(
+        &self,
+        deserializer: D,
+        component_type: &ComponentTypeId,
+        _: &ComponentMeta,
+        get_next_storage_fn: &mut dyn FnMut() -> Option<(NonNull<u8>, usize)>,
+    ) -> Result<(), <D as Deserializer<'de>>::Error> {
+        if *component_type == ComponentTypeId::of::<A>() {
+            write_components(Vec::<A>::deserialize(deserializer)?, get_next_storage_fn);
+        } else {
+            write_components(Vec::<B>::deserialize(deserializer)?, get_next_storage_fn);
+        }
+        Ok(())
+    }
+
+    fn deserialize_tags<'de, D: Deserializer<'de>>(
+        &self,
+        deserializer: D,
+        _: &TagTypeId,
+        _: &TagMeta,
+        _: &mut TagStorage,
+    ) -> Result<(), <D as Deserializer<'de>>::Error> {
+        <()>::deserialize(deserializer)
+    }
+
+    fn deserialize_entities<'de, D: Deserializer<'de>>(
+        &self,
+        deserializer: D,
+        entity_allocator: &EntityAllocator,
+        entities: &mut Vec<Entity>,
+    ) -> Result<(), <D as Deserializer<'de>>::Error> {
+        let count = u64::deserialize(deserializer)?;
+        entities.extend(entity_allocator.create_entities().take(count as usize));
+        Ok(())
+    }
+}
@@ -1,2 +1,5 @@
 pub mod de;
+#[cfg(test)]
+mod fixture;
+pub mod registry;
 pub mod ser;
@@ -0,0 +1,253 @@
+use crate::storage::{ArchetypeDescription, Component, ComponentMeta, ComponentTypeId};
+use fxhash::FxHashMap;
+use serde::{de, Deserialize, Deserializer, Serializer};
+use thiserror::Error;
+
+/// Assigns stable integer IDs to component types.
+///
+/// `ComponentTypeId` is derived from Rust's `TypeId`, which is not stable across builds or
+/// compiler versions, and so must not be written into serialized data. A `WorldSerializer`
+/// can instead write the stable ID of each component type with
+/// `serialize_archetype_components`, and a `WorldDeserializer` built with the same
+/// registrations can map those IDs back to the types of the running build with
+/// `deserialize_archetype_components`.
+///
+/// Only registered component types are written, so a `WorldSerializer` should only accept
+/// component types for which `stable_id` returns `Some` in `can_serialize_component`.
+#[derive(Default)]
+pub struct ComponentRegistry {
+    ids: FxHashMap<ComponentTypeId, u32>,
+    components: FxHashMap<u32, (ComponentTypeId, ComponentMeta)>,
+}
+
+impl ComponentRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self { Self::default() }
+
+    /// Registers `T` under the given stable ID.
+    ///
+    /// Returns an error, without registering anything, if either the ID or the component
+    /// type have already been registered.
+    pub fn register<T: Component>(&mut self, stable_id: u32) -> Result<(), RegistryError> {
+        let type_id = ComponentTypeId::of::<T>();
+        if self.components.contains_key(&stable_id) {
+            return Err(RegistryError::DuplicateId(stable_id));
+        }
+        if self.ids.contains_key(&type_id) {
+            return Err(RegistryError::DuplicateType(std::any::type_name::<T>()));
+        }
+
+        self.ids.insert(type_id, stable_id);
+        self.components
+            .insert(stable_id, (type_id, ComponentMeta::of::<T>()));
+        Ok(())
+    }
+
+    /// Gets the stable ID of the given component type, if it has been registered.
+    pub fn stable_id(&self, type_id: ComponentTypeId) -> Option<u32> {
+        self.ids.get(&type_id).copied()
+    }
+
+    /// Gets the component type and metadata registered under the given stable ID.
+    pub fn component(&self, stable_id: u32) -> Option<(ComponentTypeId, ComponentMeta)> {
+        self.components.get(&stable_id).copied()
+    }
+
+    /// Serializes the stable IDs of the registered component types in an archetype, in the order
+    /// in which they appear in the archetype description. Unregistered component types are
+    /// skipped.
+    pub fn serialize_archetype_components<S: Serializer>(
+        &self,
+        serializer: S,
+        description: &ArchetypeDescription,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            description
+                .components()
+                .iter()
+                .filter_map(|(type_id, _)| self.stable_id(*type_id)),
+        )
+    }
+
+    /// Deserializes the stable IDs written by `serialize_archetype_components`, registering the
+    /// corresponding component types with the given archetype description.
+    ///
+    /// Returns an error if any of the IDs have not been registered.
+    pub fn deserialize_archetype_components<'de, D: Deserializer<'de>>(
+        &self,
+        deserializer: D,
+        description: &mut ArchetypeDescription,
+    ) -> Result<(), D::Error> {
+        for stable_id in Vec::<u32>::deserialize(deserializer)? {
+            let (type_id, meta) = self
+                .component(stable_id)
+                .ok_or_else(|| de::Error::custom(RegistryError::UnknownId(stable_id)))?;
+            description.register_component_raw(type_id, meta);
+        }
+        Ok(())
+    }
+
+    /// Gets the number of registered component types.
+    pub fn len(&self) -> usize { self.ids.len() }
+
+    /// Determines if no component types have been registered.
+    pub fn is_empty(&self) -> bool { self.ids.is_empty() }
+}
+
+/// An error returned when registering a component type with a `ComponentRegistry`.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RegistryError {
+    #[error("stable component id {0} is already registered")]
+    DuplicateId(u32),
+    #[error("component type {0} is already registered")]
+    DuplicateType(&'static str),
+    #[error("stable component id {0} is not registered")]
+    UnknownId(u32),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use crate::serialize::{de::deserialize, fixture::Fixture, ser::serializable_world};
+    use serde::Serialize;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    struct Pos(f32, f32, f32);
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    struct Vel(f32, f32, f32);
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Unregistered(f32);
+
+    // stand-ins for `Pos` and `Vel` in another build, where their `TypeId`s differ
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    struct OtherPos(f32, f32, f32);
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    struct OtherVel(f32, f32, f32);
+
+    #[test]
+    fn world_round_trip() {
+        let mut registry = ComponentRegistry::new();
+        registry.register::<Pos>(1).unwrap();
+        registry.register::<Vel>(2).unwrap();
+
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+        world.insert((), (0..10).map(|i| (Pos(i as f32, 0., 0.),)));
+        world.insert(
+            (),
+            (0..5).map(|i| {
+                (
+                    Vel(0., i as f32, 0.),
+                    Pos(i as f32, 1., 0.),
+                    Unregistered(1.),
+                )
+            }),
+        );
+        let save = Fixture::<Pos, Vel>::new(registry);
+        let json = serde_json::to_string(&serializable_world(&world, &save)).unwrap();
+
+        // load into a build where the component types have different `TypeId`s
+        let mut other = ComponentRegistry::new();
+        other.register::<OtherVel>(2).unwrap();
+        other.register::<OtherPos>(1).unwrap();
+
+        let mut loaded = universe.create_world();
+        let mut deserializer = serde_json::Deserializer::from_str(&json);
+        let load = Fixture::<OtherPos, OtherVel>::new(other);
+        deserialize(&mut loaded, &load, &mut deserializer).unwrap();
+
+        let positions = Read::<OtherPos>::query()
+            .iter(&loaded)
+            .map(|pos| *pos)
+            .collect::<Vec<_>>();
+        let expected = (0..10)
+            .map(|i| OtherPos(i as f32, 0., 0.))
+            .chain((0..5).map(|i| OtherPos(i as f32, 1., 0.)))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, positions);
+
+        let moving = <(Read<OtherPos>, Read<OtherVel>)>::query()
+            .iter(&loaded)
+            .map(|(pos, vel)| (*pos, *vel))
+            .collect::<Vec<_>>();
+        let expected = (0..5)
+            .map(|i| (OtherPos(i as f32, 1., 0.), OtherVel(0., i as f32, 0.)))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, moving);
+    }
+
+    #[test]
+    fn unknown_stable_id() {
+        let mut desc = ArchetypeDescription::default();
+        let mut deserializer = serde_json::Deserializer::from_str("[1, 3]");
+        let mut registry = ComponentRegistry::new();
+        registry.register::<Pos>(1).unwrap();
+
+        let error = registry
+            .deserialize_archetype_components(&mut deserializer, &mut desc)
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("stable component id 3 is not registered"));
+    }
+
+    #[test]
+    fn archetype_round_trip() {
+        let mut registry = ComponentRegistry::new();
+        registry.register::<Pos>(1).unwrap();
+        registry.register::<Vel>(2).unwrap();
+
+        let mut description = ArchetypeDescription::default();
+        description.register_component::<Vel>();
+        description.register_component::<Pos>();
+
+        let ids = description
+            .components()
+            .iter()
+            .map(|(type_id, _)| registry.stable_id(*type_id).unwrap())
+            .collect::<Vec<_>>();
+        let serialized = serde_json::to_string(&ids).unwrap();
+
+        let mut other = ComponentRegistry::new();
+        other.register::<OtherVel>(2).unwrap();
+        other.register::<OtherPos>(1).unwrap();
+
+        let mut loaded = ArchetypeDescription::default();
+        for id in serde_json::from_str::<Vec<u32>>(&serialized).unwrap() {
+            let (type_id, meta) = other.component(id).unwrap();
+            loaded.register_component_raw(type_id, meta);
+        }
+
+        let types = loaded
+            .components()
+            .iter()
+            .map(|(type_id, _)| *type_id)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ComponentTypeId::of::<OtherVel>(),
+                ComponentTypeId::of::<OtherPos>()
+            ],
+            types
+        );
+        assert!(loaded.components()[1].1 == ComponentMeta::of::<OtherPos>());
+    }
+
+    #[test]
+    fn duplicate_registration() {
+        let mut registry = ComponentRegistry::new();
+        registry.register::<Pos>(1).unwrap();
+
+        assert_eq!(
+            Err(RegistryError::DuplicateId(1)),
+            registry.register::<Vel>(1)
+        );
+        assert!(matches!(
+            registry.register::<Pos>(2),
+            Err(RegistryError::DuplicateType(_))
+        ));
+        assert_eq!(1, registry.len());
+        assert_eq!(None, registry.component(2).map(|(type_id, _)| type_id));
+    }
+}
@@ -1,12 +1,17 @@
 use crate::{
     entity::Entity,
+    filter::EntityFilter,
+    query::{Query, ReadOnly, View},
     storage::{
         ArchetypeData, ArchetypeDescription, ComponentMeta, ComponentResourceSet, ComponentStorage,
         ComponentTypeId, TagMeta, TagStorage, TagTypeId,
     },
-    world::World,
+    world::{EntityStore, World},
+};
+use serde::{
+    ser::{SerializeSeq, SerializeStruct},
+    Serialize, Serializer,
 };
-use serde::{ser::SerializeStruct, Serialize, Serializer};
 
 /// Implements `Serialize` and can be passed to a `serde::Serializer`.
 pub struct WorldSerializable<'a, 'b, CS: WorldSerializer> {
@@ -25,6 +30,58 @@ pub fn serializable_world<'a, 'b, CS: WorldSerializer>(
     }
 }
 
+/// Implements `Serialize` for the results of a query, and can be passed to a `serde::Serializer`.
+pub struct QuerySerializable<'a, 'data, V, F, W>
+where
+    V: for<'v> View<'v>,
+    F: EntityFilter,
+{
+    query: &'a Query<V, F>,
+    world: &'data W,
+}
+
+/// Returns a value which serializes the data of each entity matching a query as a sequence.
+///
+/// Entity data is written to the serializer chunk by chunk as it is iterated, without first
+/// being collected. Passing the value to a streaming serializer, such as
+/// `serde_json::to_writer`, allows large query results to be exported with little memory.
+pub fn serializable_query<'a, 'data, V, F, W>(
+    query: &'a Query<V, F>,
+    world: &'data W,
+) -> QuerySerializable<'a, 'data, V, F, W>
+where
+    V: for<'v> View<'v> + ReadOnly,
+    F: EntityFilter,
+    W: EntityStore,
+{
+    QuerySerializable { query, world }
+}
+
+impl<'a, 'data, V, F, W> Serialize for QuerySerializable<'a, 'data, V, F, W>
+where
+    V: for<'v> View<'v> + ReadOnly,
+    F: EntityFilter,
+    W: EntityStore,
+    <<V as View<'data>>::Iter as Iterator>::Item: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // run the query once, as stateful filters such as `changed` may match different chunks
+        // on a second run
+        let chunks = self.query.iter_chunks(self.world).collect::<Vec<_>>();
+        let len = chunks.iter().map(|chunk| chunk.entities().len()).sum();
+        let mut seq = serializer.serialize_seq(Some(len))?;
+        for mut chunk in chunks {
+            for item in chunk.iter_mut() {
+                seq.serialize_element(&item)?;
+            }
+        }
+        seq.end()
+    }
+}
+
 /*
 // Structure optimized for saving and loading:
 [
@@ -307,3 +364,52 @@ impl<'a, 'b, CS: WorldSerializer> Serialize for EntitySerializer<'a, 'b, CS> {
             .serialize_entities(serializer, self.entities)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+    use serde::Deserialize;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    struct Pos(f32, f32, f32);
+    #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+    struct Vel(f32, f32, f32);
+
+    #[test]
+    fn serialize_query() {
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+        world.insert(
+            (),
+            (0..1000).map(|i| (Pos(i as f32, 0., 0.), Vel(0., i as f32, 0.))),
+        );
+        world.insert((), (0..10).map(|i| (Pos(i as f32, 1., 1.),)));
+
+        let query = <(Read<Pos>, Read<Vel>)>::query();
+        let mut out = Vec::new();
+        serde_json::to_writer(&mut out, &serializable_query(&query, &world)).unwrap();
+
+        let loaded: Vec<(Pos, Vel)> = serde_json::from_slice(&out).unwrap();
+        let expected = query
+            .iter(&world)
+            .map(|(pos, vel)| (*pos, *vel))
+            .collect::<Vec<_>>();
+        assert_eq!(1000, loaded.len());
+        assert_eq!(expected, loaded);
+    }
+
+    #[test]
+    fn serialize_changed_query() {
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+        world.insert((), (0..10).map(|i| (Pos(i as f32, 0., 0.),)));
+
+        let query = Read::<Pos>::query().filter(changed::<Pos>());
+        let mut out = Vec::new();
+        serde_json::to_writer(&mut out, &serializable_query(&query, &world)).unwrap();
+
+        let loaded: Vec<Pos> = serde_json::from_slice(&out).unwrap();
+        assert_eq!(10, loaded.len());
+    }
+}
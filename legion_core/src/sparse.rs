@@ -0,0 +1,66 @@
+use crate::entity::Entity;
+use crate::storage::{Component, ComponentTypeId};
+use downcast_rs::{impl_downcast, Downcast};
+use fxhash::FxHashMap;
+
+/// A side table holding all components of a single type which are stored outside of archetypes.
+trait SparseColumn: Downcast + Send + Sync {
+    /// Drops the entity's component, if it has one.
+    fn remove(&mut self, entity: Entity);
+
+    /// Drops all components in the column.
+    fn clear(&mut self);
+}
+
+impl_downcast!(SparseColumn);
+
+impl<T: Component> SparseColumn for FxHashMap<Entity, T> {
+    fn remove(&mut self, entity: Entity) { FxHashMap::remove(self, &entity); }
+
+    fn clear(&mut self) { FxHashMap::clear(self); }
+}
+
+/// Stores sparse components, keyed by component type and then by entity.
+///
+/// Adding or removing a sparse component does not move the entity between archetypes.
+#[derive(Default)]
+pub(crate) struct SparseStorage {
+    columns: FxHashMap<ComponentTypeId, Box<dyn SparseColumn>>,
+}
+
+impl SparseStorage {
+    pub fn column<T: Component>(&self) -> Option<&FxHashMap<Entity, T>> {
+        self.columns
+            .get(&ComponentTypeId::of::<T>())
+            .and_then(|column| column.downcast_ref())
+    }
+
+    pub fn column_mut<T: Component>(&mut self) -> Option<&mut FxHashMap<Entity, T>> {
+        self.columns
+            .get_mut(&ComponentTypeId::of::<T>())
+            .and_then(|column| column.downcast_mut())
+    }
+
+    pub fn insert<T: Component>(&mut self, entity: Entity, component: T) -> Option<T> {
+        self.columns
+            .entry(ComponentTypeId::of::<T>())
+            .or_insert_with(|| Box::new(FxHashMap::<Entity, T>::default()))
+            .downcast_mut::<FxHashMap<Entity, T>>()
+            .unwrap()
+            .insert(entity, component)
+    }
+
+    /// Drops all of the entity's sparse components.
+    pub fn remove_entity(&mut self, entity: Entity) {
+        for column in self.columns.values_mut() {
+            column.remove(entity);
+        }
+    }
+
+    /// Drops all sparse components.
+    pub fn clear(&mut self) {
+        for column in self.columns.values_mut() {
+            column.clear();
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use crate::query::View;
+use crate::storage::{ComponentStorage, ComponentTypeId};
+use fxhash::FxHashMap;
+use parking_lot::Mutex;
+
+/// The number of times a component type has been read and written.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ComponentAccessCount {
+    /// The number of component values read.
+    pub reads: u64,
+    /// The number of component values written.
+    pub writes: u64,
+}
+
+/// Records how many component values of each type have been accessed, for profiling.
+///
+/// Queries count one access per entity in each chunk that they fetch data from, and
+/// `get_component` and `get_component_mut` count one access per call.
+///
+/// Statistics are only recorded once enabled with `World::enable_access_stats`.
+#[derive(Default, Debug)]
+pub struct AccessStats {
+    counts: Mutex<FxHashMap<ComponentTypeId, ComponentAccessCount>>,
+}
+
+impl AccessStats {
+    /// Gets the access counts of the given component type.
+    pub fn get(&self, type_id: ComponentTypeId) -> ComponentAccessCount {
+        self.counts
+            .lock()
+            .get(&type_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Gets the access counts of all component types which have been accessed.
+    pub fn counts(&self) -> Vec<(ComponentTypeId, ComponentAccessCount)> {
+        self.counts
+            .lock()
+            .iter()
+            .map(|(type_id, count)| (*type_id, *count))
+            .collect()
+    }
+
+    /// Resets all counters to zero.
+    pub fn reset(&self) { self.counts.lock().clear(); }
+
+    pub(crate) fn record_read(&self, type_id: ComponentTypeId, count: usize) {
+        self.counts.lock().entry(type_id).or_default().reads += count as u64;
+    }
+
+    pub(crate) fn record_write(&self, type_id: ComponentTypeId, count: usize) {
+        self.counts.lock().entry(type_id).or_default().writes += count as u64;
+    }
+
+    /// Records the accesses made by view `V` when fetching data from a chunk.
+    pub(crate) fn record_view<V: for<'a> View<'a>>(&self, chunk: &ComponentStorage) {
+        let count = chunk.len() as u64;
+        let permissions = V::requires_permissions();
+        let mut counts = self.counts.lock();
+        for type_id in permissions.read_only() {
+            if chunk.components(*type_id).is_some() {
+                counts.entry(*type_id).or_default().reads += count;
+            }
+        }
+        for type_id in permissions.writes() {
+            if chunk.components(*type_id).is_some() {
+                counts.entry(*type_id).or_default().writes += count;
+            }
+        }
+    }
+}
@@ -15,12 +15,14 @@ use crate::index::ComponentIndex;
 use crate::index::SetIndex;
 use crate::iterator::FissileZip;
 use crate::iterator::SliceVecIter;
+use crate::stats::AccessStats;
 use crate::world::TagSet;
 use crate::world::WorldId;
 use derivative::Derivative;
 use fxhash::FxHashMap;
+use parking_lot::Mutex;
 use smallvec::SmallVec;
-use std::any::TypeId;
+use std::any::{Any, TypeId};
 use std::cell::UnsafeCell;
 use std::fmt::Debug;
 use std::fmt::{Display, Formatter};
@@ -43,6 +45,11 @@ fn next_version() -> u64 {
         .unwrap()
 }
 
+/// Gets the most recently allocated component version.
+///
+/// Any component modified after this call will be given a greater version.
+pub fn current_version() -> u64 { VERSION_COUNTER.load(Ordering::Relaxed) }
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct ComponentTypeId {
     type_id: TypeId,
@@ -105,11 +112,50 @@ impl TagTypeId {
 pub trait Component: Send + Sync + 'static {}
 
 /// A `Tag` is shared data that can be attached to multiple entities at once.
+///
+/// Entities are grouped into chunks by their tag values, so every distinct value creates a new chunk.
+/// Floating point values make poor tags: each distinct float produces its own chunk, and `NaN` is
+/// not equal to itself, which breaks tag matching. Debug builds assert that tag values are equal to
+/// themselves when they are inserted.
 pub trait Tag: Clone + Send + Sync + PartialEq + 'static {}
 
 impl<T: Send + Sync + 'static> Component for T {}
 impl<T: Clone + Send + Sync + PartialEq + 'static> Tag for T {}
 
+/// A tag whose value is an identifier assigned at runtime, rather than a distinct Rust type.
+///
+/// This allows data-driven content to define arbitrary groupings of entities without declaring new
+/// tag types. Entities can be selected by dynamic tag with the `tag_value` filter.
+///
+/// # Examples
+///
+/// ```
+/// # use legion_core::prelude::*;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Position(f32);
+/// # let universe = Universe::new();
+/// # let mut world = universe.create_world();
+/// world.insert((DynamicTag(7),), vec![(Position(1.0),)]);
+///
+/// let query = <Read<Position>>::query().filter(tag_value(&DynamicTag(7)));
+/// assert_eq!(query.iter(&world).count(), 1);
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct DynamicTag(pub u64);
+
+/// A marker tag attached to entities which have been disabled with `World::set_enabled`.
+///
+/// Queries skip disabled entities unless constructed with `Query::include_disabled`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Disabled;
+
+impl ArchetypeData {
+    /// Determines if the entities in this archetype have been disabled.
+    pub(crate) fn is_disabled(&self) -> bool {
+        self.tags().get(TagTypeId::of::<Disabled>()).is_some()
+    }
+}
+
 /// Stores slices of `ComponentTypeId`, each of which identifies the type of components
 /// contained within the archetype of the same index.
 #[derive(Derivative)]
@@ -171,6 +217,12 @@ impl<T> SliceVec<T> {
         self.counts.push(count);
     }
 
+    /// Removes all slices from the vector.
+    pub(crate) fn clear(&mut self) {
+        self.data.clear();
+        self.counts.clear();
+    }
+
     /// Gets an iterator over all slices in the vector.
     pub fn iter(&self) -> SliceVecIter<T> {
         SliceVecIter {
@@ -187,6 +239,7 @@ pub struct Storage {
     tag_types: TagTypes,
     archetypes: Vec<ArchetypeData>,
     subscribers: Subscribers,
+    access_stats: Option<AccessStats>,
 }
 
 impl Storage {
@@ -198,9 +251,17 @@ impl Storage {
             tag_types: TagTypes::default(),
             archetypes: Vec::default(),
             subscribers: Subscribers::default(),
+            access_stats: None,
         }
     }
 
+    /// Gets the component access statistics of the storage, if they are enabled.
+    pub fn access_stats(&self) -> Option<&AccessStats> { self.access_stats.as_ref() }
+
+    pub(crate) fn set_access_stats(&mut self, stats: Option<AccessStats>) {
+        self.access_stats = stats;
+    }
+
     pub(crate) fn subscribe<T: EntityFilter + Sync + 'static>(
         &mut self,
         sender: crossbeam_channel::Sender<Event>,
@@ -278,6 +339,40 @@ impl Storage {
     /// Gets a mutable slice reference to all archetypes.
     pub fn archetypes_mut(&mut self) -> &mut [ArchetypeData] { &mut self.archetypes }
 
+    /// Drops all archetypes which do not contain any entities.
+    ///
+    /// The remaining archetypes keep their relative order, and are moved down to fill the
+    /// indices of those removed. Returns the number of archetypes removed.
+    pub(crate) fn remove_empty_archetypes(&mut self) -> usize {
+        let before = self.archetypes.len();
+        self.archetypes.retain(|archetype| {
+            archetype
+                .chunksets()
+                .iter()
+                .any(|set| !set.occupied().is_empty())
+        });
+
+        self.component_types.0.clear();
+        self.tag_types.0.clear();
+        for (i, archetype) in self.archetypes.iter_mut().enumerate() {
+            let desc = archetype.description();
+            self.component_types
+                .0
+                .push(desc.components.iter().map(|&(t, _)| t));
+            self.tag_types.0.push(desc.tags.iter().map(|&(t, _)| t));
+            archetype.set_index(ArchetypeIndex(i));
+        }
+
+        before - self.archetypes.len()
+    }
+
+    /// Drops all archetypes, along with any entities they contain.
+    pub(crate) fn clear(&mut self) {
+        self.archetypes.clear();
+        self.component_types.0.clear();
+        self.tag_types.0.clear();
+    }
+
     pub(crate) fn drain<R: RangeBounds<usize>>(
         &mut self,
         range: R,
@@ -416,6 +511,12 @@ impl ArchetypeDescription {
     /// Gets a slice of the components in the description.
     pub fn components(&self) -> &[(ComponentTypeId, ComponentMeta)] { &self.components }
 
+    /// Gets the type names of the tags in the description.
+    pub fn tag_names(&self) -> &[&'static str] { &self.tag_names }
+
+    /// Gets the type names of the components in the description.
+    pub fn component_names(&self) -> &[&'static str] { &self.component_names }
+
     /// Adds a tag to the description.
     pub fn register_tag_raw(&mut self, type_id: TagTypeId, type_meta: TagMeta) {
         self.tags.push((type_id, type_meta));
@@ -624,6 +725,7 @@ pub struct ArchetypeData {
     component_layout: ComponentStorageLayout,
     chunk_sets: Vec<Chunkset>,
     subscribers: Subscribers,
+    insert_hint: SetIndex,
 }
 
 impl ArchetypeData {
@@ -647,8 +749,11 @@ impl ArchetypeData {
             MAX_CHUNK_SIZE / std::cmp::max(max_component_size, size_of::<Entity>()),
         );
         let mut data_capacity = 0usize;
+        let mut data_align = COMPONENT_STORAGE_ALIGNMENT;
         let mut component_data_offsets = Vec::new();
         for &(type_id, meta) in desc.components.iter() {
+            // over-aligned components require the whole allocation to share their alignment
+            data_align = std::cmp::max(data_align, meta.align);
             data_capacity = align_up(
                 align_up(data_capacity, COMPONENT_STORAGE_ALIGNMENT),
                 meta.align,
@@ -656,9 +761,8 @@ impl ArchetypeData {
             component_data_offsets.push((type_id, data_capacity, meta));
             data_capacity += meta.size * entity_capacity;
         }
-        let data_alignment =
-            std::alloc::Layout::from_size_align(data_capacity, COMPONENT_STORAGE_ALIGNMENT)
-                .expect("invalid component data size/alignment");
+        let data_alignment = std::alloc::Layout::from_size_align(data_capacity, data_align)
+            .expect("invalid component data size/alignment");
 
         ArchetypeData {
             desc,
@@ -671,9 +775,17 @@ impl ArchetypeData {
             },
             chunk_sets: Vec::new(),
             subscribers: Subscribers::default(),
+            insert_hint: SetIndex(0),
         }
     }
 
+    /// Gets the index of the chunk set which entities were most recently inserted into.
+    ///
+    /// The index may be stale, and so must be checked against the desired tag values.
+    pub(crate) fn insert_hint(&self) -> SetIndex { self.insert_hint }
+
+    pub(crate) fn set_insert_hint(&mut self, set: SetIndex) { self.insert_hint = set; }
+
     pub(crate) fn delete_all(&mut self) {
         for set in &mut self.chunk_sets {
             // Clearing the chunk will Drop all the data
@@ -711,6 +823,17 @@ impl ArchetypeData {
     /// Gets the unique ID of this archetype.
     pub fn id(&self) -> ArchetypeId { self.id }
 
+    /// Moves the archetype to a new index within its storage, updating the IDs of the archetype
+    /// and all of its chunks.
+    fn set_index(&mut self, index: ArchetypeIndex) {
+        self.id = ArchetypeId(self.id.world(), index);
+        for set in &mut self.chunk_sets {
+            for chunk in &mut set.chunks {
+                chunk.id = ChunkId(self.id, chunk.id.set(), chunk.id.index());
+            }
+        }
+    }
+
     fn find_chunk_set_by_tags(
         &self,
         other_tags: &Tags,
@@ -1133,18 +1256,20 @@ impl ArchetypeData {
         set_index: SetIndex,
         minimum_space: usize,
     ) -> ChunkIndex {
-        let count = {
-            let chunks = &mut self.chunk_sets[set_index];
-            let len = chunks.len();
-            for (i, chunk) in chunks.iter_mut().enumerate() {
-                let space_left = chunk.capacity() - chunk.len();
-                if space_left >= minimum_space {
-                    return ChunkIndex(i);
-                }
+        let chunks = &mut self.chunk_sets[set_index];
+        for (i, chunk) in chunks.iter_mut().enumerate() {
+            let space_left = chunk.capacity() - chunk.len();
+            if space_left >= minimum_space {
+                return ChunkIndex(i);
             }
-            ChunkIndex(len)
-        };
+        }
+
+        self.alloc_chunk(set_index)
+    }
 
+    /// Allocates a new empty chunk in the given chunk set.
+    pub(crate) fn alloc_chunk(&mut self, set_index: SetIndex) -> ChunkIndex {
+        let count = ChunkIndex(self.chunk_sets[set_index].len());
         let chunk = self
             .component_layout
             .alloc_storage(ChunkId(self.id, set_index, count));
@@ -1282,6 +1407,8 @@ impl ComponentStorageLayout {
                         element_size: meta.size,
                         drop_fn: meta.drop_fn,
                         version: UnsafeCell::new(0),
+                        bulk_version: UnsafeCell::new(0),
+                        element_versions: UnsafeCell::new(None),
                     },
                 )
             })
@@ -1300,6 +1427,7 @@ impl ComponentStorageLayout {
             component_info: UnsafeCell::new(Components::new(storage_info)),
             component_data: None,
             subscribers: Subscribers::default(),
+            metadata: Mutex::new(FxHashMap::default()),
         }
     }
 }
@@ -1580,8 +1708,12 @@ pub struct ComponentStorage {
     component_info: UnsafeCell<Components>,
     component_data: Option<NonNull<u8>>,
     subscribers: Subscribers,
+    metadata: Mutex<FxHashMap<TypeId, (ChunkGeneration, Box<dyn Any + Send + Sync>)>>,
 }
 
+/// Identifies the state of a chunk's contents; any write to the chunk produces a new generation.
+type ChunkGeneration = (u64, usize);
+
 pub struct StorageWriter<'a> {
     initial_count: usize,
     storage: &'a mut ComponentStorage,
@@ -1617,6 +1749,32 @@ impl ComponentStorage {
     /// Determines if the chunk is full.
     pub fn is_full(&self) -> bool { self.len() >= self.capacity }
 
+    /// Runs `f` with the chunk's capacity temporarily reduced to at most `limit`, so that
+    /// component sources writing into the chunk stop once `limit` entities are stored.
+    pub(crate) fn with_capacity_limit<R, F: FnOnce(&mut Self) -> R>(
+        &mut self,
+        limit: usize,
+        f: F,
+    ) -> R {
+        // restores the capacity even if `f` panics
+        struct CapacityGuard<'a> {
+            chunk: &'a mut ComponentStorage,
+            capacity: usize,
+        }
+
+        impl<'a> Drop for CapacityGuard<'a> {
+            fn drop(&mut self) { self.chunk.capacity = self.capacity; }
+        }
+
+        let capacity = self.capacity;
+        self.capacity = std::cmp::min(capacity, limit);
+        let guard = CapacityGuard {
+            chunk: self,
+            capacity,
+        };
+        f(guard.chunk)
+    }
+
     /// Determines if the chunk is empty.
     pub fn is_empty(&self) -> bool { self.entities.len() == 0 }
 
@@ -1634,11 +1792,73 @@ impl ComponentStorage {
     /// Gets a slice reference containing the IDs of all entities stored in the chunk.
     pub fn entities(&self) -> &[Entity] { self.entities.as_slice() }
 
+    /// Overwrites all of the chunk's `T` components with a copy of `data`.
+    ///
+    /// Returns `false`, without writing anything, if the chunk does not contain `T`
+    /// components or if `data` is not the same length as the chunk.
+    ///
+    /// # Panics
+    ///
+    /// This method performs runtime borrow checking. It will panic if
+    /// any other code is concurrently accessing the component slice.
+    pub(crate) fn overwrite_components<T: Component + Copy>(&self, data: &[T]) -> bool {
+        if data.len() != self.len() {
+            return false;
+        }
+
+        match self.components(ComponentTypeId::of::<T>()) {
+            Some(components) => {
+                unsafe { components.data_slice_mut::<T>() }.copy_from_slice(data);
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Gets a component accessor for the specified component type.
     pub fn components(&self, component_type: ComponentTypeId) -> Option<&ComponentResourceSet> {
         unsafe { &*self.component_info.get() }.get(component_type)
     }
 
+    fn generation(&self) -> ChunkGeneration {
+        let version = self
+            .component_offsets
+            .keys()
+            .filter_map(|type_id| self.components(*type_id))
+            .map(|components| components.version())
+            .max()
+            .unwrap_or(0);
+        (version, self.len())
+    }
+
+    /// Gets a copy of the user metadata of type `T` stored on the chunk.
+    ///
+    /// Returns `None` if no `T` metadata has been stored, or if the chunk's components have been
+    /// borrowed mutably, or entities added or removed, since it was stored.
+    pub fn metadata<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+        let generation = self.generation();
+        let mut metadata = self.metadata.lock();
+        let type_id = TypeId::of::<T>();
+        match metadata.get(&type_id) {
+            Some((stored, value)) if *stored == generation => value.downcast_ref::<T>().cloned(),
+            Some(_) => {
+                metadata.remove(&type_id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Stores user metadata of type `T` on the chunk, replacing any previous `T` metadata.
+    ///
+    /// The metadata is invalidated the next time the chunk is written to.
+    pub fn set_metadata<T: Send + Sync + 'static>(&self, value: T) {
+        let generation = self.generation();
+        self.metadata
+            .lock()
+            .insert(TypeId::of::<T>(), (generation, Box::new(value)));
+    }
+
     /// Increments all component versions, forcing the chunk to be seen as modified for all queries.
     fn mark_modified(&mut self) {
         unsafe {
@@ -1728,6 +1948,62 @@ impl ComponentStorage {
         removed
     }
 
+    /// Moves the last `count` entities in this chunk into the end of a target chunk, copying
+    /// each compatible component type in bulk. Any components left over will be dropped.
+    pub(crate) fn move_entities(&mut self, target: &mut ComponentStorage, count: usize) {
+        debug_assert!(count <= self.len());
+        debug_assert!(target.len() + count <= target.capacity());
+        if !target.is_allocated() {
+            target.allocate();
+        }
+
+        trace!(count, source = ?self.id, destination = ?target.id, "Moving entities");
+
+        let start = self.len() - count;
+        let self_components = unsafe { &mut *self.component_info.get() };
+        let target_components = unsafe { &mut *target.component_info.get() };
+
+        for (comp_type, accessor) in self_components.iter_mut() {
+            if let Some(target_accessor) = target_components.get_mut(*comp_type) {
+                // move the components into the target chunk
+                unsafe {
+                    let (ptr, element_size, _) = accessor.data_raw();
+                    let components = ptr.add(element_size * start);
+                    target_accessor
+                        .writer()
+                        .push_raw(NonNull::new_unchecked(components), count);
+                }
+            } else {
+                // drop the components rather than move them
+                let mut writer = accessor.writer();
+                for index in start..start + count {
+                    unsafe { writer.drop_in_place(ComponentIndex(index)) };
+                }
+            }
+
+            // forget the moved components
+            let mut writer = accessor.writer();
+            for index in (start..start + count).rev() {
+                writer.swap_remove(index, false);
+            }
+        }
+
+        let id = self.id();
+        for entity in self.entities.drain(start..) {
+            target.entities.push(entity);
+            self.subscribers.send(Event::EntityRemoved(entity, id));
+            target
+                .subscribers
+                .send(Event::EntityInserted(entity, target.id()));
+        }
+
+        self.update_count_gauge();
+        target.update_count_gauge();
+        if self.is_empty() {
+            self.free();
+        }
+    }
+
     /// Gets mutable references to the internal data of the chunk.
     pub fn writer(&mut self) -> StorageWriter {
         if !self.is_allocated() {
@@ -1879,12 +2155,61 @@ pub struct ComponentResourceSet {
     capacity: usize,
     drop_fn: Option<fn(*mut u8)>,
     version: UnsafeCell<u64>,
+    bulk_version: UnsafeCell<u64>,
+    element_versions: UnsafeCell<Option<Vec<u64>>>,
 }
 
 impl ComponentResourceSet {
     /// Gets the version of the component slice.
     pub fn version(&self) -> u64 { unsafe { *self.version.get() } }
 
+    /// Gets the version at which the component at `index` was last modified.
+    ///
+    /// Components are only versioned individually once the slice has been fetched through a
+    /// `Tracked` view; until then, every component reports the version of the whole slice.
+    /// Mutably borrowing the whole slice counts as modifying every component within it.
+    pub fn element_version(&self, index: usize) -> Option<u64> {
+        unsafe {
+            if index >= *self.count.get() {
+                return None;
+            }
+
+            let version = match &*self.element_versions.get() {
+                Some(versions) => std::cmp::max(versions[index], *self.bulk_version.get()),
+                None => *self.version.get(),
+            };
+            Some(version)
+        }
+    }
+
+    /// Starts versioning each component in the slice individually. Components already in the
+    /// slice are given the version of the whole slice.
+    ///
+    /// # Safety
+    ///
+    /// The caller must hold an exclusive borrow of the component slice.
+    pub(crate) unsafe fn track_elements(&self) {
+        let versions = &mut *self.element_versions.get();
+        if versions.is_none() {
+            let mut tracked = Vec::with_capacity(self.capacity);
+            tracked.resize(*self.count.get(), *self.version.get());
+            *versions = Some(tracked);
+        }
+    }
+
+    /// Stamps a new version onto the component at `index`, marking it as modified.
+    ///
+    /// # Safety
+    ///
+    /// The caller must hold an exclusive borrow of the component slice.
+    pub(crate) unsafe fn touch_element(&self, index: usize) {
+        let version = next_version();
+        if let Some(versions) = &mut *self.element_versions.get() {
+            versions[index] = version;
+        }
+        *self.version.get() = version;
+    }
+
     /// Gets a raw pointer to the start of the component slice.
     ///
     /// Returns a tuple containing `(pointer, element_size, count)`.
@@ -1914,7 +2239,9 @@ impl ComponentResourceSet {
         // this version increment is not thread safe
         // - but the pointer `get_mut` ensures exclusive access at runtime
         let ptr = self.ptr.get_mut();
-        *self.version.get() = next_version();
+        let version = next_version();
+        *self.version.get() = version;
+        *self.bulk_version.get() = version;
         (ptr, self.element_size, *self.count.get())
     }
 
@@ -1949,6 +2276,32 @@ impl ComponentResourceSet {
         ptr.map_into(|&mut ptr| std::slice::from_raw_parts_mut(ptr as *mut _ as *mut T, count))
     }
 
+    /// Gets a mutable reference to the slice of components without marking any of them as
+    /// modified. Modified components must be individually marked with `touch_element`.
+    ///
+    /// # Safety
+    ///
+    /// Ensure that `T` is representative of the component data actually stored.
+    ///
+    /// Access to the component data within the slice is runtime borrow checked.
+    /// This call will panic if borrowing rules are broken.
+    #[allow(clippy::mut_from_ref)]
+    pub(crate) unsafe fn data_slice_mut_tracked<T>(&self) -> RefMapMut<'_, &mut [T]> {
+        let count = *self.count.get();
+        self.ptr
+            .get_mut()
+            .map_into(|&mut ptr| std::slice::from_raw_parts_mut(ptr as *mut _ as *mut T, count))
+    }
+
+    /// Records a new version for the slice and for the `count` components just pushed onto it.
+    unsafe fn mark_pushed(&self, count: usize) {
+        let version = next_version();
+        *self.version.get() = version;
+        if let Some(versions) = &mut *self.element_versions.get() {
+            versions.resize(versions.len() + count, version);
+        }
+    }
+
     /// Creates a writer for pushing components into or removing from the vec.
     pub fn writer(&mut self) -> ComponentWriter { ComponentWriter::new(self) }
 }
@@ -2000,7 +2353,7 @@ impl<'a> ComponentWriter<'a> {
             .ptr
             .add(*self.accessor.count.get() * self.accessor.element_size);
         *self.accessor.count.get() += count;
-        *self.accessor.version.get() = next_version();
+        self.accessor.mark_pushed(count);
         NonNull::new_unchecked(ptr)
     }
 
@@ -2028,7 +2381,7 @@ impl<'a> ComponentWriter<'a> {
             count * self.accessor.element_size,
         );
         *self.accessor.count.get() += count;
-        *self.accessor.version.get() = next_version();
+        self.accessor.mark_pushed(count);
     }
 
     /// Pushes new components onto the end of the vec.
@@ -2065,6 +2418,9 @@ impl<'a> ComponentWriter<'a> {
             }
 
             *self.accessor.count.get() -= 1;
+            if let Some(versions) = &mut *self.accessor.element_versions.get() {
+                versions.swap_remove(index);
+            }
         }
     }
 
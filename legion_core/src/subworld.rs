@@ -1,11 +1,11 @@
 use crate::{
     borrow::{Ref, RefMut},
-    entity::Entity,
+    entity::{Entity, EntityLocation},
     filter::EntityFilter,
     index::ArchetypeIndex,
     permission::Permissions,
     prelude::Query,
-    query::View,
+    query::{DefaultFilter, IntoQuery, ReadOnly, View},
     storage::{Component, ComponentTypeId, Storage, Tag},
     world::{EntityStore, World},
 };
@@ -218,6 +218,26 @@ impl<'a> SubWorld<'a> {
         self.split::<V>()
     }
 
+    /// Constructs a read-only query which can be used for ad-hoc lookups within this world,
+    /// without needing to be declared ahead of time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the view reads any component which this world does not allow read access to.
+    pub fn query<V>(&self) -> Query<V, <V as DefaultFilter>::Filter>
+    where
+        V: IntoQuery + ReadOnly,
+    {
+        if !V::validate_access(&self.components) {
+            panic!("Attempted to construct a query which reads components that this system does not have declared access to. \
+                Consider adding a query which contains `{}` to the system, \
+                or use `SystemBuilder::read_component` to declare global access.",
+                std::any::type_name::<V>());
+        }
+
+        V::query()
+    }
+
     fn validate_archetype_access(&self, entity: Entity) -> bool {
         if let Some(archetypes) = self.archetypes {
             if let Some(location) = (*self.world).get_entity_location(entity) {
@@ -315,6 +335,11 @@ impl<'a> EntityStore for SubWorld<'a> {
     #[inline]
     fn is_alive(&self, entity: Entity) -> bool { self.world.is_alive(entity) }
 
+    #[inline]
+    fn get_entity_location(&self, entity: Entity) -> Option<EntityLocation> {
+        self.world.get_entity_location(entity)
+    }
+
     fn get_component_storage<V: for<'b> View<'b>>(
         &self,
     ) -> Result<StorageAccessor, ComponentAccessError> {
@@ -1,8 +1,10 @@
 use crate::borrow::Ref;
+use crate::borrow::RefMap;
 use crate::borrow::RefMut;
 use crate::entity::BlockAllocator;
 use crate::entity::Entity;
 use crate::entity::EntityAllocator;
+use crate::entity::EntityIndex;
 use crate::entity::EntityLocation;
 use crate::entity::Locations;
 use crate::event::Event;
@@ -10,16 +12,24 @@ use crate::filter::ArchetypeFilterData;
 use crate::filter::ChunksetFilterData;
 use crate::filter::EntityFilter;
 use crate::filter::Filter;
+use crate::filter::FilterResult;
 use crate::index::ArchetypeIndex;
+use crate::index::ChunkIndex;
 use crate::index::ComponentIndex;
 use crate::index::SetIndex;
 use crate::iterator::SliceVecIter;
+use crate::sparse::SparseStorage;
+use crate::stats::AccessStats;
 use crate::storage::ArchetypeData;
 use crate::storage::ArchetypeDescription;
+use crate::storage::ArchetypeId;
 use crate::storage::Component;
 use crate::storage::ComponentMeta;
 use crate::storage::ComponentStorage;
 use crate::storage::ComponentTypeId;
+use crate::storage::ComponentWriter;
+use crate::storage::Components;
+use crate::storage::Disabled;
 use crate::storage::Storage;
 use crate::storage::Tag;
 use crate::storage::TagMeta;
@@ -27,13 +37,17 @@ use crate::storage::TagTypeId;
 use crate::storage::Tags;
 use crate::{
     prelude::Query,
-    query::View,
+    query::{DefaultFilter, IntoQuery, Read, View, ViewSlices},
     subworld::{ComponentAccess, ComponentAccessError, StorageAccessor, SubWorld},
     tuple::TupleEq,
+    worldcell::WorldCell,
+    worldview::FrozenWorld,
 };
 use parking_lot::Mutex;
+use std::any::Any;
 use std::cell::UnsafeCell;
 use std::collections::HashMap;
+use std::fmt::{Debug, Formatter, Write};
 use std::iter::Enumerate;
 use std::iter::Fuse;
 use std::iter::FusedIterator;
@@ -49,6 +63,32 @@ use std::sync::Arc;
 use thiserror::Error;
 use tracing::{info, span, trace, Level};
 
+type DebugFn = unsafe fn(*const u8, &mut Formatter) -> std::fmt::Result;
+
+unsafe fn debug_fmt<T: Debug>(ptr: *const u8, f: &mut Formatter) -> std::fmt::Result {
+    (&*(ptr as *const T)).fmt(f)
+}
+
+struct DebugValue(DebugFn, *const u8);
+
+type VisitFn = unsafe fn(*const u8, &'static str, &mut dyn ComponentVisitor);
+
+unsafe fn visit_component<T: Component>(
+    ptr: *const u8,
+    name: &'static str,
+    visitor: &mut dyn ComponentVisitor,
+) {
+    visitor.visit(name, &*(ptr as *const T));
+}
+
+type DropHook = Box<dyn Fn(*mut u8) + Send + Sync>;
+type CloneHook = Box<dyn Fn(*const u8, &mut ComponentWriter) + Send + Sync>;
+type MigrateHook = Box<dyn Fn(Entity, &[ComponentTypeId], &[ComponentTypeId]) + Send + Sync>;
+
+impl Debug for DebugValue {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result { unsafe { (self.0)(self.1, f) } }
+}
+
 static NEXT_UNIVERSE_ID: AtomicUsize = AtomicUsize::new(1);
 static NEXT_WORLD_ID: AtomicUsize = AtomicUsize::new(0);
 
@@ -63,15 +103,55 @@ pub struct UniverseId(usize);
 pub struct Universe {
     id: UniverseId,
     allocator: Arc<Mutex<BlockAllocator>>,
+    lanes: Option<usize>,
+    worlds: AtomicUsize,
 }
 
 impl Universe {
     /// Creates a new `Universe`.
     #[allow(clippy::new_without_default)]
-    pub fn new() -> Self {
+    pub fn new() -> Self { Self::with_block_size(BlockAllocator::DEFAULT_BLOCK_SIZE) }
+
+    /// Creates a new `Universe` whose worlds allocate `Entity` IDs in blocks of `block_size`.
+    ///
+    /// Each world takes a block of IDs from the universe whenever it runs out. The default of
+    /// 1024 suits most uses, while smaller blocks avoid reserving unused IDs in worlds which only
+    /// ever contain a handful of entities, and larger blocks reduce contention between worlds
+    /// which insert in large batches.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is not a power of two.
+    pub fn with_block_size(block_size: usize) -> Self {
         Self {
             id: UniverseId(NEXT_UNIVERSE_ID.fetch_add(1, Ordering::SeqCst)),
-            allocator: Arc::new(Mutex::new(BlockAllocator::new())),
+            allocator: Arc::new(Mutex::new(BlockAllocator::new(block_size))),
+            lanes: None,
+            worlds: AtomicUsize::new(0),
+        }
+    }
+
+    /// Creates a new `Universe` in which the `Entity` IDs assigned by each world do not depend
+    /// upon the order in which worlds take blocks of IDs from the universe.
+    ///
+    /// The block indices are interleaved between up to `max_worlds` worlds, which are each given
+    /// a lane according to the order in which they are created with `create_world`. Each world
+    /// only takes blocks from its own lane, so a world is assigned the same IDs whenever it
+    /// performs the same insertions, regardless of what other worlds are doing concurrently.
+    /// This is useful for lockstep simulations spread across several worlds.
+    ///
+    /// Worlds must be created in a consistent order for their IDs to be reproducible. Lanes are
+    /// not reclaimed when a world is dropped, so at most `max_worlds` worlds can ever be created
+    /// within the universe.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is not a power of two, or if `max_worlds` is zero.
+    pub fn with_deterministic_blocks(block_size: usize, max_worlds: usize) -> Self {
+        assert!(max_worlds > 0, "a universe must support at least one world");
+        Self {
+            lanes: Some(max_worlds),
+            ..Self::with_block_size(block_size)
         }
     }
 
@@ -79,13 +159,76 @@ impl Universe {
     ///
     /// Entities inserted into worlds created within the same universe are guarenteed to have
     /// unique `Entity` IDs, even across worlds. See also `World::new`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the universe was created with `with_deterministic_blocks` and has already
+    /// created its maximum number of worlds, including any which have since been dropped.
     pub fn create_world(&self) -> World {
         let id = WorldId::next(self.id.0);
-        let world = World::new_in_universe(id, EntityAllocator::new(self.allocator.clone()));
+        let allocator = match self.lanes {
+            Some(lanes) => {
+                let lane = self.worlds.fetch_add(1, Ordering::SeqCst);
+                assert!(
+                    lane < lanes,
+                    "deterministic universe supports at most {} worlds",
+                    lanes
+                );
+                EntityAllocator::with_lane(self.allocator.clone(), Some(id), lane, lanes)
+            }
+            None => EntityAllocator::with_owner(self.allocator.clone(), id),
+        };
+        let world = World::new_in_universe(id, allocator);
 
         info!(universe = self.id.0, world = world.id().1, "Created world");
         world
     }
+
+    /// Creates a new `World` which only allocates `Entity` IDs from a reserved range.
+    ///
+    /// The range covers `len` indices from `start`, widened to whole allocation blocks of the
+    /// universe's block size. No other world in this universe will be given IDs from within the
+    /// range, which allows the ID space to be deterministically partitioned between several
+    /// authorities, such as a server assigning ranges to its clients.
+    ///
+    /// Returns `None` if any part of the range has already been reserved or allocated.
+    ///
+    /// # Panics
+    ///
+    /// The returned world panics when inserting entities once its range is exhausted.
+    pub fn create_world_in_range(&self, start: EntityIndex, len: usize) -> Option<World> {
+        let range = self.allocator.lock().reserve_range(start, len)?;
+        let id = WorldId::next(self.id.0);
+        let allocator = EntityAllocator::with_range(self.allocator.clone(), Some(id), range);
+        let world = World::new_in_universe(id, allocator);
+
+        info!(universe = self.id.0, world = world.id().1, "Created world");
+        Some(world)
+    }
+
+    /// Gets the ID of the world which owns the given entity's ID.
+    ///
+    /// Worlds within a universe reserve `Entity` IDs in blocks, and the universe records which
+    /// world each block was reserved by. This allows entities in one world to refer to entities
+    /// in another, and to resolve where each reference points. The owning world should still
+    /// be consulted with `World::is_alive` to check that the entity has not been deleted.
+    ///
+    /// Returns `None` if the entity's ID was not reserved by any world within this universe.
+    pub fn entity_owner(&self, entity: Entity) -> Option<WorldId> {
+        self.allocator.lock().owner(entity.index())
+    }
+}
+
+/// Receives the components of an entity from `World::visit_entity`, without knowing their
+/// types statically.
+pub trait ComponentVisitor {
+    /// Called with each component of a type registered with `World::register_visit`.
+    ///
+    /// The value may be downcast to its concrete type.
+    fn visit(&mut self, name: &'static str, value: &dyn Any);
+
+    /// Called with the type name of each component whose type has not been registered.
+    fn visit_unregistered(&mut self, _name: &'static str) {}
 }
 
 /// A queryable collection of entities.
@@ -146,9 +289,20 @@ pub trait EntityStore {
     /// Otherwise `None` is returned.
     fn get_tag<T: Tag>(&self, entity: Entity) -> Option<&T>;
 
+    /// Checks that the provided `Tag` is present on a given entity.
+    ///
+    /// Returns true if it exists, otherwise false.
+    fn has_tag<T: Tag>(&self, entity: Entity) -> bool { self.get_tag::<T>(entity).is_some() }
+
     /// Determines if the given `Entity` is alive within this `World`.
     fn is_alive(&self, entity: Entity) -> bool;
 
+    /// Gets the location of the given `Entity` within the world's storage, if it is alive.
+    ///
+    /// The default implementation returns `None`, for stores which do not expose the locations
+    /// of their entities.
+    fn get_entity_location(&self, _entity: Entity) -> Option<EntityLocation> { None }
+
     /// Gets the entity component storage. Validates that the world can provide access to everything needed by the view.
     fn get_component_storage<V: for<'a> View<'a>>(
         &self,
@@ -177,6 +331,24 @@ pub struct World {
     defrag_progress: usize,
     command_buffer_size: usize,
     pub(crate) allocation_buffer: Vec<Entity>,
+    recently_spawned: Vec<Entity>,
+    accumulate_spawned: bool,
+    component_formatters: HashMap<ComponentTypeId, DebugFn>,
+    tag_formatters: HashMap<TagTypeId, DebugFn>,
+    visitors: HashMap<ComponentTypeId, VisitFn>,
+    drop_hooks: HashMap<ComponentTypeId, DropHook>,
+    clone_hooks: HashMap<ComponentTypeId, CloneHook>,
+    migrate_hooks: Vec<MigrateHook>,
+    pending_despawns: Mutex<Vec<Entity>>,
+    sparse: SparseStorage,
+    lifecycle: Option<Lifecycle>,
+}
+
+/// Entities inserted and deleted since the end of the previous frame.
+#[derive(Default)]
+struct Lifecycle {
+    spawned: Vec<Entity>,
+    despawned: Vec<Entity>,
 }
 
 unsafe impl Send for World {}
@@ -193,7 +365,7 @@ impl World {
     pub fn new() -> Self {
         Self::new_in_universe(
             WorldId::next(0),
-            EntityAllocator::new(Arc::new(Mutex::new(BlockAllocator::new()))),
+            EntityAllocator::new(Arc::new(Mutex::new(BlockAllocator::default()))),
         )
     }
 
@@ -206,6 +378,17 @@ impl World {
             defrag_progress: 0,
             command_buffer_size: Self::DEFAULT_COMMAND_BUFFER_SIZE,
             allocation_buffer: Vec::with_capacity(Self::DEFAULT_COMMAND_BUFFER_SIZE),
+            recently_spawned: Vec::new(),
+            accumulate_spawned: false,
+            component_formatters: HashMap::new(),
+            tag_formatters: HashMap::new(),
+            visitors: HashMap::new(),
+            drop_hooks: HashMap::new(),
+            clone_hooks: HashMap::new(),
+            migrate_hooks: Vec::new(),
+            pending_despawns: Mutex::new(Vec::new()),
+            sparse: SparseStorage::default(),
+            lifecycle: None,
         }
     }
 
@@ -250,9 +433,189 @@ impl World {
 
     pub fn storage_mut(&mut self) -> &mut Storage { unsafe { &mut *self.storage.get() } }
 
+    /// Begins recording how many times each component type is read and written.
+    ///
+    /// Statistics are recorded by queries and by `get_component` and `get_component_mut`. See
+    /// `AccessStats`.
+    pub fn enable_access_stats(&mut self) {
+        if self.storage().access_stats().is_none() {
+            self.storage_mut()
+                .set_access_stats(Some(AccessStats::default()));
+        }
+    }
+
+    /// Stops recording component access statistics, and discards those already recorded.
+    pub fn disable_access_stats(&mut self) { self.storage_mut().set_access_stats(None); }
+
+    /// Gets the component access statistics recorded since they were last reset, if enabled.
+    pub fn access_stats(&self) -> Option<&AccessStats> { self.storage().access_stats() }
+
+    /// Counts the entities in the world grouped by their component types, to help identify
+    /// unexpected archetype fragmentation.
+    ///
+    /// Each entry contains a sorted set of component types and the number of entities which have
+    /// exactly those components. Archetypes which differ only by their tag types are counted
+    /// together. Entries are ordered by when their first archetype was created, and include
+    /// archetypes which are currently empty.
+    pub fn archetype_histogram(&self) -> Vec<(Vec<ComponentTypeId>, usize)> {
+        let mut histogram: Vec<(Vec<ComponentTypeId>, usize)> = Vec::new();
+        for archetype in self.storage().archetypes() {
+            let mut types = archetype
+                .description()
+                .components()
+                .iter()
+                .map(|(type_id, _)| *type_id)
+                .collect::<Vec<_>>();
+            types.sort();
+
+            let count = archetype
+                .chunksets()
+                .iter()
+                .flat_map(|set| set.occupied())
+                .map(|chunk| chunk.len())
+                .sum::<usize>();
+
+            match histogram
+                .iter_mut()
+                .find(|(existing, _)| *existing == types)
+            {
+                Some((_, total)) => *total += count,
+                None => histogram.push((types, count)),
+            }
+        }
+        histogram
+    }
+
+    /// Resets all component access statistics to zero, such as at the start of a frame.
+    pub fn reset_access_stats(&self) {
+        if let Some(stats) = self.access_stats() {
+            stats.reset();
+        }
+    }
+
+    /// Begins recording which entities are inserted into and deleted from the world, to be
+    /// retrieved with `spawned_this_frame` and `despawned_this_frame`.
+    pub fn enable_lifecycle_tracking(&mut self) {
+        if self.lifecycle.is_none() {
+            self.lifecycle = Some(Lifecycle::default());
+        }
+    }
+
+    /// Stops recording entity insertions and deletions, and discards those already recorded.
+    pub fn disable_lifecycle_tracking(&mut self) { self.lifecycle = None; }
+
+    /// Gets the entities which have been inserted since the last call to `end_frame`.
+    ///
+    /// Entities are only recorded once enabled with `enable_lifecycle_tracking`. An entity
+    /// which was inserted and then deleted within the same frame is returned by both this and
+    /// `despawned_this_frame`.
+    pub fn spawned_this_frame(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.lifecycle
+            .iter()
+            .flat_map(|lifecycle| lifecycle.spawned.iter().copied())
+    }
+
+    /// Gets the entities which have been deleted since the last call to `end_frame`.
+    ///
+    /// Entities are only recorded once enabled with `enable_lifecycle_tracking`.
+    pub fn despawned_this_frame(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.lifecycle
+            .iter()
+            .flat_map(|lifecycle| lifecycle.despawned.iter().copied())
+    }
+
+    /// Clears the entities recorded by `spawned_this_frame` and `despawned_this_frame`, such as
+    /// at the end of a frame.
+    pub fn end_frame(&mut self) {
+        if let Some(lifecycle) = &mut self.lifecycle {
+            lifecycle.spawned.clear();
+            lifecycle.despawned.clear();
+        }
+    }
+
+    /// Gets the entities created by recent calls to `insert`.
+    ///
+    /// By default, this contains only the entities created by the most recent `insert`, and is
+    /// replaced by each subsequent `insert`. When accumulation is enabled with
+    /// `set_accumulate_spawned`, entities from every `insert` are appended until
+    /// `clear_recently_spawned` is called, such as at the end of each frame.
+    ///
+    /// Unlike `spawned_this_frame`, this does not require lifecycle tracking and does not record
+    /// deletions. The entities are not guaranteed to still be alive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_core::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.set_accumulate_spawned(true);
+    /// let a = world.insert((), vec![(Position(1.0),)])[0];
+    /// let b = world.insert((), vec![(Position(2.0),)])[0];
+    /// assert_eq!(&[a, b], world.recently_spawned());
+    ///
+    /// world.clear_recently_spawned();
+    /// assert!(world.recently_spawned().is_empty());
+    /// ```
+    pub fn recently_spawned(&self) -> &[Entity] { &self.recently_spawned }
+
+    /// Sets whether `recently_spawned` accumulates the entities created by every `insert` until
+    /// it is explicitly cleared, rather than only holding those created by the latest `insert`.
+    pub fn set_accumulate_spawned(&mut self, accumulate: bool) {
+        self.accumulate_spawned = accumulate;
+    }
+
+    /// Clears the entities returned by `recently_spawned`.
+    pub fn clear_recently_spawned(&mut self) { self.recently_spawned.clear(); }
+
     /// Gets the unique ID of this world within its universe.
     pub fn id(&self) -> WorldId { self.id }
 
+    #[cfg(feature = "serialize")]
+    pub(crate) fn set_entity_locations<I: IntoIterator<Item = (Entity, EntityLocation)>>(
+        &mut self,
+        locations: I,
+    ) {
+        for (entity, location) in locations {
+            self.entity_locations.set(entity, location);
+        }
+    }
+
+    /// Determines if the recorded location of every entity in the world refers to the chunk
+    /// slot which holds it.
+    #[cfg(test)]
+    pub(crate) fn entity_locations_valid(&self) -> bool {
+        self.storage()
+            .archetypes()
+            .iter()
+            .enumerate()
+            .all(|(i, archetype)| {
+                archetype
+                    .iter_entity_locations(ArchetypeIndex(i))
+                    .all(|(entity, location)| self.entity_locations.get(entity) == Some(location))
+            })
+    }
+
+    /// Determines if each of the given entities is alive within this `World`.
+    ///
+    /// Returns a vector with one entry per entity in `entities`, in the same order.
+    pub fn are_alive(&self, entities: &[Entity]) -> Vec<bool> {
+        self.entity_allocator.are_alive(entities)
+    }
+
+    /// Filters the given entities down to those which are alive within this `World`,
+    /// preserving their order.
+    pub fn retain_alive(&self, entities: &[Entity]) -> Vec<Entity> {
+        entities
+            .iter()
+            .zip(self.are_alive(entities))
+            .filter(|(_, alive)| *alive)
+            .map(|(entity, _)| *entity)
+            .collect()
+    }
+
     pub fn get_entity_location(&self, entity: Entity) -> Option<EntityLocation> {
         if self.is_alive(entity) {
             self.entity_locations.get(entity)
@@ -261,6 +624,37 @@ impl World {
         }
     }
 
+    /// Determines if the given entity is stored within this `World`.
+    ///
+    /// This differs from `is_alive`, which also returns `true` for entities which have been
+    /// allocated from this world, such as by a `CommandBuffer`, but not yet inserted into it.
+    pub fn contains(&self, entity: Entity) -> bool {
+        // locations are not cleared when an entity is allocated without being inserted, so
+        // check that the recorded slot really holds the entity
+        let stored = self.get_entity_location(entity).and_then(|location| {
+            let chunk = self.storage().chunk(location)?;
+            chunk.entities().get(*location.component()).copied()
+        });
+        stored == Some(entity)
+    }
+
+    /// Gets the number of entities in the world, including disabled entities.
+    ///
+    /// This sums the lengths of all occupied chunks, and so is linear in the number of chunks
+    /// rather than the number of entities.
+    pub fn len(&self) -> usize {
+        self.storage()
+            .archetypes()
+            .iter()
+            .flat_map(|archetype| archetype.chunksets())
+            .flat_map(|set| set.occupied())
+            .map(|chunk| chunk.len())
+            .sum()
+    }
+
+    /// Determines if the world contains no entities.
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
     /// Iterate all entities in existence. Internally this iterates archetypes instead of
     /// entity allocators because the data structures contains a list of free entities instead
     /// of allocated entities
@@ -271,6 +665,39 @@ impl World {
             .flat_map(|archetype_data| archetype_data.iter_entities().map(|entity| entity))
     }
 
+    /// Iterates through the `T` components of every entity in the world, one chunk at a time.
+    ///
+    /// Each item holds the entities of a chunk which contains `T`, and the slice of their `T`
+    /// components in the same order. This allows a component type to be exported as a single
+    /// column, such as into a columnar file format, without any per-entity overhead. Disabled
+    /// entities are included.
+    ///
+    /// # Panics
+    ///
+    /// Iteration panics if the components of a chunk are currently mutably borrowed.
+    pub fn export_column<T: Component>(
+        &self,
+    ) -> impl Iterator<Item = (&[Entity], RefMap<'_, &[T]>)> + '_ {
+        let type_id = ComponentTypeId::of::<T>();
+        self.storage()
+            .archetypes()
+            .iter()
+            .filter(move |archetype| {
+                archetype
+                    .description()
+                    .components()
+                    .iter()
+                    .any(|(component_type, _)| *component_type == type_id)
+            })
+            .flat_map(|archetype| archetype.chunksets())
+            .flat_map(|set| set.occupied())
+            .map(move |chunk| {
+                // safe because the components were stored under the type ID of `T`
+                let components = unsafe { chunk.components(type_id).unwrap().data_slice::<T>() };
+                (chunk.entities(), components)
+            })
+    }
+
     /// Inserts new entities into the world. This insertion method should be preferred, as it performs
     /// no movement of components for inserting multiple entities and components.
     ///
@@ -302,13 +729,154 @@ impl World {
         T: TagSet + TagLayout + for<'a> Filter<ChunksetFilterData<'a>>,
         C: IntoComponentSource,
     {
-        self.insert_impl(tags, components.into())
+        self.insert_impl(tags, components.into(), None)
+    }
+
+    /// Inserts new entities into the world, as with `insert`, but places at most `max_per_chunk`
+    /// entities into each chunk.
+    ///
+    /// A new chunk is started for every `max_per_chunk` entities, rather than filling chunks
+    /// with free space or to their full capacity. This allows chunk boundaries to be controlled,
+    /// such as to align network snapshot deltas to chunks.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_per_chunk` is zero.
+    pub fn insert_with_chunk_limit<T, C>(
+        &mut self,
+        tags: T,
+        components: C,
+        max_per_chunk: usize,
+    ) -> &[Entity]
+    where
+        T: TagSet + TagLayout + for<'a> Filter<ChunksetFilterData<'a>>,
+        C: IntoComponentSource,
+    {
+        assert!(max_per_chunk > 0, "chunk limit must be greater than zero");
+        self.insert_impl(tags, components.into(), Some(max_per_chunk))
+    }
+
+    /// Inserts `count` new entities into the world, with the components of each entity generated
+    /// by calling `f` with the entity's index.
+    ///
+    /// Component tuples are written directly into chunk storage as they are generated, without
+    /// first being collected into an intermediate buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_core::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Rotation(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.spawn_n((), 100, |i| (Position(i as f32), Rotation(0.0)));
+    /// ```
+    #[inline]
+    pub fn spawn_n<T, C, F>(&mut self, tags: T, count: usize, f: F) -> &[Entity]
+    where
+        T: TagSet + TagLayout + for<'a> Filter<ChunksetFilterData<'a>>,
+        F: FnMut(usize) -> C,
+        std::iter::Map<std::ops::Range<usize>, F>: IntoComponentSource,
+    {
+        self.insert(tags, (0..count).map(f))
+    }
+
+    /// Gets the singleton entity with the given tags and the layout of the components returned by
+    /// `f`, inserting a new entity with those components if no such entity exists.
+    ///
+    /// `f` is only called when a new entity is inserted. This avoids accidentally creating
+    /// duplicate singletons when checking for an existing entity and inserting one are performed
+    /// separately.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_core::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct GameConfig(u32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// let config = world.get_or_spawn_singleton((), || (GameConfig(60),));
+    /// assert_eq!(config, world.get_or_spawn_singleton((), || (GameConfig(30),)));
+    /// assert_eq!(60, world.get_component::<GameConfig>(config).unwrap().0);
+    /// ```
+    pub fn get_or_spawn_singleton<T, C, F>(&mut self, mut tags: T, f: F) -> Entity
+    where
+        T: TagSet + TagLayout + for<'a> Filter<ChunksetFilterData<'a>>,
+        F: FnOnce() -> C,
+        std::iter::OnceWith<F>: IntoComponentSource,
+    {
+        let mut components = IntoComponentSource::into(std::iter::once_with(f));
+
+        let existing = self
+            .find_archetype(tags.get_filter(), components.get_filter())
+            .and_then(|archetype| {
+                let set = self.find_chunk_set(archetype, &mut tags)?;
+                let archetype = unsafe { self.storage().archetype_unchecked(archetype) };
+                archetype.chunksets()[*set]
+                    .occupied()
+                    .iter()
+                    .flat_map(|chunk| chunk.entities())
+                    .next()
+                    .copied()
+            });
+
+        match existing {
+            Some(entity) => entity,
+            None => self.insert_impl(tags, components, None)[0],
+        }
+    }
+
+    pub(crate) fn insert_impl<T, C>(
+        &mut self,
+        tags: T,
+        components: C,
+        chunk_limit: Option<usize>,
+    ) -> &[Entity]
+    where
+        T: TagSet + TagLayout + for<'a> Filter<ChunksetFilterData<'a>>,
+        C: ComponentSource,
+    {
+        let entities = self.entity_allocator.clone();
+
+        // take the blocks for the new entities up front, so that the location list can be sized
+        // for the highest index that will be recorded
+        entities.reserve(components.len());
+        self.entity_locations.reserve(entities.index_bound());
+
+        self.insert_with_entities(tags, components, entities.create_entities(), chunk_limit);
+        self.record_spawned();
+
+        &self.allocation_buffer
+    }
+
+    /// Records the entities in the allocation buffer as having been spawned.
+    fn record_spawned(&mut self) {
+        if let Some(lifecycle) = &mut self.lifecycle {
+            lifecycle.spawned.extend_from_slice(&self.allocation_buffer);
+        }
+
+        if !self.accumulate_spawned {
+            self.recently_spawned.clear();
+        }
+        self.recently_spawned
+            .extend_from_slice(&self.allocation_buffer);
     }
 
-    pub(crate) fn insert_impl<T, C>(&mut self, mut tags: T, mut components: C) -> &[Entity]
+    fn insert_with_entities<T, C, E>(
+        &mut self,
+        mut tags: T,
+        mut components: C,
+        entities: E,
+        chunk_limit: Option<usize>,
+    ) -> &[Entity]
     where
         T: TagSet + TagLayout + for<'a> Filter<ChunksetFilterData<'a>>,
         C: ComponentSource,
+        E: Iterator<Item = Entity>,
     {
         let span = span!(Level::TRACE, "Inserting entities", world = self.id().0);
         let _guard = span.enter();
@@ -322,20 +890,42 @@ impl World {
         self.allocation_buffer.clear();
         self.allocation_buffer.reserve(components.len());
 
+        // undo the insert if the component source panics
+        let mut guard = InsertGuard {
+            storage: self.storage.get(),
+            allocator: self.entity_allocator.clone(),
+            archetype: archetype_index,
+            set: chunk_set_index,
+            chunks: Vec::new(),
+        };
+
+        // declared after the guard so that it is dropped first, releasing any lock it holds on
+        // the entity allocator before the guard frees entities
+        let mut entities = entities;
+
         // insert components into chunks
         while !components.is_empty() {
             // get chunk component storage
             let archetype =
                 unsafe { (&mut *self.storage.get()).archetype_unchecked_mut(archetype_index) };
-            let chunk_index = archetype.get_free_chunk(chunk_set_index, 1);
+            let chunk_index = match chunk_limit {
+                Some(_) => archetype.alloc_chunk(chunk_set_index),
+                None => archetype.get_free_chunk(chunk_set_index, 1),
+            };
             let chunk = unsafe {
                 archetype
                     .chunkset_unchecked_mut(chunk_set_index)
                     .chunk_unchecked_mut(chunk_index)
             };
+            guard.chunks.push((chunk_index, chunk.len()));
 
             // insert as many components as we can into the chunk
-            let allocated = components.write(self.entity_allocator.create_entities(), chunk);
+            let allocated = match chunk_limit {
+                Some(limit) => {
+                    chunk.with_capacity_limit(limit, |chunk| components.write(&mut entities, chunk))
+                }
+                None => components.write(&mut entities, chunk),
+            };
 
             // record new entity locations
             let start = chunk.len() - allocated;
@@ -352,39 +942,549 @@ impl World {
             }
         }
 
+        // the insert completed, so there is nothing to roll back
+        guard.chunks.clear();
+
         trace!(count = self.allocation_buffer.len(), "Inserted entities");
 
         &self.allocation_buffer
     }
 
-    /// Removes the given `Entity` from the `World`.
+    /// Reserves entity IDs for at least `additional` more entities, so that a large insert does
+    /// not need to repeatedly take the universe's entity allocator lock.
+    pub fn reserve_entities(&mut self, additional: usize) {
+        self.entity_allocator.reserve(additional);
+    }
+
+    /// Allocates an entity ID without storing the entity in the world, so that it can be
+    /// referenced before all of its components are known.
     ///
-    /// Returns `true` if the entity was deleted; else `false`.
-    pub fn delete(&mut self, entity: Entity) -> bool {
-        if !self.is_alive(entity) {
-            return false;
-        }
+    /// The entity is alive, but has no components or tags and is not matched by any query until
+    /// it is stored with `populate`.
+    pub fn reserve_entity(&mut self) -> Entity {
+        let entity = self.entity_allocator.create_entity();
 
-        if self.entity_allocator.delete_entity(entity) {
-            let location = self.entity_locations.get(entity).unwrap();
-            self.delete_location(location);
-            trace!(world = self.id().0, ?entity, "Deleted entity");
-            true
-        } else {
-            false
-        }
+        // the ID's index may have been used before, so replace the previous entity's location
+        // with one which does not exist
+        self.entity_locations.set(
+            entity,
+            EntityLocation::new(
+                ArchetypeIndex(usize::MAX),
+                SetIndex(0),
+                ChunkIndex(0),
+                ComponentIndex(0),
+            ),
+        );
+        entity
     }
 
-    /// Delete all entity data. This leaves subscriptions and the command buffer intact.
-    pub fn delete_all(&mut self) {
-        for archetype in self.storage_mut().archetypes_mut() {
-            archetype.delete_all();
+    /// Stores an entity reserved with `reserve_entity` in the world, with the given tags and
+    /// components.
+    ///
+    /// Returns `false`, leaving the world unchanged, if the entity is not alive or has already
+    /// been stored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_core::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Parent(Entity);
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Name(&'static str);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// let parent = world.reserve_entity();
+    /// world.insert((), vec![(Parent(parent),)]);
+    ///
+    /// assert!(world.populate(parent, (), (Name("root"),)));
+    /// assert_eq!(Name("root"), *world.get_component::<Name>(parent).unwrap());
+    /// ```
+    pub fn populate<T, C>(&mut self, entity: Entity, tags: T, components: C) -> bool
+    where
+        T: TagSet + TagLayout + for<'a> Filter<ChunksetFilterData<'a>>,
+        std::iter::Once<C>: IntoComponentSource,
+    {
+        if !self.is_alive(entity) || self.contains(entity) {
+            return false;
         }
 
-        self.entity_allocator.delete_all_entities();
+        trace!(world = self.id().0, ?entity, "Populating entity");
+
+        let components = IntoComponentSource::into(std::iter::once(components));
+        self.insert_with_entities(tags, components, std::iter::once(entity), None);
+        self.record_spawned();
+        true
     }
 
-    fn delete_location(&mut self, location: EntityLocation) {
+    /// Finds or creates the archetype with exactly the tags and components in `description`, and
+    /// returns its ID.
+    ///
+    /// Archetype IDs are assigned in creation order, so reserving a fixed list of archetypes in a
+    /// fixed order before inserting any entities gives each archetype the same ID in every world,
+    /// regardless of the order in which entities are later inserted.
+    pub fn reserve_archetype(&mut self, mut description: ArchetypeDescription) -> ArchetypeId {
+        let archetype_data = ArchetypeFilterData {
+            component_types: self.storage().component_types(),
+            tag_types: self.storage().tag_types(),
+        };
+        let existing = description
+            .matches(archetype_data)
+            .matching_indices()
+            .next();
+
+        let index = if let Some(index) = existing {
+            ArchetypeIndex(index)
+        } else {
+            unsafe { &mut *self.storage.get() }
+                .alloc_archetype(description)
+                .0
+        };
+        ArchetypeId::new(self.id, index)
+    }
+
+    /// Removes the given `Entity` from the `World`.
+    ///
+    /// Returns `true` if the entity was deleted; else `false`.
+    ///
+    /// Deleting an entity which has already been deleted is a no-op which returns `false`, and
+    /// does not affect any other entities, even if the deleted entity's ID has since been reused.
+    pub fn delete(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+
+        // entities which have been allocated but not yet stored have no components to drop
+        let stored = self.contains(entity);
+        if self.entity_allocator.delete_entity(entity) {
+            if stored {
+                let location = self.entity_locations.get(entity).unwrap();
+                self.run_drop_hooks(location, |_| true);
+                self.delete_location(location);
+            }
+            self.sparse.remove_entity(entity);
+            if let Some(lifecycle) = &mut self.lifecycle {
+                lifecycle.despawned.push(entity);
+            }
+            trace!(world = self.id().0, ?entity, "Deleted entity");
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Marks the given `Entity` for deletion by the next call to `flush_despawns`.
+    ///
+    /// This only requires a shared reference to the world, so entities can be marked while
+    /// iterating through a query. Marking an entity more than once is harmless.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_core::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Health(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.insert((), vec![(Health(0.0),), (Health(1.0),)]);
+    ///
+    /// let query = Read::<Health>::query();
+    /// for (entity, health) in query.iter_entities(&world) {
+    ///     if health.0 <= 0.0 {
+    ///         world.despawn_deferred(entity);
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(1, world.flush_despawns());
+    /// ```
+    pub fn despawn_deferred(&self, entity: Entity) { self.pending_despawns.lock().push(entity); }
+
+    /// Deletes all entities marked with `despawn_deferred`.
+    ///
+    /// Returns the number of entities which were deleted. Entities which were already deleted
+    /// by the time of the flush are skipped.
+    pub fn flush_despawns(&mut self) -> usize {
+        let pending = std::mem::take(self.pending_despawns.get_mut());
+        pending
+            .into_iter()
+            .filter(|entity| self.delete(*entity))
+            .count()
+    }
+
+    /// Replaces all of an entity's tags and components, moving it into the archetype described by
+    /// the new data. The entity's previous components are dropped.
+    ///
+    /// The entity's ID is preserved, so existing references to it remain valid.
+    ///
+    /// Returns `false` if the entity does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_core::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Seed;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Plant(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// let entity = world.insert((), vec![(Seed,)])[0];
+    ///
+    /// assert!(world.replace_components(entity, (), (Plant(0.1),)));
+    /// assert!(world.get_component::<Seed>(entity).is_none());
+    /// assert_eq!(Plant(0.1), *world.get_component::<Plant>(entity).unwrap());
+    /// ```
+    pub fn replace_components<T, C>(&mut self, entity: Entity, tags: T, components: C) -> bool
+    where
+        T: TagSet + TagLayout + for<'a> Filter<ChunksetFilterData<'a>>,
+        std::iter::Once<C>: IntoComponentSource,
+    {
+        if !self.contains(entity) {
+            return false;
+        }
+
+        trace!(world = self.id().0, ?entity, "Replacing entity components");
+
+        let location = self.entity_locations.get(entity).unwrap();
+        self.run_drop_hooks(location, |_| true);
+        self.delete_location(location);
+        let components = IntoComponentSource::into(std::iter::once(components));
+        self.insert_with_entities(tags, components, std::iter::once(entity), None);
+
+        let target = self.entity_locations.get(entity).unwrap();
+        if target.archetype() != location.archetype() {
+            self.run_migrate_hooks(entity, location.archetype(), target.archetype());
+        }
+
+        true
+    }
+
+    /// Delete all entity data. This leaves subscriptions and the command buffer intact.
+    pub fn delete_all(&mut self) {
+        if !self.drop_hooks.is_empty() {
+            for archetype in self.storage().archetypes() {
+                for chunk in archetype.chunksets().iter().flat_map(|set| set.occupied()) {
+                    for index in 0..chunk.len() {
+                        Self::run_chunk_drop_hooks(&self.drop_hooks, chunk, index, |_| true);
+                    }
+                }
+            }
+        }
+
+        if let Some(lifecycle) = &mut self.lifecycle {
+            let storage = unsafe { &*self.storage.get() };
+            for archetype in storage.archetypes() {
+                for chunk in archetype.chunksets().iter().flat_map(|set| set.occupied()) {
+                    lifecycle.despawned.extend_from_slice(chunk.entities());
+                }
+            }
+        }
+
+        for archetype in self.storage_mut().archetypes_mut() {
+            archetype.delete_all();
+        }
+
+        self.sparse.clear();
+        self.entity_allocator.delete_all_entities();
+    }
+
+    /// Deletes all entities, and drops all archetypes and chunks to release their memory.
+    ///
+    /// This is useful for reusing a world, such as between levels, while leaving its
+    /// subscriptions and registered hooks intact. Unlike `delete_all`, which keeps empty chunks
+    /// around for reuse, the world is left as if newly created. Archetype IDs and entity
+    /// locations obtained before the world was cleared must not be used afterwards, as they may
+    /// be reused.
+    pub fn clear(&mut self) {
+        self.delete_all();
+
+        self.storage_mut().clear();
+        self.entity_locations = Locations::new();
+        self.defrag_progress = 0;
+        self.recently_spawned.clear();
+        self.pending_despawns.get_mut().clear();
+    }
+
+    /// Drops all archetypes which no longer contain any entities, releasing their memory.
+    ///
+    /// Archetypes are identified by their index, so the remaining archetypes are moved down to
+    /// fill the gaps, and the locations of their entities are updated. Archetype IDs, chunk IDs
+    /// and entity locations obtained before the call must not be used afterwards.
+    ///
+    /// Returns the number of archetypes removed.
+    pub fn gc_archetypes(&mut self) -> usize {
+        let removed = self.storage_mut().remove_empty_archetypes();
+        if removed == 0 {
+            return 0;
+        }
+
+        let archetypes = unsafe { &*self.storage.get() }.archetypes();
+        for (i, archetype) in archetypes.iter().enumerate() {
+            for (entity, location) in archetype.iter_entity_locations(ArchetypeIndex(i)) {
+                self.entity_locations.set(entity, location);
+            }
+        }
+        self.defrag_progress = 0;
+
+        trace!(world = self.id().0, removed, "Removed empty archetypes");
+        removed
+    }
+
+    /// Registers a callback which is invoked with each `T` component just before it is dropped
+    /// by the world, replacing any callback previously registered for `T`.
+    ///
+    /// The callback is run when an entity with a `T` is deleted, when its `T` is removed
+    /// or replaced, and when its components are overwritten with `replace_components`.
+    /// It is not run when the world itself is dropped.
+    ///
+    /// This is useful for releasing external resources held by components, such as returning
+    /// GPU buffers to a pool.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_core::prelude::*;
+    /// # use std::sync::atomic::{AtomicUsize, Ordering};
+    /// # use std::sync::Arc;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Buffer(usize);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// let released = Arc::new(AtomicUsize::new(0));
+    /// let released_clone = released.clone();
+    /// world.on_component_drop::<Buffer, _>(move |buffer| {
+    ///     released_clone.fetch_add(buffer.0, Ordering::SeqCst);
+    /// });
+    ///
+    /// let entity = world.insert((), vec![(Buffer(3),)])[0];
+    /// world.delete(entity);
+    /// assert_eq!(3, released.load(Ordering::SeqCst));
+    /// ```
+    pub fn on_component_drop<T, F>(&mut self, f: F)
+    where
+        T: Component,
+        F: Fn(&mut T) + Send + Sync + 'static,
+    {
+        self.drop_hooks.insert(
+            ComponentTypeId::of::<T>(),
+            Box::new(move |ptr| f(unsafe { &mut *(ptr as *mut T) })),
+        );
+    }
+
+    /// Registers `T` as a cloneable component type, allowing entities with a `T` component to be
+    /// duplicated with `clone_entity`.
+    pub fn register_clone<T: Component + Clone>(&mut self) {
+        self.clone_hooks.insert(
+            ComponentTypeId::of::<T>(),
+            Box::new(|src, writer| unsafe {
+                let value = [(*(src as *const T)).clone()];
+                writer.push(&value);
+                std::mem::forget(value);
+            }),
+        );
+    }
+
+    /// Creates a new entity with the same tags as the given entity and a clone of each of its
+    /// components.
+    ///
+    /// Returns the new entity, or `None` if the given entity does not exist.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the entity's component types have not been registered with
+    /// `register_clone`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_core::prelude::*;
+    /// # #[derive(Clone, Debug, PartialEq)]
+    /// # struct Name(String);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.register_clone::<Name>();
+    ///
+    /// let entity = world.insert((), vec![(Name("crate".to_string()),)])[0];
+    /// let copy = world.clone_entity(entity).unwrap();
+    /// assert_eq!("crate", world.get_component::<Name>(copy).unwrap().0);
+    /// ```
+    pub fn clone_entity(&mut self, entity: Entity) -> Option<Entity> {
+        if !self.contains(entity) {
+            return None;
+        }
+
+        let location = self.entity_locations.get(entity)?;
+        let archetype = unsafe { &mut *self.storage.get() }
+            .archetype_mut(location.archetype())
+            .unwrap();
+
+        let component_types = archetype
+            .description()
+            .components()
+            .iter()
+            .map(|(type_id, _)| *type_id)
+            .collect::<Vec<_>>();
+        for type_id in &component_types {
+            if !self.clone_hooks.contains_key(type_id) {
+                panic!(
+                    "component type not registered with register_clone: {:?}",
+                    type_id
+                );
+            }
+        }
+
+        // find space for the clone before taking pointers into the source chunk, as allocating
+        // a new chunk may move the chunkset's chunks
+        let chunk_index = archetype.get_free_chunk(location.set(), 1);
+        let sources = {
+            let chunk = archetype
+                .chunkset(location.set())
+                .and_then(|set| set.chunk(location.chunk()))
+                .unwrap();
+            component_types
+                .iter()
+                .map(|type_id| unsafe {
+                    let (ptr, size, _) = chunk.components(*type_id).unwrap().data_raw();
+                    ptr.add(size * *location.component()) as *const u8
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let chunk = unsafe {
+            archetype
+                .chunkset_unchecked_mut(location.set())
+                .chunk_unchecked_mut(chunk_index)
+        };
+
+        let clone = {
+            let mut writer = chunk.writer();
+            let (entities, components) = writer.get();
+
+            // clone every component before the entity is added, removing the clones already
+            // written if a clone hook panics so that the chunk's columns stay the same length
+            let mut guard = CloneGuard {
+                components: unsafe { &mut *components.get() },
+                component_types: &component_types,
+                index: entities.len(),
+                written: 0,
+            };
+            for (type_id, src) in component_types.iter().zip(sources) {
+                let mut writer = guard.components.get_mut(*type_id).unwrap().writer();
+                self.clone_hooks[type_id](src, &mut writer);
+                guard.written += 1;
+            }
+            std::mem::forget(guard);
+
+            let clone = self.entity_allocator.create_entity();
+            entities.push(clone);
+            clone
+        };
+
+        self.entity_locations.set(
+            clone,
+            EntityLocation::new(
+                location.archetype(),
+                location.set(),
+                chunk_index,
+                ComponentIndex(chunk.len() - 1),
+            ),
+        );
+
+        if let Some(lifecycle) = &mut self.lifecycle {
+            lifecycle.spawned.push(clone);
+        }
+
+        Some(clone)
+    }
+
+    /// Registers a callback which is invoked whenever an entity moves to a different archetype,
+    /// with the entity and the component types it had before and after the move.
+    ///
+    /// The callback is run when components or tags are added to or removed from an entity, and
+    /// when `replace_components` changes an entity's layout. It is run after the entity has
+    /// moved. This is useful for incrementally updating external indices of entities by their
+    /// components.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_core::prelude::*;
+    /// # use std::sync::{Arc, Mutex};
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Velocity(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// let moved = Arc::new(Mutex::new(Vec::new()));
+    /// let moved_clone = moved.clone();
+    /// world.on_migrate(move |entity, _, _| moved_clone.lock().unwrap().push(entity));
+    ///
+    /// let entity = world.insert((), vec![(Position(0.0),)])[0];
+    /// world.add_component(entity, Velocity(1.0)).unwrap();
+    /// assert_eq!(vec![entity], *moved.lock().unwrap());
+    /// ```
+    pub fn on_migrate<F>(&mut self, f: F)
+    where
+        F: Fn(Entity, &[ComponentTypeId], &[ComponentTypeId]) + Send + Sync + 'static,
+    {
+        self.migrate_hooks.push(Box::new(f));
+    }
+
+    fn run_migrate_hooks(&self, entity: Entity, from: ArchetypeIndex, to: ArchetypeIndex) {
+        if self.migrate_hooks.is_empty() {
+            return;
+        }
+
+        let component_types = |index| -> Vec<ComponentTypeId> {
+            self.storage()
+                .archetype(index)
+                .unwrap()
+                .description()
+                .components()
+                .iter()
+                .map(|(type_id, _)| *type_id)
+                .collect()
+        };
+        let before = component_types(from);
+        let after = component_types(to);
+
+        for hook in &self.migrate_hooks {
+            hook(entity, &before, &after);
+        }
+    }
+
+    fn run_drop_hooks<F: Fn(ComponentTypeId) -> bool>(&self, location: EntityLocation, filter: F) {
+        if self.drop_hooks.is_empty() {
+            return;
+        }
+
+        if let Some(chunk) = self.storage().chunk(location) {
+            Self::run_chunk_drop_hooks(&self.drop_hooks, chunk, *location.component(), filter);
+        }
+    }
+
+    fn run_chunk_drop_hooks<F: Fn(ComponentTypeId) -> bool>(
+        hooks: &HashMap<ComponentTypeId, DropHook>,
+        chunk: &ComponentStorage,
+        index: usize,
+        filter: F,
+    ) {
+        for (type_id, hook) in hooks {
+            if !filter(*type_id) {
+                continue;
+            }
+
+            if let Some(components) = chunk.components(*type_id) {
+                unsafe {
+                    let (ptr, size, _) = components.data_raw_mut();
+                    hook(ptr.add(size * index));
+                }
+            }
+        }
+    }
+
+    fn delete_location(&mut self, location: EntityLocation) {
         // find entity's chunk
         let chunk = self.storage_mut().chunk_mut(location).unwrap();
 
@@ -479,6 +1579,7 @@ impl World {
         remove_tags: &[TagTypeId],
     ) -> &mut ComponentStorage {
         let location = self.entity_locations.get(entity).expect("entity not found");
+        self.run_drop_hooks(location, |type_id| remove_components.contains(&type_id));
 
         // find or create the target chunk
         let (target_arch_index, target_chunkset_index) = self.find_chunk_with_delta(
@@ -527,6 +1628,8 @@ impl World {
             ),
         );
 
+        self.run_migrate_hooks(entity, location.archetype(), target_arch_index);
+
         target_chunk
     }
 
@@ -542,11 +1645,15 @@ impl World {
         entity: Entity,
         component: T,
     ) -> Result<(), EntityMutationError> {
-        if !self.is_alive(entity) {
+        if !self.contains(entity) {
             return Err(EntityMutationError::DoesNotExist);
         }
 
-        if let Some(mut comp) = self.get_component_mut(entity) {
+        // safe because the &mut self ensures exclusivity
+        if let Some(mut comp) = unsafe { self.get_component_mut_unchecked::<T>(entity) } {
+            if let Some(hook) = self.drop_hooks.get(&ComponentTypeId::of::<T>()) {
+                hook(&mut *comp as *mut T as *mut u8);
+            }
             *comp = component;
             return Ok(());
         }
@@ -584,6 +1691,32 @@ impl World {
         Ok(())
     }
 
+    /// Adds a component with its default value to an entity, or resets it to its default value
+    /// if the component is already present.
+    ///
+    /// # Notes
+    /// This function has the same overhead as `add_component`.
+    pub fn add_component_default<T: Component + Default>(
+        &mut self,
+        entity: Entity,
+    ) -> Result<(), EntityMutationError> {
+        self.add_component(entity, T::default())
+    }
+
+    /// Overwrites an entity's existing component with a new value, without moving the entity to
+    /// another archetype.
+    ///
+    /// Returns `false`, leaving the entity unchanged, if the entity is not alive or does not have
+    /// the component. Unlike `add_component`, this never adds the component.
+    pub fn apply_component_patch<T: Component>(&mut self, entity: Entity, value: T) -> bool {
+        if let Some(mut component) = self.get_component_mut::<T>(entity) {
+            *component = value;
+            true
+        } else {
+            false
+        }
+    }
+
     /// Removes a component from an entity.
     ///
     /// # Notes
@@ -596,7 +1729,7 @@ impl World {
         &mut self,
         entity: Entity,
     ) -> Result<(), EntityMutationError> {
-        if !self.is_alive(entity) {
+        if !self.contains(entity) {
             return Err(EntityMutationError::DoesNotExist);
         }
 
@@ -615,6 +1748,117 @@ impl World {
         Ok(())
     }
 
+    /// Removes a component type from every entity in the world which has it, returning
+    /// the number of entities affected.
+    ///
+    /// # Notes
+    /// The entities in each chunk with `T` are moved in bulk into the archetype without `T`.
+    /// This is a bulk version of `remove_component`, useful for stripping a component type when
+    /// a feature is disabled.
+    pub fn remove_component_global<T: Component>(&mut self) -> usize {
+        let span = span!(
+            Level::TRACE,
+            "Removing component from all entities",
+            world = self.id().0,
+            component = std::any::type_name::<T>()
+        );
+        let _guard = span.enter();
+
+        let component_type = ComponentTypeId::of::<T>();
+        let sources = self
+            .storage()
+            .archetypes()
+            .iter()
+            .enumerate()
+            .filter(|(_, archetype)| {
+                archetype
+                    .description()
+                    .components()
+                    .iter()
+                    .any(|(type_id, _)| *type_id == component_type)
+            })
+            .map(|(index, _)| ArchetypeIndex(index))
+            .collect::<Vec<_>>();
+
+        let mut count = 0;
+        for source in sources {
+            let sets = self.storage().archetype(source).unwrap().len();
+            for set in (0..sets).map(SetIndex) {
+                let chunks = self.storage().archetype(source).unwrap().chunksets()[*set].len();
+                for chunk in (0..chunks).map(ChunkIndex) {
+                    count += self.remove_chunk_component(source, set, chunk, component_type);
+                }
+            }
+        }
+
+        trace!(count, "Removed component from entities");
+
+        count
+    }
+
+    /// Moves every entity in a chunk into the archetype without the given component type,
+    /// returning the number of entities moved.
+    fn remove_chunk_component(
+        &mut self,
+        archetype: ArchetypeIndex,
+        set: SetIndex,
+        chunk: ChunkIndex,
+        component_type: ComponentTypeId,
+    ) -> usize {
+        let location = EntityLocation::new(archetype, set, chunk, ComponentIndex(0));
+        let len = match self.storage().chunk(location) {
+            Some(source_chunk) if !source_chunk.is_empty() => source_chunk.len(),
+            _ => return 0,
+        };
+
+        for index in 0..len {
+            self.run_drop_hooks(
+                EntityLocation::new(archetype, set, chunk, ComponentIndex(index)),
+                |type_id| type_id == component_type,
+            );
+        }
+
+        let (target_arch_index, target_chunkset_index) =
+            self.find_chunk_with_delta(location, &[], &[component_type], &[], &[]);
+
+        // Safety Note:
+        // It is only safe for us to have 2 &mut references to storage here because
+        // the source and target archetypes differ by the removed component type.
+        let source_chunk = unsafe { &mut *self.storage.get() }
+            .chunk_mut(location)
+            .unwrap();
+        while !source_chunk.is_empty() {
+            let target_archetype = unsafe { &mut *self.storage.get() }
+                .archetype_mut(target_arch_index)
+                .unwrap();
+            let target_chunk_index = target_archetype.get_free_chunk(target_chunkset_index, 1);
+            let target_chunk = unsafe {
+                target_archetype
+                    .chunkset_unchecked_mut(target_chunkset_index)
+                    .chunk_unchecked_mut(target_chunk_index)
+            };
+
+            let start = target_chunk.len();
+            let moved = std::cmp::min(source_chunk.len(), target_chunk.capacity() - start);
+            source_chunk.move_entities(target_chunk, moved);
+
+            for (index, entity) in target_chunk.entities()[start..].iter().enumerate() {
+                self.entity_locations.set(
+                    *entity,
+                    EntityLocation::new(
+                        target_arch_index,
+                        target_chunkset_index,
+                        target_chunk_index,
+                        ComponentIndex(start + index),
+                    ),
+                );
+                self.run_migrate_hooks(*entity, archetype, target_arch_index);
+            }
+        }
+
+        len
+    }
+
     /// Removes
     ///
     /// # Notes
@@ -625,7 +1869,7 @@ impl World {
         &mut self,
         entity: Entity,
     ) -> Result<(), EntityMutationError> {
-        if !self.is_alive(entity) {
+        if !self.contains(entity) {
             return Err(EntityMutationError::DoesNotExist);
         }
 
@@ -636,14 +1880,81 @@ impl World {
             }
         }
 
-        self.move_entity(entity, &[], &components, &[], &[]);
-        Ok(())
+        self.move_entity(entity, &[], &components, &[], &[]);
+        Ok(())
+    }
+
+    /// Attaches a sparse component to an entity, returning the entity's previous `T` sparse
+    /// component, if it had one.
+    ///
+    /// Sparse components are stored in a side table outside of the entity's archetype, so
+    /// adding and removing them does not move the entity between archetypes. This makes them
+    /// suitable for transient markers which are added and removed every frame. Sparse components
+    /// are not visible to queries; use `sparse_iter` to iterate through them.
+    ///
+    /// Sparse components are dropped when their entity is deleted, but are not carried along
+    /// when entities are moved or cloned into other worlds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_core::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Colliding;
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// let entity = world.insert((), vec![(Position(1.0),)])[0];
+    ///
+    /// world.sparse_insert(entity, Colliding).unwrap();
+    /// assert_eq!(Some(&Colliding), world.sparse_get::<Colliding>(entity));
+    ///
+    /// assert_eq!(Some(Colliding), world.sparse_remove::<Colliding>(entity));
+    /// assert_eq!(None, world.sparse_get::<Colliding>(entity));
+    /// ```
+    pub fn sparse_insert<T: Component>(
+        &mut self,
+        entity: Entity,
+        component: T,
+    ) -> Result<Option<T>, EntityMutationError> {
+        if !self.is_alive(entity) {
+            return Err(EntityMutationError::DoesNotExist);
+        }
+
+        Ok(self.sparse.insert(entity, component))
+    }
+
+    /// Removes a sparse component from an entity, returning it if the entity had one.
+    pub fn sparse_remove<T: Component>(&mut self, entity: Entity) -> Option<T> {
+        self.sparse.column_mut::<T>()?.remove(&entity)
+    }
+
+    /// Borrows an entity's sparse component.
+    pub fn sparse_get<T: Component>(&self, entity: Entity) -> Option<&T> {
+        self.sparse.column::<T>()?.get(&entity)
+    }
+
+    /// Mutably borrows an entity's sparse component.
+    pub fn sparse_get_mut<T: Component>(&mut self, entity: Entity) -> Option<&mut T> {
+        self.sparse.column_mut::<T>()?.get_mut(&entity)
+    }
+
+    /// Iterates through all `T` sparse components, along with the entities they are attached to.
+    ///
+    /// The order of iteration is unspecified.
+    pub fn sparse_iter<T: Component>(&self) -> impl Iterator<Item = (Entity, &T)> + '_ {
+        self.sparse.column::<T>().into_iter().flat_map(|column| {
+            column
+                .iter()
+                .map(|(entity, component)| (*entity, component))
+        })
     }
 
     /// Adds a tag to an entity, or sets its value if the tag is
     /// already present.
     pub fn add_tag<T: Tag>(&mut self, entity: Entity, tag: T) -> Result<(), EntityMutationError> {
-        if !self.is_alive(entity) {
+        if !self.contains(entity) {
             return Err(EntityMutationError::DoesNotExist);
         }
 
@@ -676,7 +1987,7 @@ impl World {
 
     /// Removes a tag from an entity.
     pub fn remove_tag<T: Tag>(&mut self, entity: Entity) -> Result<(), EntityMutationError> {
-        if !self.is_alive(entity) {
+        if !self.contains(entity) {
             return Err(EntityMutationError::DoesNotExist);
         }
 
@@ -695,6 +2006,92 @@ impl World {
         Ok(())
     }
 
+    /// Overwrites the `T` components of every entity in the chunk containing the given location
+    /// with a copy of `data`.
+    ///
+    /// Locations can be found with `get_entity_location`. `data` must contain one component for
+    /// each entity in the chunk, in the order of the chunk's entities.
+    ///
+    /// Returns `false`, without writing anything, if the location is invalid, the chunk does not
+    /// contain `T` components, or `data` is not the same length as the chunk.
+    pub fn overwrite_chunk_components<T: Component + Copy>(
+        &mut self,
+        location: EntityLocation,
+        data: &[T],
+    ) -> bool {
+        match self.storage().chunk(location) {
+            Some(chunk) => chunk.overwrite_components(data),
+            None => false,
+        }
+    }
+
+    /// Calls `f` once for each chunk matching the view `V`, with the chunk's entities and a
+    /// tuple of its component slices.
+    ///
+    /// `V` is a tuple of `Read<T>` and `Write<T>`, which borrow `&[T]` and `&mut [T]`
+    /// respectively. This allows processing several component columns in bulk, without the
+    /// per-entity overhead of query iterators.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_core::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Velocity(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.insert((), vec![(Position(1.0), Velocity(0.5))]);
+    ///
+    /// world.for_each_chunk_mut::<(Write<Position>, Read<Velocity>), _>(|_, (pos, vel)| {
+    ///     for (pos, vel) in pos.iter_mut().zip(vel) {
+    ///         pos.0 += vel.0;
+    ///     }
+    /// });
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `V` contains duplicate component types.
+    pub fn for_each_chunk_mut<V, F>(&mut self, mut f: F)
+    where
+        V: IntoQuery + for<'a> ViewSlices<'a>,
+        F: for<'a> FnMut(&'a [Entity], <V as ViewSlices<'a>>::Slices),
+    {
+        let query = V::query();
+        for chunk in query.iter_chunks_mut(self) {
+            // safe because the &mut World ensures exclusivity, and the query
+            // has validated that the view contains no duplicate components
+            let slices = unsafe { chunk.slices() };
+            f(chunk.entities(), slices);
+        }
+    }
+
+    /// Enables or disables an entity.
+    ///
+    /// Disabled entities keep their ID and all of their data, but are excluded from queries
+    /// which have not opted in with `Query::include_disabled`. This is implemented by attaching
+    /// the `Disabled` tag to the entity, and so moves it into another chunk.
+    pub fn set_enabled(
+        &mut self,
+        entity: Entity,
+        enabled: bool,
+    ) -> Result<(), EntityMutationError> {
+        if enabled {
+            self.remove_tag::<Disabled>(entity)
+        } else if self.get_tag::<Disabled>(entity).is_none() {
+            self.add_tag(entity, Disabled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Determines if the given `Entity` is alive and has not been disabled.
+    pub fn is_enabled(&self, entity: Entity) -> bool {
+        self.is_alive(entity) && self.get_tag::<Disabled>(entity).is_none()
+    }
+
     fn get_component_storage(&self, entity: Entity) -> Option<&ComponentStorage> {
         let location = self.entity_locations.get(entity)?;
         self.storage().chunk(location)
@@ -727,6 +2124,170 @@ impl World {
         archetype.map(|archetype| archetype.description().tags())
     }
 
+    /// Registers the `Debug` implementation of component type `T` for use by `describe_entity`.
+    pub fn register_component_debug<T: Component + Debug>(&mut self) {
+        self.component_formatters
+            .insert(ComponentTypeId::of::<T>(), debug_fmt::<T>);
+    }
+
+    /// Registers the `Debug` implementation of tag type `T` for use by `describe_entity`.
+    pub fn register_tag_debug<T: Tag + Debug>(&mut self) {
+        self.tag_formatters
+            .insert(TagTypeId::of::<T>(), debug_fmt::<T>);
+    }
+
+    /// Produces a human-readable description of an entity, listing its archetype and the
+    /// names of all of its component and tag types, if the entity exists.
+    ///
+    /// Values are only printed for types which have been registered with
+    /// `register_component_debug` or `register_tag_debug`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_core::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.register_component_debug::<Position>();
+    /// let entity = world.insert((), vec![(Position(1.5),)])[0];
+    ///
+    /// let description = world.describe_entity(entity).unwrap();
+    /// assert!(description.contains("Position(1.5)"));
+    /// ```
+    pub fn describe_entity(&self, entity: Entity) -> Option<String> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+
+        let location = self.entity_locations.get(entity)?;
+        let archetype = self.storage().archetype(location.archetype())?;
+        let chunk = self.storage().chunk(location)?;
+        let description = archetype.description();
+
+        let mut out = String::new();
+        let _ = writeln!(out, "{:?} in {:?}", entity, archetype.id());
+
+        let _ = writeln!(out, "components:");
+        for ((type_id, _), name) in description
+            .components()
+            .iter()
+            .zip(description.component_names())
+        {
+            let value = self.component_formatters.get(type_id).and_then(|&fmt| {
+                let components = chunk.components(*type_id)?;
+                unsafe {
+                    let (ptr, size, _) = components.data_raw();
+                    Some(DebugValue(fmt, ptr.add(size * *location.component())))
+                }
+            });
+            let _ = match value {
+                Some(value) => writeln!(out, "  {}: {:?}", name, value),
+                None => writeln!(out, "  {}", name),
+            };
+        }
+
+        let _ = writeln!(out, "tags:");
+        for ((type_id, _), name) in description.tags().iter().zip(description.tag_names()) {
+            let value = self.tag_formatters.get(type_id).and_then(|&fmt| {
+                let tags = archetype.tags().get(*type_id)?;
+                unsafe {
+                    let (ptr, size, _) = tags.data_raw();
+                    Some(DebugValue(fmt, ptr.as_ptr().add(size * *location.set())))
+                }
+            });
+            let _ = match value {
+                Some(value) => writeln!(out, "  {}: {:?}", name, value),
+                None => writeln!(out, "  {}", name),
+            };
+        }
+
+        Some(out)
+    }
+
+    /// Registers component type `T` to be passed to visitors by `visit_entity`.
+    pub fn register_visit<T: Component>(&mut self) {
+        self.visitors
+            .insert(ComponentTypeId::of::<T>(), visit_component::<T>);
+    }
+
+    /// Calls the visitor once for each of the entity's components.
+    ///
+    /// Components of types registered with `register_visit` are passed to
+    /// `ComponentVisitor::visit`, while only the names of other component types are passed to
+    /// `ComponentVisitor::visit_unregistered`. This allows generic tools, such as inspectors,
+    /// to process entities without knowing their component types statically.
+    ///
+    /// Returns `false` if the entity does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_core::prelude::*;
+    /// # use legion_core::world::ComponentVisitor;
+    /// # use std::any::Any;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// struct Sum(f32);
+    ///
+    /// impl ComponentVisitor for Sum {
+    ///     fn visit(&mut self, _: &'static str, value: &dyn Any) {
+    ///         if let Some(pos) = value.downcast_ref::<Position>() {
+    ///             self.0 += pos.0;
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// world.register_visit::<Position>();
+    /// let entity = world.insert((), vec![(Position(1.5),)])[0];
+    ///
+    /// let mut sum = Sum(0.0);
+    /// world.visit_entity(entity, &mut sum);
+    /// assert_eq!(1.5, sum.0);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the entity's components are currently mutably borrowed.
+    pub fn visit_entity(&self, entity: Entity, visitor: &mut dyn ComponentVisitor) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+
+        let location = match self.entity_locations.get(entity) {
+            Some(location) => location,
+            None => return false,
+        };
+        let (archetype, chunk) = match (
+            self.storage().archetype(location.archetype()),
+            self.storage().chunk(location),
+        ) {
+            (Some(archetype), Some(chunk)) => (archetype, chunk),
+            _ => return false,
+        };
+
+        let description = archetype.description();
+        for ((type_id, _), name) in description
+            .components()
+            .iter()
+            .zip(description.component_names())
+        {
+            match (self.visitors.get(type_id), chunk.components(*type_id)) {
+                (Some(visit), Some(components)) => unsafe {
+                    // the borrow is held until the visitor returns
+                    let (ptr, size, _) = components.data_raw();
+                    visit(ptr.add(size * *location.component()), name, visitor);
+                },
+                _ => visitor.visit_unregistered(name),
+            }
+        }
+
+        true
+    }
+
     /// Iteratively defragments the world's internal memory.
     ///
     /// This compacts entities into fewer more continuous chunks.
@@ -1061,6 +2622,15 @@ impl World {
             archetype_data: archetype_data.deref(),
         };
 
+        // consecutive inserts usually share tag values, so check the most recently used chunk
+        // set before searching through every chunk set in the archetype
+        let hint = archetype_data.insert_hint();
+        if let Some(tags_at_hint) = tags.collect(chunk_filter_data).nth(*hint) {
+            if tags.is_match(&tags_at_hint).is_pass() {
+                return Some(hint);
+            }
+        }
+
         if let Some(i) = tags.matches(chunk_filter_data).matching_indices().next() {
             return Some(SetIndex(i));
         }
@@ -1080,10 +2650,79 @@ impl World {
     where
         T: TagSet + for<'a> Filter<ChunksetFilterData<'a>>,
     {
-        if let Some(i) = self.find_chunk_set(archetype, tags) {
+        let set = if let Some(i) = self.find_chunk_set(archetype, tags) {
             i
         } else {
             self.create_chunk_set(archetype, tags)
+        };
+
+        unsafe { self.storage_mut().archetype_unchecked_mut(archetype) }.set_insert_hint(set);
+        set
+    }
+
+    /// Borrows the world as a `WorldCell`, which allows components of multiple entities to be
+    /// mutated at once through a shared reference, with borrow rules enforced at runtime.
+    pub fn cell(&mut self) -> WorldCell { WorldCell::new(self) }
+
+    /// Freezes the world, such as for the read-only phase of a frame. The returned
+    /// `FrozenWorld` only provides shared access to the world until it is unfrozen.
+    pub fn freeze(&mut self) -> FrozenWorld<'_> { FrozenWorld::new(self) }
+
+    /// Updates every entity's `T` component from a snapshot of the values of `T` taken before
+    /// the update began, such that the result does not depend upon the order in which entities are visited.
+    ///
+    /// `f` is called for each entity which matches both `Write<T>` and the view `V`, and is given
+    /// the entity, its live `T` component, its `V` data and the `ComponentSnapshot<T>`. Reading another
+    /// entity's `T` from the snapshot will always observe its pre-update value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `V` reads or writes `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_core::prelude::*;
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Position(f32);
+    /// # #[derive(Copy, Clone, Debug, PartialEq)]
+    /// # struct Velocity(f32);
+    /// # let universe = Universe::new();
+    /// # let mut world = universe.create_world();
+    /// world.insert((), vec![(Position(1.0), Velocity(0.5)), (Position(2.0), Velocity(1.5))]);
+    ///
+    /// world.double_buffered_update::<Position, Read<Velocity>, _>(|_, pos, vel, _| {
+    ///     pos.0 += vel.0;
+    /// });
+    /// ```
+    pub fn double_buffered_update<'a, T, V, F>(&'a mut self, mut f: F)
+    where
+        T: Component + Clone,
+        V: for<'v> View<'v> + DefaultFilter,
+        F: FnMut(Entity, &mut T, <<V as View<'a>>::Iter as Iterator>::Item, &ComponentSnapshot<T>),
+    {
+        let snapshot = ComponentSnapshot {
+            values: <Read<T>>::query()
+                .iter_entities(self)
+                .map(|(entity, value)| (entity, (*value).clone()))
+                .collect(),
+        };
+
+        assert!(
+            !V::reads::<T>() && !V::writes::<T>(),
+            "the view of a double buffered update must not access the updated component"
+        );
+
+        let query = V::query();
+        for mut chunk in query.iter_chunks_mut(self) {
+            // safety: the view does not access `T`, and we hold the world exclusively
+            let mut values = match unsafe { chunk.components_mut_unchecked::<T>() } {
+                Some(values) => values,
+                None => continue,
+            };
+            for ((entity, data), value) in chunk.iter_entities_mut().zip(values.iter_mut()) {
+                f(entity, value, data, &snapshot);
+            }
         }
     }
 
@@ -1151,6 +2790,10 @@ impl EntityStore for World {
         };
         let component = slice.get(*location.component())?;
 
+        if let Some(stats) = self.access_stats() {
+            stats.record_read(ComponentTypeId::of::<T>(), 1);
+        }
+
         Some(Ref::new(slice_borrow, component))
     }
 
@@ -1165,11 +2808,14 @@ impl EntityStore for World {
 
         let location = self.entity_locations.get(entity)?;
         let chunk = self.storage().chunk(location)?;
-        let (slice_borrow, slice) = chunk
-            .components(ComponentTypeId::of::<T>())?
-            .data_slice_mut::<T>()
-            .deconstruct();
+        let set = chunk.components(ComponentTypeId::of::<T>())?;
+        let (slice_borrow, slice) = set.data_slice_mut_tracked::<T>().deconstruct();
         let component = slice.get_mut(*location.component())?;
+        set.touch_element(*location.component());
+
+        if let Some(stats) = self.access_stats() {
+            stats.record_write(ComponentTypeId::of::<T>(), 1);
+        }
 
         Some(RefMut::new(slice_borrow, component))
     }
@@ -1187,9 +2833,28 @@ impl EntityStore for World {
         unsafe { tags.data_slice::<T>().get(*location.set()) }
     }
 
+    #[inline]
+    fn has_tag<T: Tag>(&self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+
+        // only the archetype's layout is inspected; the tag data is not accessed
+        self.entity_locations
+            .get(entity)
+            .and_then(|location| self.storage().archetype(location.archetype()))
+            .map(|archetype| archetype.tags().get(TagTypeId::of::<T>()).is_some())
+            .unwrap_or(false)
+    }
+
     #[inline]
     fn is_alive(&self, entity: Entity) -> bool { self.entity_allocator.is_alive(entity) }
 
+    #[inline]
+    fn get_entity_location(&self, entity: Entity) -> Option<EntityLocation> {
+        World::get_entity_location(self, entity)
+    }
+
     #[inline]
     fn get_component_storage<V: for<'b> View<'b>>(
         &self,
@@ -1198,10 +2863,43 @@ impl EntityStore for World {
     }
 }
 
+/// Removes the components written for a partially cloned entity when dropped.
+struct CloneGuard<'a> {
+    components: &'a mut Components,
+    component_types: &'a [ComponentTypeId],
+    index: usize,
+    written: usize,
+}
+
+impl<'a> Drop for CloneGuard<'a> {
+    fn drop(&mut self) {
+        for type_id in &self.component_types[..self.written] {
+            let mut writer = self.components.get_mut(*type_id).unwrap().writer();
+            writer.swap_remove(self.index, true);
+        }
+    }
+}
+
 impl Default for World {
     fn default() -> Self { Self::new() }
 }
 
+/// A copy of the values of component `T` taken at the start of `World::double_buffered_update`.
+pub struct ComponentSnapshot<T> {
+    values: HashMap<Entity, T>,
+}
+
+impl<T> ComponentSnapshot<T> {
+    /// Gets the value the entity's component held before the update began.
+    pub fn get(&self, entity: Entity) -> Option<&T> { self.values.get(&entity) }
+
+    /// Gets the number of components in the snapshot.
+    pub fn len(&self) -> usize { self.values.len() }
+
+    /// Determines if the snapshot is empty.
+    pub fn is_empty(&self) -> bool { self.values.is_empty() }
+}
+
 /// Describes how to handle a `clone_from`. Allows the user to transform components from one type
 /// to another and provide their own implementation for cloning/transforming
 pub trait CloneImpl {
@@ -1357,6 +3055,42 @@ pub enum EntityMutationError {
     DoesNotExist,
 }
 
+/// Rolls back an insert when dropped, removing the entities written into each chunk since the
+/// insert began and freeing their IDs.
+///
+/// The recorded chunks are cleared once the insert completes, so that this only has an effect if
+/// the component source panics partway through the insert.
+struct InsertGuard {
+    storage: *mut Storage,
+    allocator: Arc<EntityAllocator>,
+    archetype: ArchetypeIndex,
+    set: SetIndex,
+    chunks: Vec<(ChunkIndex, usize)>,
+}
+
+impl Drop for InsertGuard {
+    fn drop(&mut self) {
+        if self.chunks.is_empty() {
+            return;
+        }
+
+        let archetype = unsafe { (&mut *self.storage).archetype_unchecked_mut(self.archetype) };
+        for &(chunk_index, len) in &self.chunks {
+            let chunk = unsafe {
+                archetype
+                    .chunkset_unchecked_mut(self.set)
+                    .chunk_unchecked_mut(chunk_index)
+            };
+            while chunk.len() > len {
+                let last = chunk.len() - 1;
+                let entity = chunk.entities()[last];
+                chunk.swap_remove(ComponentIndex(last), true);
+                self.allocator.delete_entity(entity);
+            }
+        }
+    }
+}
+
 /// Describes the types of a set of components attached to an entity.
 pub trait ComponentLayout: Sized {
     /// A filter type which filters archetypes to an exact match with this layout.
@@ -1715,6 +3449,14 @@ mod tuple_impls {
                 fn is_match(&self, item: &<Self::Iter as Iterator>::Item) -> Option<bool> {
                     #![allow(non_snake_case)]
                     let ($( $ty, )*) = self;
+                    $(
+                        debug_assert!(
+                            is_reflexive($ty),
+                            "tag value of type `{}` is not equal to itself (is it NaN?); \
+                            tags must have reflexive equality to be grouped into chunks",
+                            std::any::type_name::<$ty>()
+                        );
+                    )*
                     Some(($( &*$ty, )*).legion_eq(item))
                 }
             }
@@ -1734,6 +3476,9 @@ mod tuple_impls {
         };
     }
 
+    #[allow(clippy::eq_op)]
+    fn is_reflexive<T: PartialEq>(value: &T) -> bool { value == value }
+
     impl_data_tuple!();
     impl_data_tuple!(A => a);
     impl_data_tuple!(A => a, B => b);
@@ -1963,6 +3708,162 @@ mod tests {
         universe.create_world();
     }
 
+    #[test]
+    #[should_panic(expected = "power of two")]
+    fn universe_block_size_not_power_of_two() { Universe::with_block_size(1000); }
+
+    #[test]
+    fn insert_reserves_locations() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let universe = Universe::new();
+        let mut world = universe.create_world_in_range(5 << 20, 100_000).unwrap();
+
+        // the location list is sized for the highest index in the batch before any are recorded
+        let entities = world.insert((), (0..10_000).map(|i| (Pos(i as f32, 0., 0.),)));
+        assert!(entities.iter().all(|e| e.index() >= 5 << 20));
+        assert_eq!(0, world.entity_locations.reallocations);
+    }
+
+    #[test]
+    fn insert_rolls_back_on_panic() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let _ = tracing_subscriber::fmt::try_init();
+
+        struct Counted(Arc<AtomicUsize>);
+
+        impl Drop for Counted {
+            fn drop(&mut self) { self.0.fetch_add(1, Ordering::SeqCst); }
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let mut world = create();
+        let existing = world
+            .insert_with_chunk_limit(
+                (),
+                (0..3).map(|i| (Pos(i as f32, 0., 0.), Counted(drops.clone()))),
+                2,
+            )
+            .to_vec();
+
+        // the 3rd component panics, after filling one new chunk and beginning another
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            world.insert_with_chunk_limit(
+                (),
+                (0..5).map(|i| {
+                    if i == 2 {
+                        panic!("failed to construct component");
+                    }
+                    (Pos(10., 0., 0.), Counted(drops.clone()))
+                }),
+                2,
+            );
+        }));
+        assert!(result.is_err());
+
+        // the components written before the panic were dropped, and the world is unchanged
+        assert_eq!(2, drops.load(Ordering::SeqCst));
+        assert!(world.entity_locations_valid());
+        let query = Read::<Pos>::query();
+        let mut remaining = query.iter_entities(&world).collect::<Vec<_>>();
+        remaining.sort_by_key(|(e, _)| e.index());
+        assert_eq!(
+            existing,
+            remaining.iter().map(|(e, _)| *e).collect::<Vec<_>>()
+        );
+        assert!(remaining.iter().all(|(_, pos)| pos.0 < 3.));
+        drop(remaining);
+
+        // the IDs allocated during the failed insert were freed for reuse
+        let entity = world.insert((), vec![(Pos(0., 0., 0.),)])[0];
+        assert!(entity.index() < 5);
+        assert!(world.entity_locations_valid());
+    }
+
+    #[test]
+    fn insert_with_chunk_limit_restores_capacity_on_panic() {
+        let mut world = create();
+
+        // the 4th component panics while it is being written into the second chunk
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            world.insert_with_chunk_limit(
+                (),
+                (0..5).map(|i| {
+                    if i == 3 {
+                        panic!("failed to construct component");
+                    }
+                    (Pos(i as f32, 0., 0.),)
+                }),
+                2,
+            );
+        }));
+        assert!(result.is_err());
+
+        // chunks left behind by the failed insert have their full capacity restored
+        let capacities = world
+            .storage()
+            .archetypes()
+            .iter()
+            .flat_map(|archetype| archetype.chunksets())
+            .flat_map(|set| set.iter())
+            .map(|chunk| chunk.capacity())
+            .collect::<Vec<_>>();
+        assert!(!capacities.is_empty());
+        assert!(capacities.iter().all(|capacity| *capacity > 2));
+    }
+
+    #[test]
+    fn insert_reuses_recent_chunk_set() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static COMPARISONS: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Clone, Copy, Debug)]
+        struct Layer(u32);
+
+        impl PartialEq for Layer {
+            fn eq(&self, other: &Self) -> bool {
+                COMPARISONS.fetch_add(1, Ordering::SeqCst);
+                self.0 == other.0
+            }
+        }
+
+        let mut world = create();
+        for i in 0..100 {
+            world.insert((Layer(i),), vec![(Pos(0., 0., 0.),)]);
+        }
+
+        // repeated inserts with the same tag value do not search every chunk set
+        COMPARISONS.store(0, Ordering::SeqCst);
+        for _ in 0..10 {
+            world.insert((Layer(99),), vec![(Pos(0., 0., 0.),)]);
+        }
+        assert!(COMPARISONS.load(Ordering::SeqCst) <= 20);
+
+        // other tag values are still found in their existing chunk sets
+        world.insert((Layer(5),), vec![(Pos(0., 0., 0.),)]);
+        world.insert((Layer(99),), vec![(Pos(0., 0., 0.),)]);
+
+        let archetype = &world.storage().archetypes()[0];
+        assert_eq!(100, archetype.chunksets().len());
+        let layers = unsafe {
+            archetype
+                .tags()
+                .get(TagTypeId::of::<Layer>())
+                .unwrap()
+                .data_slice::<Layer>()
+        };
+        let counts = archetype
+            .chunksets()
+            .iter()
+            .zip(layers)
+            .map(|(set, layer)| (layer.0, set.occupied().iter().map(|c| c.len()).sum()))
+            .filter(|(_, count): &(u32, usize)| *count > 1)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![(5, 2), (99, 12)], counts);
+    }
+
     #[test]
     fn insert_many() {
         let _ = tracing_subscriber::fmt::try_init();
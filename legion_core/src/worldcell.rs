@@ -0,0 +1,273 @@
+use crate::{
+    entity::Entity,
+    storage::{Component, ComponentTypeId},
+    world::{EntityStore, World},
+};
+use fxhash::FxHashMap;
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+
+type Borrows = RefCell<FxHashMap<(Entity, ComponentTypeId), isize>>;
+
+/// Provides access to the components of a `World` through a shared reference, with borrow rules
+/// enforced at runtime for each individual entity component.
+///
+/// This allows single-threaded code to hold mutable references to components of several entities
+/// at once, which is useful when the world is stored behind an `Rc<RefCell<World>>`.
+///
+/// A `WorldCell` is constructed with `World::cell`.
+///
+/// # Examples
+///
+/// ```
+/// # use legion_core::prelude::*;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Position(f32);
+/// # let universe = Universe::new();
+/// # let mut world = universe.create_world();
+/// let entities = world.insert((), vec![(Position(1.0),), (Position(2.0),)]).to_vec();
+///
+/// let cell = world.cell();
+/// let mut a = cell.get_component_mut::<Position>(entities[0]).unwrap();
+/// let mut b = cell.get_component_mut::<Position>(entities[1]).unwrap();
+/// std::mem::swap(&mut *a, &mut *b);
+/// ```
+pub struct WorldCell<'a> {
+    world: &'a World,
+    borrows: Borrows,
+}
+
+impl<'a> WorldCell<'a> {
+    pub(crate) fn new(world: &'a mut World) -> Self {
+        Self {
+            world,
+            borrows: RefCell::default(),
+        }
+    }
+
+    /// Borrows an entity's component immutably.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component is already mutably borrowed from this cell.
+    pub fn get_component<T: Component>(&self, entity: Entity) -> Option<CellRef<T>> {
+        // record the borrow before creating any reference to the component, so that a shared
+        // reference is never created while a mutable one exists
+        let key = (entity, ComponentTypeId::of::<T>());
+        {
+            let mut borrows = self.borrows.borrow_mut();
+            let state = borrows.entry(key).or_insert(0);
+            if *state < 0 {
+                panic!(
+                    "component already borrowed as mutable: {} of {:?}",
+                    std::any::type_name::<T>(),
+                    entity
+                );
+            }
+            *state += 1;
+        }
+
+        let component = match self.world.get_component::<T>(entity) {
+            Some(component) => component,
+            None => {
+                release(&self.borrows, key);
+                return None;
+            }
+        };
+        let ptr = &*component as *const T;
+
+        // release the chunk borrow; the cell's exclusive borrow of the world
+        // ensures that it can not be mutated elsewhere
+        drop(component);
+
+        Some(CellRef {
+            borrows: &self.borrows,
+            key,
+            value: unsafe { &*ptr },
+        })
+    }
+
+    /// Borrows an entity's component mutably.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the component is already borrowed from this cell.
+    pub fn get_component_mut<T: Component>(&self, entity: Entity) -> Option<CellRefMut<T>> {
+        // record the borrow before creating any reference to the component, so that a mutable
+        // reference is never created while any other reference exists
+        let key = (entity, ComponentTypeId::of::<T>());
+        {
+            let mut borrows = self.borrows.borrow_mut();
+            if borrows.contains_key(&key) {
+                panic!(
+                    "component already borrowed: {} of {:?}",
+                    std::any::type_name::<T>(),
+                    entity
+                );
+            }
+            borrows.insert(key, -1);
+        }
+
+        // safe because the cell holds an exclusive borrow of the world, and the borrow of this
+        // individual component has been recorded above
+        let mut component = match unsafe { self.world.get_component_mut_unchecked::<T>(entity) } {
+            Some(component) => component,
+            None => {
+                self.borrows.borrow_mut().remove(&key);
+                return None;
+            }
+        };
+        let ptr = &mut *component as *mut T;
+        drop(component);
+
+        Some(CellRefMut {
+            borrows: &self.borrows,
+            key,
+            value: unsafe { &mut *ptr },
+        })
+    }
+}
+
+/// Releases a shared borrow recorded in `borrows`.
+fn release(borrows: &Borrows, key: (Entity, ComponentTypeId)) {
+    let mut borrows = borrows.borrow_mut();
+    if let Some(state) = borrows.get_mut(&key) {
+        *state -= 1;
+        if *state == 0 {
+            borrows.remove(&key);
+        }
+    }
+}
+
+/// An immutable borrow of a component from a `WorldCell`.
+pub struct CellRef<'c, T> {
+    borrows: &'c Borrows,
+    key: (Entity, ComponentTypeId),
+    value: &'c T,
+}
+
+impl<'c, T> Deref for CellRef<'c, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T { self.value }
+}
+
+impl<'c, T> Drop for CellRef<'c, T> {
+    fn drop(&mut self) { release(self.borrows, self.key); }
+}
+
+/// A mutable borrow of a component from a `WorldCell`.
+pub struct CellRefMut<'c, T> {
+    borrows: &'c Borrows,
+    key: (Entity, ComponentTypeId),
+    value: &'c mut T,
+}
+
+impl<'c, T> Deref for CellRefMut<'c, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T { self.value }
+}
+
+impl<'c, T> DerefMut for CellRefMut<'c, T> {
+    fn deref_mut(&mut self) -> &mut T { self.value }
+}
+
+impl<'c, T> Drop for CellRefMut<'c, T> {
+    fn drop(&mut self) { self.borrows.borrow_mut().remove(&self.key); }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Pos(f32, f32, f32);
+
+    #[test]
+    fn mutate_different_entities() {
+        let mut world = World::new();
+        let entities = world
+            .insert((), vec![(Pos(1., 2., 3.),), (Pos(4., 5., 6.),)])
+            .to_vec();
+
+        {
+            let cell = world.cell();
+            let mut a = cell.get_component_mut::<Pos>(entities[0]).unwrap();
+            let mut b = cell.get_component_mut::<Pos>(entities[1]).unwrap();
+            a.0 = 7.;
+            b.0 = 8.;
+        }
+
+        assert_eq!(
+            Pos(7., 2., 3.),
+            *world.get_component::<Pos>(entities[0]).unwrap()
+        );
+        assert_eq!(
+            Pos(8., 5., 6.),
+            *world.get_component::<Pos>(entities[1]).unwrap()
+        );
+    }
+
+    #[test]
+    fn reborrow_after_release() {
+        let mut world = World::new();
+        let entity = world.insert((), vec![(Pos(1., 2., 3.),)])[0];
+
+        let cell = world.cell();
+        {
+            let _a = cell.get_component::<Pos>(entity).unwrap();
+            let _b = cell.get_component::<Pos>(entity).unwrap();
+        }
+        let mut a = cell.get_component_mut::<Pos>(entity).unwrap();
+        a.1 = 0.;
+    }
+
+    #[test]
+    #[should_panic(expected = "already borrowed")]
+    fn alias_same_component() {
+        let mut world = World::new();
+        let entity = world.insert((), vec![(Pos(1., 2., 3.),)])[0];
+
+        let cell = world.cell();
+        let _a = cell.get_component_mut::<Pos>(entity).unwrap();
+        let _b = cell.get_component_mut::<Pos>(entity).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "already borrowed as mutable")]
+    fn read_while_written() {
+        let mut world = World::new();
+        let entity = world.insert((), vec![(Pos(1., 2., 3.),)])[0];
+
+        let cell = world.cell();
+        let _a = cell.get_component_mut::<Pos>(entity).unwrap();
+        let _b = cell.get_component::<Pos>(entity).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "already borrowed")]
+    fn write_while_read() {
+        let mut world = World::new();
+        let entity = world.insert((), vec![(Pos(1., 2., 3.),)])[0];
+
+        let cell = world.cell();
+        let _a = cell.get_component::<Pos>(entity).unwrap();
+        let _b = cell.get_component_mut::<Pos>(entity).unwrap();
+    }
+
+    #[test]
+    fn missing_component_not_borrowed() {
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        struct Vel(f32);
+
+        let mut world = World::new();
+        let entity = world.insert((), vec![(Pos(1., 2., 3.),)])[0];
+
+        let cell = world.cell();
+        assert!(cell.get_component::<Vel>(entity).is_none());
+        assert!(cell.get_component_mut::<Vel>(entity).is_none());
+        assert!(cell.get_component_mut::<Vel>(entity).is_none());
+        assert!(cell.borrows.borrow().is_empty());
+    }
+}
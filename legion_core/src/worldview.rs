@@ -0,0 +1,224 @@
+use crate::{
+    borrow::{Ref, RefMut},
+    cons::ConsContains,
+    entity::Entity,
+    storage::{Component, Tag},
+    world::{EntityStore, World},
+};
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+/// Provides access to the components of an `EntityStore`, restricted at compile time to the
+/// component types declared in its type parameters.
+///
+/// `R` and `W` are cons lists (nested tuples ending in `()`, such as `(A, (B, ()))`) of the
+/// component types which may be read and written, respectively. Reading a component not listed
+/// in `R`, or writing a component not listed in `W`, fails to compile rather than panicking at
+/// runtime. Components which are both read and written must be listed in both.
+///
+/// The accessor methods have a second type parameter describing where the component is found
+/// within the list, which is always inferred. It can be elided with `_` when using turbofish
+/// syntax, as in `view.get_component::<Position, _>(entity)`.
+///
+/// # Examples
+///
+/// ```
+/// # use legion_core::prelude::*;
+/// # use legion_core::worldview::WorldView;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Position(f32);
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Velocity(f32);
+/// # let universe = Universe::new();
+/// # let mut world = universe.create_world();
+/// let entity = world.insert((), vec![(Position(1.0), Velocity(0.5))])[0];
+///
+/// let mut view = WorldView::<(Velocity, ()), (Position, ()), _>::new(&mut world);
+/// let velocity = *view.get_component::<Velocity, _>(entity).unwrap();
+/// view.get_component_mut::<Position, _>(entity).unwrap().0 += velocity.0;
+/// ```
+///
+/// Reading a component which is not declared in `R` does not compile:
+///
+/// ```compile_fail
+/// # use legion_core::prelude::*;
+/// # use legion_core::worldview::WorldView;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Position(f32);
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Velocity(f32);
+/// # let universe = Universe::new();
+/// # let mut world = universe.create_world();
+/// # let entity = world.insert((), vec![(Position(1.0), Velocity(0.5))])[0];
+/// let view = WorldView::<(Velocity, ()), (), _>::new(&mut world);
+/// view.get_component::<Position, _>(entity);
+/// ```
+///
+/// Nor does writing a component which is not declared in `W`, even if it may be read:
+///
+/// ```compile_fail
+/// # use legion_core::prelude::*;
+/// # use legion_core::worldview::WorldView;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Position(f32);
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Velocity(f32);
+/// # let universe = Universe::new();
+/// # let mut world = universe.create_world();
+/// # let entity = world.insert((), vec![(Position(1.0), Velocity(0.5))])[0];
+/// let mut view = WorldView::<(Velocity, ()), (), _>::new(&mut world);
+/// view.get_component_mut::<Velocity, _>(entity);
+/// ```
+pub struct WorldView<'a, R, W, S: EntityStore> {
+    store: &'a mut S,
+    access: PhantomData<fn() -> (R, W)>,
+}
+
+impl<'a, R, W, S: EntityStore> WorldView<'a, R, W, S> {
+    /// Creates a new view of the given store.
+    pub fn new(store: &'a mut S) -> Self {
+        Self {
+            store,
+            access: PhantomData,
+        }
+    }
+
+    /// Borrows component data for the given entity.
+    ///
+    /// Returns `Some(data)` if the entity was found and contains the specified data.
+    /// Otherwise `None` is returned.
+    pub fn get_component<T: Component, I>(&self, entity: Entity) -> Option<Ref<'_, T>>
+    where
+        R: ConsContains<T, I>,
+    {
+        self.store.get_component(entity)
+    }
+
+    /// Mutably borrows component data for the given entity.
+    ///
+    /// Returns `Some(data)` if the entity was found and contains the specified data.
+    /// Otherwise `None` is returned.
+    pub fn get_component_mut<T: Component, I>(&mut self, entity: Entity) -> Option<RefMut<'_, T>>
+    where
+        W: ConsContains<T, I>,
+    {
+        self.store.get_component_mut(entity)
+    }
+
+    /// Gets tag data for the given entity.
+    pub fn get_tag<T: Tag>(&self, entity: Entity) -> Option<&T> { self.store.get_tag(entity) }
+
+    /// Determines if the given `Entity` is alive.
+    pub fn is_alive(&self, entity: Entity) -> bool { self.store.is_alive(entity) }
+
+    /// Gets the store this view provides access to.
+    pub fn into_inner(self) -> &'a mut S { self.store }
+}
+
+/// A world which can not be mutated until it is unfrozen.
+///
+/// A `FrozenWorld` holds the world's exclusive borrow, while only dereferencing to a shared
+/// `&World`. This guarantees that no structural changes or component writes can occur while it
+/// is frozen, and `&FrozenWorld` can be shared freely, including across threads. Queries may only
+/// be run with read-only views, by passing `&*frozen` as the world.
+///
+/// A `FrozenWorld` is constructed with `World::freeze`, and `unfreeze` releases the world for
+/// mutation.
+///
+/// # Examples
+///
+/// ```
+/// # use legion_core::prelude::*;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Position(f32);
+/// # let universe = Universe::new();
+/// # let mut world = universe.create_world();
+/// world.insert((), vec![(Position(1.0),), (Position(2.0),)]);
+///
+/// let frozen = world.freeze();
+/// let query = Read::<Position>::query();
+/// let sum: f32 = query.iter(&*frozen).map(|pos| pos.0).sum();
+/// assert_eq!(3.0, sum);
+///
+/// let world = frozen.unfreeze();
+/// world.insert((), vec![(Position(sum),)]);
+/// ```
+///
+/// Mutating the world while it is frozen does not compile:
+///
+/// ```compile_fail
+/// # use legion_core::prelude::*;
+/// # #[derive(Copy, Clone, Debug, PartialEq)]
+/// # struct Position(f32);
+/// # let universe = Universe::new();
+/// # let mut world = universe.create_world();
+/// let frozen = world.freeze();
+/// frozen.insert((), vec![(Position(1.0),)]);
+/// ```
+pub struct FrozenWorld<'a> {
+    world: &'a mut World,
+}
+
+impl<'a> FrozenWorld<'a> {
+    pub(crate) fn new(world: &'a mut World) -> Self { Self { world } }
+
+    /// Releases the world for mutation.
+    pub fn unfreeze(self) -> &'a mut World { self.world }
+}
+
+impl<'a> Deref for FrozenWorld<'a> {
+    type Target = World;
+
+    fn deref(&self) -> &World { self.world }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Pos(f32);
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Vel(f32);
+
+    #[test]
+    fn read_and_write_declared() {
+        let mut world = World::new();
+        let entity = world.insert((), vec![(Pos(1.), Vel(2.))])[0];
+
+        {
+            let mut view = WorldView::<(Pos, (Vel, ())), (Pos, ()), _>::new(&mut world);
+            let vel = *view.get_component::<Vel, _>(entity).unwrap();
+            view.get_component_mut::<Pos, _>(entity).unwrap().0 += vel.0;
+            assert_eq!(Pos(3.), *view.get_component::<Pos, _>(entity).unwrap());
+        }
+
+        assert_eq!(Pos(3.), *world.get_component::<Pos>(entity).unwrap());
+    }
+
+    #[test]
+    fn frozen_shared_reads() {
+        let mut world = World::new();
+        let entities = world.insert((), (0..4).map(|i| (Pos(i as f32),))).to_vec();
+
+        let frozen = world.freeze();
+        let shared = &frozen;
+        let query = Read::<Pos>::query();
+        let sums = std::thread::scope(|scope| {
+            let handles = (0..2)
+                .map(|_| scope.spawn(|| query.iter(&**shared).map(|pos| pos.0).sum::<f32>()))
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(vec![6., 6.], sums);
+        assert_eq!(Pos(2.), *frozen.get_component::<Pos>(entities[2]).unwrap());
+
+        let world = frozen.unfreeze();
+        world.delete(entities[0]);
+        assert!(!world.is_alive(entities[0]));
+    }
+}
@@ -10,7 +10,7 @@ pub mod prelude {
     pub use crate::{
         bit_set::BitSet,
         resource::{ResourceSet, Resources},
-        schedule::{Executor, Runnable, Schedulable, Schedule},
+        schedule::{Executor, PhasedExecutor, Runnable, Schedulable, Schedule, SystemGroup},
         System, SystemBuilder,
     };
 }
@@ -3,9 +3,10 @@ use fxhash::FxHashMap;
 use legion_core::borrow::{AtomicRefCell, Ref, RefMut};
 use legion_core::query::{Read, ReadOnly, Write};
 use std::{
-    any::TypeId,
+    any::{Any, TypeId},
     marker::PhantomData,
     ops::{Deref, DerefMut},
+    sync::OnceLock,
 };
 
 #[cfg(not(feature = "ffi"))]
@@ -193,60 +194,467 @@ impl<'a, T: 'a + Resource + std::fmt::Debug> std::fmt::Debug for FetchMut<'a, T>
     }
 }
 
+/// A resource which holds two values of `T`: the `current` value, which was written during the
+/// previous frame, and the `next` value, which is being written during this frame.
+///
+/// Double buffered resources are inserted with `Resources::insert_double_buffered`, and are
+/// swapped by `Resources::swap_double_buffers`, which is usually called at the end of each frame.
+/// This allows producer systems to publish data (e.g. event queues) which consumer systems
+/// observe in the following frame, without manual clearing or swapping.
+#[derive(Default, Debug)]
+pub struct DoubleBuffered<T> {
+    current: T,
+    next: T,
+}
+
+impl<T: Default> DoubleBuffered<T> {
+    /// Gets the value written during the previous frame.
+    pub fn current(&self) -> &T { &self.current }
+
+    /// Gets the value being written during this frame.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> &mut T { &mut self.next }
+
+    /// Makes the next value current, and resets the next value to its default.
+    pub fn swap(&mut self) { self.current = std::mem::take(&mut self.next); }
+}
+
+/// A resource which queues events of type `T` for other systems to read.
+///
+/// Events are held for two frames: those sent during the current frame and those sent during the
+/// previous frame. `update` discards the older buffer, and is usually called at the end of each
+/// frame by `Resources::swap_double_buffers` after inserting the resource with
+/// `Resources::insert_events`. Each consumer reads events through its own `EventReader`, which
+/// yields every event exactly once as long as the consumer reads at least once per frame.
+#[derive(Debug)]
+pub struct Events<T> {
+    previous: Vec<T>,
+    current: Vec<T>,
+    previous_start: usize,
+}
+
+impl<T> Default for Events<T> {
+    fn default() -> Self {
+        Self {
+            previous: Vec::new(),
+            current: Vec::new(),
+            previous_start: 0,
+        }
+    }
+}
+
+impl<T> Events<T> {
+    /// Queues an event to be read by consumers.
+    pub fn send(&mut self, event: T) { self.current.push(event); }
+
+    /// Creates a reader which will yield all events sent after its creation.
+    pub fn reader(&self) -> EventReader<T> {
+        EventReader {
+            next: self.end(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Discards events sent before the previous call to `update`.
+    pub fn update(&mut self) {
+        self.previous_start += self.previous.len();
+        self.previous = std::mem::take(&mut self.current);
+    }
+
+    /// Removes all queued events, yielding those sent during the previous and current frames.
+    pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
+        self.previous_start += self.previous.len() + self.current.len();
+        self.previous.drain(..).chain(self.current.drain(..))
+    }
+
+    /// Gets the number of queued events.
+    pub fn len(&self) -> usize { self.previous.len() + self.current.len() }
+
+    /// Determines if there are no queued events.
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    fn end(&self) -> usize { self.previous_start + self.len() }
+}
+
+/// Tracks which events a consumer has read from an `Events<T>` resource.
+#[derive(Debug)]
+pub struct EventReader<T> {
+    next: usize,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for EventReader<T> {
+    fn default() -> Self {
+        Self {
+            next: 0,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> EventReader<T> {
+    /// Gets the events which have been sent since this reader last read from `events`.
+    ///
+    /// Events which were discarded by `Events::update` before being read are skipped.
+    pub fn read<'a>(&mut self, events: &'a Events<T>) -> impl Iterator<Item = &'a T> {
+        let skip = self.next.saturating_sub(events.previous_start);
+        self.next = events.end();
+        events
+            .previous
+            .iter()
+            .chain(events.current.iter())
+            .skip(skip)
+    }
+}
+
+/// Ergonomic wrapper type which contains a `Ref` to a resource, viewed as the trait object `T`.
+pub struct FetchDyn<'a, T: ?Sized + 'static> {
+    inner: Ref<'a, Box<dyn Resource>>,
+    cast: &'a DynCast<T>,
+}
+
+impl<'a, T: ?Sized + 'static> Deref for FetchDyn<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target { (self.cast.cast)(&**self.inner) }
+}
+
+/// Converts the resource registered for a trait object type into a reference to that trait object.
+struct DynCast<T: ?Sized> {
+    resource: ResourceTypeId,
+    cast: Box<dyn Fn(&dyn Resource) -> &T + Send + Sync>,
+}
+
+/// A factory registered with `Resources::register_loader`, along with the resource it constructs
+/// on first access.
+struct Loader {
+    load: Box<dyn Fn() -> Box<dyn Resource> + Send + Sync>,
+    value: OnceLock<AtomicRefCell<Box<dyn Resource>>>,
+}
+
 /// Resources container. This container stores its underlying resources in a `FxHashMap` keyed on
 /// `ResourceTypeId`. This means that the ID's used in this storage will not persist between recompiles.
 #[derive(Default)]
 pub struct Resources {
     storage: FxHashMap<ResourceTypeId, AtomicRefCell<Box<dyn Resource>>>,
+    dyn_casts: FxHashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    double_buffers: FxHashMap<ResourceTypeId, fn(&mut dyn Resource)>,
+    loaders: FxHashMap<ResourceTypeId, Loader>,
 }
 
 impl Resources {
     /// Returns `true` if type `T` exists in the store. Otherwise, returns `false`
+    ///
+    /// Resources with a registered loader are only considered to exist once they have been
+    /// constructed.
     pub fn contains<T: Resource>(&self) -> bool {
-        self.storage.contains_key(&ResourceTypeId::of::<T>())
+        let type_id = ResourceTypeId::of::<T>();
+        self.storage.contains_key(&type_id)
+            || self
+                .loaders
+                .get(&type_id)
+                .and_then(|loader| loader.value.get())
+                .is_some()
+    }
+
+    /// Registers a factory which constructs `T` the first time it is fetched, if it has not
+    /// already been inserted. This defers the cost of constructing resources which may not be
+    /// needed.
+    ///
+    /// The constructed resource is cached until `T` is inserted or removed. The loader remains
+    /// registered, and will construct `T` again on the next access after it has been removed.
+    /// Methods such as `get_or_insert` return the loaded resource rather than inserting a value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_systems::prelude::*;
+    /// struct Atlas(Vec<u8>);
+    ///
+    /// let mut resources = Resources::default();
+    /// resources.register_loader(|| Atlas(vec![0; 1024]));
+    /// assert!(!resources.contains::<Atlas>());
+    ///
+    /// assert_eq!(1024, resources.get::<Atlas>().unwrap().0.len());
+    /// assert!(resources.contains::<Atlas>());
+    /// ```
+    pub fn register_loader<T: Resource, F: Fn() -> T + Send + Sync + 'static>(&mut self, f: F) {
+        self.loaders.insert(
+            ResourceTypeId::of::<T>(),
+            Loader {
+                load: Box::new(move || Box::new(f())),
+                value: OnceLock::new(),
+            },
+        );
+    }
+
+    /// Finds the cell containing a resource, constructing it with its loader if required.
+    fn cell(&self, type_id: &ResourceTypeId) -> Option<&AtomicRefCell<Box<dyn Resource>>> {
+        self.storage.get(type_id).or_else(|| {
+            let loader = self.loaders.get(type_id)?;
+            Some(
+                loader
+                    .value
+                    .get_or_init(|| AtomicRefCell::new((loader.load)())),
+            )
+        })
+    }
+
+    /// Finds the cell containing a resource as with `cell`, first inserting the value returned by
+    /// `f` if the resource neither exists nor has a loader.
+    fn cell_or_insert_with<T: Resource, F: FnOnce() -> T>(
+        &mut self,
+        f: F,
+    ) -> &AtomicRefCell<Box<dyn Resource>> {
+        let type_id = ResourceTypeId::of::<T>();
+        if !self.storage.contains_key(&type_id) && !self.loaders.contains_key(&type_id) {
+            self.storage
+                .insert(type_id, AtomicRefCell::new(Box::new(f())));
+        }
+        self.cell(&type_id).unwrap()
+    }
+
+    /// Takes a resource which was constructed by its loader.
+    fn take_loaded<T: Resource>(&mut self) -> Option<T> {
+        let cell = self
+            .loaders
+            .get_mut(&ResourceTypeId::of::<T>())?
+            .value
+            .take()?;
+        Some(*cell.into_inner().downcast::<T>().ok()?)
+    }
+
+    /// Drops any resource constructed by a loader, so that it can not be returned in place of a
+    /// resource inserted or removed since.
+    fn unload(&mut self, type_id: &ResourceTypeId) {
+        if let Some(loader) = self.loaders.get_mut(type_id) {
+            loader.value.take();
+        }
     }
 
     /// Inserts the instance of `T` into the store. If the type already exists, it will be silently
     /// overwritten. If you would like to retain the instance of the resource that already exists,
-    /// call `remove` first to retrieve it.
+    /// call `replace` instead.
     pub fn insert<T: Resource>(&mut self, value: T) {
+        self.unload(&ResourceTypeId::of::<T>());
         self.storage.insert(
             ResourceTypeId::of::<T>(),
             AtomicRefCell::new(Box::new(value)),
         );
     }
 
+    /// Inserts the instance of `T` into the store, returning the instance it replaced.
+    ///
+    /// # Returns
+    /// If the type `T` was already stored, the previous instance of `T` is returned. Otherwise, `None`
+    pub fn replace<T: Resource>(&mut self, value: T) -> Option<T> {
+        let previous = self.storage.insert(
+            ResourceTypeId::of::<T>(),
+            AtomicRefCell::new(Box::new(value)),
+        );
+        match previous {
+            Some(previous) => Some(*previous.into_inner().downcast::<T>().ok()?),
+            None => self.take_loaded::<T>(),
+        }
+    }
+
     /// Removes the type `T` from this store if it exists.
     ///
     /// # Returns
     /// If the type `T` was stored, the inner instance of `T is returned. Otherwise, `None`
     pub fn remove<T: Resource>(&mut self) -> Option<T> {
-        Some(
-            *self
-                .storage
-                .remove(&ResourceTypeId::of::<T>())?
-                .into_inner()
-                .downcast::<T>()
-                .ok()?,
-        )
+        match self.storage.remove(&ResourceTypeId::of::<T>()) {
+            Some(resource) => {
+                self.unload(&ResourceTypeId::of::<T>());
+                Some(*resource.into_inner().downcast::<T>().ok()?)
+            }
+            None => self.take_loaded::<T>(),
+        }
     }
 
     /// Retrieve an immutable reference to  `T` from the store if it exists. Otherwise, return `None`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is already mutably borrowed. Runtime borrow checking is only conducted in
+    /// builds with `debug_assertions` enabled.
     pub fn get<T: Resource>(&self) -> Option<Fetch<'_, T>> {
+        let inner = self
+            .cell(&ResourceTypeId::of::<T>())?
+            .try_get()
+            .unwrap_or_else(|_| {
+                panic!(
+                    "resource already borrowed as mutable: {}",
+                    std::any::type_name::<T>()
+                )
+            });
         Some(Fetch {
-            inner: self.storage.get(&ResourceTypeId::of::<T>())?.get(),
+            inner,
             _marker: Default::default(),
         })
     }
 
     /// Retrieve a mutable reference to  `T` from the store if it exists. Otherwise, return `None`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is already borrowed. Runtime borrow checking is only conducted in builds
+    /// with `debug_assertions` enabled.
     pub fn get_mut<T: Resource>(&self) -> Option<FetchMut<'_, T>> {
+        let inner = self
+            .cell(&ResourceTypeId::of::<T>())?
+            .try_get_mut()
+            .unwrap_or_else(|_| {
+                panic!("resource already borrowed: {}", std::any::type_name::<T>())
+            });
         Some(FetchMut {
-            inner: self.storage.get(&ResourceTypeId::of::<T>())?.get_mut(),
+            inner,
             _marker: Default::default(),
         })
     }
 
+    /// Inserts a default `DoubleBuffered<T>` into the store, and registers it to be swapped by
+    /// `swap_double_buffers`. If the type already exists, it will be silently overwritten.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_systems::prelude::*;
+    /// # use legion_systems::resource::DoubleBuffered;
+    /// let mut resources = Resources::default();
+    /// resources.insert_double_buffered::<Vec<u32>>();
+    ///
+    /// resources
+    ///     .get_mut::<DoubleBuffered<Vec<u32>>>()
+    ///     .unwrap()
+    ///     .next()
+    ///     .push(5);
+    /// resources.swap_double_buffers();
+    ///
+    /// let events = resources.get::<DoubleBuffered<Vec<u32>>>().unwrap();
+    /// assert_eq!(events.current(), &vec![5]);
+    /// ```
+    pub fn insert_double_buffered<T: Resource + Default>(&mut self) {
+        self.insert(DoubleBuffered::<T>::default());
+        self.double_buffers
+            .insert(ResourceTypeId::of::<DoubleBuffered<T>>(), |resource| {
+                resource
+                    .downcast_mut::<DoubleBuffered<T>>()
+                    .unwrap_or_else(|| {
+                        panic!(
+                            "Unable to downcast the resource!: {}",
+                            std::any::type_name::<DoubleBuffered<T>>()
+                        )
+                    })
+                    .swap()
+            });
+    }
+
+    /// Inserts a default `Events<T>` into the store, and registers it to be updated by
+    /// `swap_double_buffers`. If the type already exists, it will be silently overwritten.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_systems::prelude::*;
+    /// # use legion_systems::resource::Events;
+    /// let mut resources = Resources::default();
+    /// resources.insert_events::<u32>();
+    ///
+    /// let mut reader = resources.get::<Events<u32>>().unwrap().reader();
+    /// resources.get_mut::<Events<u32>>().unwrap().send(5);
+    /// resources.swap_double_buffers();
+    ///
+    /// let events = resources.get::<Events<u32>>().unwrap();
+    /// assert_eq!(reader.read(&events).collect::<Vec<_>>(), vec![&5]);
+    /// ```
+    pub fn insert_events<T: Resource>(&mut self) {
+        self.insert(Events::<T>::default());
+        self.double_buffers
+            .insert(ResourceTypeId::of::<Events<T>>(), |resource| {
+                resource
+                    .downcast_mut::<Events<T>>()
+                    .unwrap_or_else(|| {
+                        panic!(
+                            "Unable to downcast the resource!: {}",
+                            std::any::type_name::<Events<T>>()
+                        )
+                    })
+                    .update()
+            });
+    }
+
+    /// Swaps all double buffered resources, making the values written during this frame current.
+    pub fn swap_double_buffers(&mut self) {
+        for (type_id, swap) in &self.double_buffers {
+            if let Some(resource) = self.storage.get_mut(type_id) {
+                swap(&mut **resource.get_mut());
+            }
+        }
+    }
+
+    /// Inserts the instance of `T` into the store, and registers it as the resource to be returned
+    /// when fetching the trait object `D` with `get_dyn`. Any resource previously registered for `D`
+    /// is replaced as the `D` implementation, but is not removed from the store.
+    ///
+    /// `as_dyn` converts a reference to `T` into a reference to `D`. This is usually just an
+    /// unsizing coercion, such as `|r| r`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use legion_systems::prelude::*;
+    /// trait Renderer: Send + Sync {
+    ///     fn name(&self) -> &str;
+    /// }
+    ///
+    /// struct GlRenderer;
+    ///
+    /// impl Renderer for GlRenderer {
+    ///     fn name(&self) -> &str { "gl" }
+    /// }
+    ///
+    /// let mut resources = Resources::default();
+    /// resources.insert_as_dyn::<_, dyn Renderer>(GlRenderer, |r| r);
+    ///
+    /// assert_eq!(resources.get_dyn::<dyn Renderer>().unwrap().name(), "gl");
+    /// ```
+    pub fn insert_as_dyn<T: Resource, D: ?Sized + 'static>(
+        &mut self,
+        value: T,
+        as_dyn: fn(&T) -> &D,
+    ) {
+        self.insert(value);
+
+        let cast = DynCast::<D> {
+            resource: ResourceTypeId::of::<T>(),
+            cast: Box::new(move |resource| {
+                as_dyn(resource.downcast_ref::<T>().unwrap_or_else(|| {
+                    panic!(
+                        "Unable to downcast the resource!: {}",
+                        std::any::type_name::<T>()
+                    )
+                }))
+            }),
+        };
+        self.dyn_casts.insert(TypeId::of::<D>(), Box::new(cast));
+    }
+
+    /// Retrieve an immutable reference to the resource registered for the trait object `T` with
+    /// `insert_as_dyn`, if it exists. Otherwise, return `None`
+    pub fn get_dyn<T: ?Sized + 'static>(&self) -> Option<FetchDyn<'_, T>> {
+        let cast = self
+            .dyn_casts
+            .get(&TypeId::of::<T>())?
+            .downcast_ref::<DynCast<T>>()?;
+
+        Some(FetchDyn {
+            inner: self.cell(&cast.resource)?.get(),
+            cast,
+        })
+    }
+
     /// Attempts to retrieve an immutable reference to `T` from the store. If it does not exist,
     /// the closure `f` is called to construct the object and it is then inserted into the store.
     pub fn get_or_insert_with<T: Resource, F: FnOnce() -> T>(
@@ -269,11 +677,7 @@ impl Resources {
     /// the provided value is inserted and then a reference to it is returned.
     pub fn get_or_insert<T: Resource>(&mut self, value: T) -> Option<Fetch<'_, T>> {
         Some(Fetch {
-            inner: self
-                .storage
-                .entry(ResourceTypeId::of::<T>())
-                .or_insert_with(|| AtomicRefCell::new(Box::new(value)))
-                .get(),
+            inner: self.cell_or_insert_with(|| value).get(),
             _marker: Default::default(),
         })
     }
@@ -282,11 +686,7 @@ impl Resources {
     /// the provided value is inserted and then a reference to it is returned.
     pub fn get_mut_or_insert<T: Resource>(&mut self, value: T) -> Option<FetchMut<'_, T>> {
         Some(FetchMut {
-            inner: self
-                .storage
-                .entry(ResourceTypeId::of::<T>())
-                .or_insert_with(|| AtomicRefCell::new(Box::new(value)))
-                .get_mut(),
+            inner: self.cell_or_insert_with(|| value).get_mut(),
             _marker: Default::default(),
         })
     }
@@ -297,11 +697,7 @@ impl Resources {
     /// `T` must implement `Default` for this method.
     pub fn get_or_default<T: Resource + Default>(&mut self) -> Option<Fetch<'_, T>> {
         Some(Fetch {
-            inner: self
-                .storage
-                .entry(ResourceTypeId::of::<T>())
-                .or_insert_with(|| AtomicRefCell::new(Box::new(T::default())))
-                .get(),
+            inner: self.cell_or_insert_with(T::default).get(),
             _marker: Default::default(),
         })
     }
@@ -312,11 +708,7 @@ impl Resources {
     /// `T` must implement `Default` for this method.
     pub fn get_mut_or_default<T: Resource + Default>(&mut self) -> Option<FetchMut<'_, T>> {
         Some(FetchMut {
-            inner: self
-                .storage
-                .entry(ResourceTypeId::of::<T>())
-                .or_insert_with(|| AtomicRefCell::new(Box::new(T::default())))
-                .get_mut(),
+            inner: self.cell_or_insert_with(T::default).get_mut(),
             _marker: Default::default(),
         })
     }
@@ -329,6 +721,18 @@ impl Resources {
         for resource in other.storage.drain() {
             self.storage.entry(resource.0).or_insert(resource.1);
         }
+
+        for cast in other.dyn_casts.drain() {
+            self.dyn_casts.entry(cast.0).or_insert(cast.1);
+        }
+
+        for swap in other.double_buffers.drain() {
+            self.double_buffers.entry(swap.0).or_insert(swap.1);
+        }
+
+        for loader in other.loaders.drain() {
+            self.loaders.entry(loader.0).or_insert(loader.1);
+        }
     }
 }
 
@@ -433,4 +837,205 @@ mod tests {
         let owned = resources.remove::<TestTwo>();
         assert_eq!(owned.unwrap().value, "balls")
     }
+
+    #[test]
+    fn replace_returns_previous() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        #[derive(Debug, PartialEq)]
+        struct Config(u32);
+
+        let mut resources = Resources::default();
+        assert_eq!(None, resources.replace(Config(1)));
+        assert_eq!(Some(Config(1)), resources.replace(Config(2)));
+        assert_eq!(Config(2), *resources.get::<Config>().unwrap());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn conflicting_write_fetch_names_type() {
+        struct Counter;
+
+        let mut resources = Resources::default();
+        resources.insert(Counter);
+
+        let _first = resources.get_mut::<Counter>().unwrap();
+        let message = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            resources.get_mut::<Counter>();
+        }))
+        .unwrap_err()
+        .downcast::<String>()
+        .unwrap();
+        assert!(message.ends_with(std::any::type_name::<Counter>()));
+    }
+
+    #[test]
+    fn lazy_loader() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        struct Expensive(u32);
+
+        let loads = Arc::new(AtomicUsize::new(0));
+        let counter = loads.clone();
+
+        let mut resources = Resources::default();
+        resources.register_loader(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Expensive(5)
+        });
+
+        // nothing is constructed until the resource is first fetched
+        assert!(!resources.contains::<Expensive>());
+        assert_eq!(0, loads.load(Ordering::SeqCst));
+
+        assert_eq!(5, resources.get::<Expensive>().unwrap().0);
+        assert_eq!(1, loads.load(Ordering::SeqCst));
+        assert!(resources.contains::<Expensive>());
+
+        // the constructed resource is cached
+        resources.get_mut::<Expensive>().unwrap().0 = 6;
+        assert_eq!(6, resources.get::<Expensive>().unwrap().0);
+        assert_eq!(1, loads.load(Ordering::SeqCst));
+
+        // removing the resource yields the cached instance, and the loader remains registered
+        assert_eq!(6, resources.remove::<Expensive>().unwrap().0);
+        assert!(!resources.contains::<Expensive>());
+        assert_eq!(5, resources.get::<Expensive>().unwrap().0);
+        assert_eq!(2, loads.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn lazy_loader_insert_discards_loaded() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        struct Value(u32);
+
+        let mut resources = Resources::default();
+        resources.register_loader(|| Value(1));
+        assert_eq!(1, resources.get::<Value>().unwrap().0);
+
+        // an inserted resource replaces the loaded one, and removing it does not uncover the
+        // stale loaded value
+        resources.insert(Value(2));
+        assert_eq!(2, resources.get::<Value>().unwrap().0);
+        assert_eq!(2, resources.remove::<Value>().unwrap().0);
+        assert!(!resources.contains::<Value>());
+
+        resources.get_mut::<Value>().unwrap().0 = 3;
+        resources.insert(Value(4));
+        assert_eq!(4, resources.remove::<Value>().unwrap().0);
+        assert_eq!(1, resources.get::<Value>().unwrap().0);
+    }
+
+    #[test]
+    fn lazy_loader_get_or_insert() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        #[derive(Default)]
+        struct Value(u32);
+
+        let mut resources = Resources::default();
+        resources.register_loader(|| Value(1));
+        resources.get_mut::<Value>().unwrap().0 = 2;
+
+        // the loaded resource is found rather than shadowed by a newly inserted value
+        assert_eq!(2, resources.get_or_insert(Value(3)).unwrap().0);
+        assert_eq!(2, resources.get_mut_or_insert(Value(3)).unwrap().0);
+        assert_eq!(2, resources.get_or_default::<Value>().unwrap().0);
+        assert_eq!(2, resources.remove::<Value>().unwrap().0);
+
+        // a loader which has not yet run constructs the resource
+        assert_eq!(1, resources.get_mut_or_default::<Value>().unwrap().0);
+    }
+
+    #[test]
+    fn trait_object_read_test() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        trait Renderer: Send + Sync {
+            fn draw_calls(&self) -> usize;
+        }
+
+        struct GlRenderer {
+            draw_calls: usize,
+        }
+
+        impl Renderer for GlRenderer {
+            fn draw_calls(&self) -> usize { self.draw_calls }
+        }
+
+        struct NullRenderer;
+
+        impl Renderer for NullRenderer {
+            fn draw_calls(&self) -> usize { 0 }
+        }
+
+        let mut resources = Resources::default();
+        assert!(resources.get_dyn::<dyn Renderer>().is_none());
+
+        resources.insert_as_dyn::<_, dyn Renderer>(GlRenderer { draw_calls: 12 }, |r| r);
+        assert_eq!(
+            resources.get_dyn::<dyn Renderer>().unwrap().draw_calls(),
+            12
+        );
+
+        // the concrete resource is still available
+        resources.get_mut::<GlRenderer>().unwrap().draw_calls = 3;
+        assert_eq!(resources.get_dyn::<dyn Renderer>().unwrap().draw_calls(), 3);
+
+        resources.insert_as_dyn::<_, dyn Renderer>(NullRenderer, |r| r);
+        assert_eq!(resources.get_dyn::<dyn Renderer>().unwrap().draw_calls(), 0);
+
+        resources.remove::<NullRenderer>();
+        assert!(resources.get_dyn::<dyn Renderer>().is_none());
+    }
+
+    #[test]
+    fn events_read_once() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let mut resources = Resources::default();
+        resources.insert_events::<u32>();
+
+        let mut fast = resources.get::<Events<u32>>().unwrap().reader();
+        let mut slow = resources.get::<Events<u32>>().unwrap().reader();
+
+        let read = |reader: &mut EventReader<u32>, resources: &Resources| {
+            let events = resources.get::<Events<u32>>().unwrap();
+            reader.read(&events).copied().collect::<Vec<_>>()
+        };
+
+        // frame 1
+        resources.get_mut::<Events<u32>>().unwrap().send(1);
+        resources.get_mut::<Events<u32>>().unwrap().send(2);
+        assert_eq!(vec![1, 2], read(&mut fast, &resources));
+        resources.swap_double_buffers();
+
+        // frame 2: the previous frame's events remain readable
+        resources.get_mut::<Events<u32>>().unwrap().send(3);
+        assert_eq!(vec![3], read(&mut fast, &resources));
+        assert_eq!(vec![1, 2, 3], read(&mut slow, &resources));
+        assert!(read(&mut fast, &resources).is_empty());
+        assert!(read(&mut slow, &resources).is_empty());
+        resources.swap_double_buffers();
+
+        // frame 3
+        resources.get_mut::<Events<u32>>().unwrap().send(4);
+        resources.swap_double_buffers();
+        resources.swap_double_buffers();
+
+        // events older than the previous frame are discarded
+        assert!(read(&mut fast, &resources).is_empty());
+
+        let mut events = resources.get_mut::<Events<u32>>().unwrap();
+        events.send(5);
+        events.send(6);
+        assert_eq!(vec![5, 6], events.drain().collect::<Vec<_>>());
+        assert!(events.is_empty());
+        drop(events);
+        assert!(read(&mut slow, &resources).is_empty());
+    }
 }
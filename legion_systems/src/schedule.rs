@@ -1,22 +1,24 @@
 use crate::{
     resource::{ResourceTypeId, Resources},
-    system::SystemId,
+    system::{QueryAccess, SystemId},
 };
+use bit_set::BitSet;
 use legion_core::{
     borrow::RefMut,
     command::CommandBuffer,
+    permission::Permissions,
     storage::ComponentTypeId,
     subworld::ArchetypeAccess,
     world::{World, WorldId},
 };
 use std::cell::UnsafeCell;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 #[cfg(feature = "par-schedule")]
 use tracing::{span, trace, Level};
 
-#[cfg(feature = "par-schedule")]
-use std::sync::atomic::{AtomicUsize, Ordering};
-
 #[cfg(feature = "par-schedule")]
 use fxhash::{FxHashMap, FxHashSet};
 
@@ -26,6 +28,9 @@ use rayon::prelude::*;
 #[cfg(feature = "par-schedule")]
 use itertools::izip;
 
+#[cfg(feature = "par-schedule")]
+use crossbeam_channel::Sender;
+
 #[cfg(feature = "par-schedule")]
 use std::iter::repeat;
 
@@ -54,6 +59,37 @@ pub trait Runnable {
     /// as determined when the system was last prepared.
     fn accesses_archetypes(&self) -> &ArchetypeAccess;
 
+    /// Determines if the system must be run on the thread which executes the schedule.
+    fn on_main_thread(&self) -> bool { false }
+
+    /// Gets the component access of each of the system's queries.
+    fn queries(&self) -> &[QueryAccess] { &[] }
+
+    /// Describes the system's component access, and that of each of its queries, for debugging.
+    fn describe(&self) -> String {
+        fn list(items: &[ComponentTypeId]) -> String {
+            items
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+
+        let mut description = format!(
+            "{}: reads: [{}], writes: [{}]",
+            self.name(),
+            list(self.reads().1),
+            list(self.writes().1)
+        );
+        for (i, query) in self.queries().iter().enumerate() {
+            description += &match &query.name {
+                Some(name) => format!("\n  query \"{}\": {}", name, query.components),
+                None => format!("\n  query {}: {}", i, query.components),
+            };
+        }
+        description
+    }
+
     /// Runs the system.
     ///
     /// # Safety
@@ -66,6 +102,11 @@ pub trait Runnable {
     /// Gets the system's command buffer.
     fn command_buffer_mut(&self, world: WorldId) -> Option<RefMut<CommandBuffer>>;
 
+    /// Gets the command buffers of the system and of any systems nested within it.
+    fn command_buffers_mut(&self, world: WorldId) -> Vec<RefMut<CommandBuffer>> {
+        self.command_buffer_mut(world).into_iter().collect()
+    }
+
     /// Runs the system.
     fn run(&mut self, world: &mut World, resources: &mut Resources) {
         unsafe { self.run_unsafe(world, resources) };
@@ -87,6 +128,108 @@ pub struct Executor {
     static_dependency_counts: Vec<AtomicUsize>,
     #[cfg(feature = "par-schedule")]
     awaiting: Vec<AtomicUsize>,
+    trace: Option<Mutex<Vec<TraceEvent>>>,
+    stats: Option<StatsRecorder>,
+    budgeted_progress: usize,
+}
+
+/// An event recorded in an `ExecutionTrace`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceEvent {
+    /// A system started running.
+    Started {
+        /// The index of the system within the executor.
+        system: usize,
+        /// The index of the dependency whose completion allowed the system to start, or `None`
+        /// if the system had no outstanding dependencies when execution began.
+        gated_by: Option<usize>,
+    },
+    /// A system finished running.
+    Finished {
+        /// The index of the system within the executor.
+        system: usize,
+    },
+}
+
+/// A record of the order in which an `Executor` started and finished its systems, produced by
+/// `Executor::execute_traced`.
+///
+/// Comparing the traces of two executions can pinpoint where their ordering diverges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionTrace {
+    systems: Vec<SystemId>,
+    dependencies: Vec<Vec<usize>>,
+    events: Vec<TraceEvent>,
+}
+
+impl ExecutionTrace {
+    /// Gets the names of the executor's systems, by index.
+    pub fn systems(&self) -> &[SystemId] { &self.systems }
+
+    /// Gets the indices of the systems which had to finish before the given system could start.
+    pub fn dependencies(&self, system: usize) -> &[usize] { &self.dependencies[system] }
+
+    /// Gets the recorded events, in the order in which they occurred.
+    pub fn events(&self) -> &[TraceEvent] { &self.events }
+
+    /// Gets the indices of the systems in the order in which they were started.
+    pub fn start_order(&self) -> Vec<usize> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                TraceEvent::Started { system, .. } => Some(*system),
+                TraceEvent::Finished { .. } => None,
+            })
+            .collect()
+    }
+}
+
+/// Measurements of the parallelism achieved by an `Executor`, produced by
+/// `Executor::execute_with_stats`.
+///
+/// A `total_system_time` close to `wall_time`, or a `max_concurrency` of 1, indicates that the
+/// systems' dependencies serialized the stage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExecutionStats {
+    /// The time taken to run all systems, not including flushing their command buffers.
+    pub wall_time: Duration,
+    /// The sum of the time taken by each system.
+    pub total_system_time: Duration,
+    /// The greatest number of systems which were running at the same time.
+    pub max_concurrency: usize,
+}
+
+impl ExecutionStats {
+    /// Gets the average number of systems which were running at once; the ratio of
+    /// `total_system_time` to `wall_time`.
+    pub fn parallelism(&self) -> f64 {
+        if self.wall_time == Duration::default() {
+            return 0.0;
+        }
+        self.total_system_time.as_secs_f64() / self.wall_time.as_secs_f64()
+    }
+}
+
+/// Accumulates `ExecutionStats` while systems are running.
+#[derive(Default)]
+struct StatsRecorder {
+    running: AtomicUsize,
+    max_concurrency: AtomicUsize,
+    system_nanos: AtomicU64,
+}
+
+impl StatsRecorder {
+    fn start(&self) -> Instant {
+        let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_concurrency.fetch_max(running, Ordering::SeqCst);
+        Instant::now()
+    }
+
+    fn finish(&self, start: Instant) {
+        let elapsed = start.elapsed().as_nanos() as u64;
+        self.system_nanos.fetch_add(elapsed, Ordering::SeqCst);
+        self.running.fetch_sub(1, Ordering::SeqCst);
+    }
 }
 
 struct SystemBox(UnsafeCell<Box<dyn Schedulable>>);
@@ -119,6 +262,9 @@ impl Executor {
                 .into_iter()
                 .map(|s| SystemBox(UnsafeCell::new(s)))
                 .collect(),
+            trace: None,
+            stats: None,
+            budgeted_progress: 0,
         }
     }
 
@@ -274,6 +420,9 @@ impl Executor {
                     .into_iter()
                     .map(|s| SystemBox(UnsafeCell::new(s)))
                     .collect(),
+                trace: None,
+                stats: None,
+                budgeted_progress: 0,
             }
         } else {
             Executor {
@@ -285,6 +434,9 @@ impl Executor {
                     .into_iter()
                     .map(|s| SystemBox(UnsafeCell::new(s)))
                     .collect(),
+                trace: None,
+                stats: None,
+                budgeted_progress: 0,
             }
         }
     }
@@ -300,26 +452,238 @@ impl Executor {
         self.flush_command_buffers(world);
     }
 
+    /// Executes waves of systems until `budget` has elapsed, resuming from where the previous
+    /// call stopped, and then flushes all command buffers once every system has run.
+    ///
+    /// A wave contains every system whose dependencies have all completed, and its systems may be
+    /// run in parallel. This allows a heavy stage to be spread across several frames. At least one
+    /// wave is run by each call, and no system is run again until all systems have run.
+    ///
+    /// Returns `true` once all systems have run and their command buffers have been flushed.
+    pub fn execute_budgeted(
+        &mut self,
+        world: &mut World,
+        resources: &mut Resources,
+        budget: Duration,
+    ) -> bool {
+        let start = Instant::now();
+
+        // archetypes may have been created since the previous call
+        #[cfg(feature = "par-schedule")]
+        if self.budgeted_progress > 0 {
+            self.prepare_pending(world);
+        }
+
+        while self.budgeted_progress < self.systems.len() {
+            self.budgeted_progress += self.run_wave(world, resources);
+
+            if start.elapsed() >= budget {
+                break;
+            }
+        }
+
+        if self.budgeted_progress < self.systems.len() {
+            return false;
+        }
+
+        self.budgeted_progress = 0;
+        self.flush_command_buffers(world);
+        true
+    }
+
+    /// Executes all systems and then flushes their command buffers, as with `execute`, while
+    /// recording the order in which systems were started and finished.
+    pub fn execute_traced(
+        &mut self,
+        world: &mut World,
+        resources: &mut Resources,
+    ) -> ExecutionTrace {
+        self.trace = Some(Mutex::new(Vec::new()));
+        self.execute(world, resources);
+        let events = self.trace.take().unwrap().into_inner().unwrap();
+
+        ExecutionTrace {
+            systems: self
+                .systems
+                .iter()
+                .map(|system| unsafe { system.get_mut() }.name().clone())
+                .collect(),
+            dependencies: self.dependencies(),
+            events,
+        }
+    }
+
+    /// Executes all systems and then flushes their command buffers, as with `execute`, while
+    /// measuring how much parallelism was achieved.
+    pub fn execute_with_stats(
+        &mut self,
+        world: &mut World,
+        resources: &mut Resources,
+    ) -> ExecutionStats {
+        self.stats = Some(StatsRecorder::default());
+        let start = Instant::now();
+        self.run_systems(world, resources);
+        let wall_time = start.elapsed();
+        self.flush_command_buffers(world);
+        let stats = self.stats.take().unwrap();
+
+        ExecutionStats {
+            wall_time,
+            total_system_time: Duration::from_nanos(stats.system_nanos.into_inner()),
+            max_concurrency: stats.max_concurrency.into_inner(),
+        }
+    }
+
+    /// Gets the systems each system waited on during the last execution.
+    #[cfg(not(feature = "par-schedule"))]
+    fn dependencies(&self) -> Vec<Vec<usize>> {
+        (0..self.systems.len()).map(|i| (0..i).collect()).collect()
+    }
+
+    /// Gets the systems each system waited on during the last execution.
+    #[cfg(feature = "par-schedule")]
+    fn dependencies(&self) -> Vec<Vec<usize>> {
+        let mut dependencies = vec![Vec::new(); self.systems.len()];
+        for (i, dependants) in self.static_dependants.iter().enumerate() {
+            for dep in dependants {
+                dependencies[*dep].push(i);
+            }
+        }
+        dependencies
+    }
+
+    /// Runs the system at index `i`, recording it in the execution trace and statistics if they
+    /// are being recorded.
+    ///
+    /// # Safety
+    ///
+    /// Ensure the system indexed by `i` is only accessed once.
+    unsafe fn run_system(
+        &self,
+        i: usize,
+        gated_by: Option<usize>,
+        world: &World,
+        resources: &Resources,
+    ) {
+        self.record(TraceEvent::Started {
+            system: i,
+            gated_by,
+        });
+        let start = self.stats.as_ref().map(StatsRecorder::start);
+        self.systems[i].get_mut().run_unsafe(world, resources);
+        if let (Some(stats), Some(start)) = (&self.stats, start) {
+            stats.finish(start);
+        }
+        self.record(TraceEvent::Finished { system: i });
+    }
+
+    fn record(&self, event: TraceEvent) {
+        if let Some(trace) = &self.trace {
+            trace.lock().unwrap().push(event);
+        }
+    }
+
     /// Executes all systems sequentially.
     ///
     /// Only enabled with par-schedule is disabled
     #[cfg(not(feature = "par-schedule"))]
     pub fn run_systems(&mut self, world: &mut World, resources: &mut Resources) {
-        self.systems.iter_mut().for_each(|system| {
-            let system = unsafe { system.get_mut() };
-            system.prepare(world);
-            system.run(world, resources);
-        });
+        for i in 0..self.systems.len() {
+            // safety: we have exlusive access to all systems, world and resources here
+            unsafe {
+                self.systems[i].get_mut().prepare(world);
+                self.run_system(i, i.checked_sub(1), world, resources);
+            }
+        }
+    }
+
+    /// Executes the next system of a budgeted execution, returning the number of systems run.
+    ///
+    /// Only enabled with par-schedule is disabled
+    #[cfg(not(feature = "par-schedule"))]
+    fn run_wave(&mut self, world: &mut World, resources: &mut Resources) -> usize {
+        let i = self.budgeted_progress;
+        // safety: we have exlusive access to all systems, world and resources here
+        unsafe {
+            self.systems[i].get_mut().prepare(world);
+            self.run_system(i, i.checked_sub(1), world, resources);
+        }
+        1
+    }
+
+    /// Executes the next wave of a budgeted execution in parallel, returning the number of
+    /// systems run.
+    ///
+    /// The first wave prepares the systems and contains those with no dependencies. Each later
+    /// wave contains the systems whose last outstanding dependency completed in an earlier wave.
+    /// Systems marked with `SystemBuilder::on_main_thread` are run on the calling thread.
+    #[cfg(feature = "par-schedule")]
+    fn run_wave(&mut self, world: &mut World, resources: &mut Resources) -> usize {
+        if self.systems.len() == 1 {
+            // safety: we have exlusive access to all systems, world and resources here
+            unsafe {
+                self.systems[0].get_mut().prepare(world);
+                self.run_system(0, None, world, resources);
+            };
+            return 1;
+        }
+
+        if self.budgeted_progress == 0 {
+            self.prepare_dependencies(world);
+        }
+
+        let this = &*self;
+        let world = &*world;
+        let resources = &*resources;
+        let wave = (0..this.systems.len())
+            .filter(|i| this.awaiting[*i].load(Ordering::SeqCst) == 0)
+            .collect::<Vec<_>>();
+        let (main_thread, pooled): (Vec<usize>, Vec<usize>) = wave
+            .iter()
+            .partition(|i| unsafe { this.systems[**i].get() }.on_main_thread());
+
+        // safety: every dependency of a system in the wave has already completed, and each index
+        // appears in the wave once
+        pooled
+            .par_iter()
+            .for_each(|i| unsafe { this.run_system(*i, None, world, resources) });
+        for i in main_thread {
+            unsafe { this.run_system(i, None, world, resources) };
+        }
+
+        for i in &wave {
+            for dep in &this.static_dependants[*i] {
+                this.awaiting[*dep].fetch_sub(1, Ordering::Relaxed);
+            }
+        }
+
+        // mark the wave as completed so that it is not run again, until the counts are reset by
+        // the next call to `prepare_dependencies`
+        for i in &wave {
+            this.awaiting[*i].store(usize::MAX, Ordering::Relaxed);
+        }
+
+        wave.len()
     }
 
     /// Executes all systems, potentially in parallel.
     ///
     /// Ordering is retained in so far as the order of observed resource and component
-    /// accesses is maintained.
+    /// accesses is maintained. Systems marked with `SystemBuilder::on_main_thread` are run
+    /// on the calling thread.
     ///
     /// Call from within `rayon::ThreadPool::install()` to execute within a specific thread pool.
     #[cfg(feature = "par-schedule")]
     pub fn run_systems(&mut self, world: &mut World, resources: &mut Resources) {
+        if self
+            .systems
+            .iter()
+            .any(|system| unsafe { system.get() }.on_main_thread())
+        {
+            self.run_systems_with_main_thread(world, resources);
+            return;
+        }
+
         rayon::join(
             || {},
             || {
@@ -327,54 +691,17 @@ impl Executor {
                     1 => {
                         // safety: we have exlusive access to all systems, world and resources here
                         unsafe {
-                            let system = self.systems[0].get_mut();
-                            system.prepare(world);
-                            system.run(world, resources);
+                            self.systems[0].get_mut().prepare(world);
+                            self.run_system(0, None, world, resources);
                         };
                     }
                     _ => {
-                        let systems = &mut self.systems;
-                        let static_dependency_counts = &self.static_dependency_counts;
-                        let awaiting = &mut self.awaiting;
-
-                        // prepare all systems - archetype filters are pre-executed here
-                        systems
-                            .par_iter_mut()
-                            .for_each(|sys| unsafe { sys.get_mut() }.prepare(world));
-
-                        // determine dynamic dependencies
-                        izip!(
-                            systems.iter(),
-                            self.static_dependants.iter_mut(),
-                            self.dynamic_dependants.iter_mut()
-                        )
-                        .par_bridge()
-                        .for_each(|(sys, static_dep, dyn_dep)| {
-                            // safety: systems is held exclusively, and we are only reading each system
-                            let archetypes = unsafe { sys.get() }.accesses_archetypes();
-                            for i in (0..dyn_dep.len()).rev() {
-                                let dep = dyn_dep[i];
-                                let other = unsafe { systems[dep].get() };
-
-                                // if the archetype sets intersect,
-                                // then we can move the dynamic dependant into the static dependants set
-                                if !other.accesses_archetypes().is_disjoint(archetypes) {
-                                    static_dep.push(dep);
-                                    dyn_dep.swap_remove(i);
-                                    static_dependency_counts[dep].fetch_add(1, Ordering::Relaxed);
-                                }
-                            }
-                        });
-
-                        // initialize dependency tracking
-                        for (i, count) in static_dependency_counts.iter().enumerate() {
-                            awaiting[i].store(count.load(Ordering::Relaxed), Ordering::Relaxed);
-                        }
+                        self.prepare_dependencies(world);
 
+                        let systems = &self.systems;
+                        let static_dependency_counts = &self.static_dependency_counts;
                         let awaiting = &self.awaiting;
 
-                        trace!(?awaiting, "Initialized await counts");
-
                         // execute all systems with no outstanding dependencies
                         (0..systems.len())
                             .into_par_iter()
@@ -382,7 +709,7 @@ impl Executor {
                             .for_each(|i| {
                                 // safety: we are at the root of the execution tree, so we know each
                                 // index is exclusive here
-                                unsafe { self.run_recursive(i, world, resources) };
+                                unsafe { self.run_recursive(i, None, world, resources, None) };
                             });
 
                         debug_assert!(
@@ -396,12 +723,168 @@ impl Executor {
         );
     }
 
+    /// Executes all systems, running those which are marked as needing the main thread on the
+    /// calling thread while the rest of the dependency cascade runs on the rayon thread pool.
+    #[cfg(feature = "par-schedule")]
+    fn run_systems_with_main_thread(&mut self, world: &mut World, resources: &mut Resources) {
+        if self.systems.len() == 1 {
+            // safety: we have exlusive access to all systems, world and resources here
+            unsafe {
+                self.systems[0].get_mut().prepare(world);
+                self.run_system(0, None, world, resources);
+            };
+            return;
+        }
+
+        self.prepare_dependencies(world);
+
+        let this = &*self;
+        let world = &*world;
+        let resources = &*resources;
+        let main_thread_count = this
+            .systems
+            .iter()
+            .filter(|system| unsafe { system.get() }.on_main_thread())
+            .count();
+        let (sender, receiver) = crossbeam_channel::unbounded();
+
+        rayon::in_place_scope(|scope| {
+            let sender = &sender;
+            let start = |i: usize, gated_by: Option<usize>| {
+                if unsafe { this.systems[i].get() }.on_main_thread() {
+                    sender.send((i, gated_by)).unwrap();
+                } else {
+                    // safety: each system is started exactly once, when its
+                    // last dependency completes
+                    scope.spawn(move |_| unsafe {
+                        this.run_recursive(i, gated_by, world, resources, Some(sender))
+                    });
+                }
+            };
+
+            // start all systems with no outstanding dependencies
+            for i in 0..this.systems.len() {
+                if this.static_dependency_counts[i].load(Ordering::SeqCst) == 0 {
+                    start(i, None);
+                }
+            }
+
+            // run main thread systems here as they become ready
+            for _ in 0..main_thread_count {
+                let (i, gated_by) = loop {
+                    match receiver.try_recv() {
+                        Ok(message) => break message,
+                        // on a rayon worker thread, the systems being waited on may be queued on
+                        // this thread, so run pending work rather than block
+                        Err(_) => match rayon::yield_now() {
+                            Some(rayon::Yield::Executed) => {}
+                            Some(rayon::Yield::Idle) => std::thread::yield_now(),
+                            None => break receiver.recv().unwrap(),
+                        },
+                    }
+                };
+                trace!(system = %unsafe { this.systems[i].get() }.name(), "Running on main thread");
+
+                // safety: each system index is only sent once
+                unsafe { this.run_system(i, gated_by, world, resources) };
+
+                for dep in &this.static_dependants[i] {
+                    if this.awaiting[*dep].fetch_sub(1, Ordering::Relaxed) == 1 {
+                        start(*dep, Some(i));
+                    }
+                }
+            }
+        });
+
+        debug_assert!(
+            this.awaiting.iter().all(|x| x.load(Ordering::SeqCst) == 0),
+            "not all systems run: {:?}",
+            this.awaiting
+        );
+    }
+
+    /// Prepares all systems and resolves which dynamic dependencies apply to this execution.
+    #[cfg(feature = "par-schedule")]
+    fn prepare_dependencies(&mut self, world: &World) {
+        let systems = &mut self.systems;
+        let static_dependency_counts = &self.static_dependency_counts;
+        let awaiting = &mut self.awaiting;
+
+        // prepare all systems - archetype filters are pre-executed here
+        systems
+            .par_iter_mut()
+            .for_each(|sys| unsafe { sys.get_mut() }.prepare(world));
+
+        // determine dynamic dependencies
+        izip!(
+            systems.iter(),
+            self.static_dependants.iter_mut(),
+            self.dynamic_dependants.iter_mut()
+        )
+        .par_bridge()
+        .for_each(|(sys, static_dep, dyn_dep)| {
+            // safety: systems is held exclusively, and we are only reading each system
+            let archetypes = unsafe { sys.get() }.accesses_archetypes();
+            for i in (0..dyn_dep.len()).rev() {
+                let dep = dyn_dep[i];
+                let other = unsafe { systems[dep].get() };
+
+                // if the archetype sets intersect,
+                // then we can move the dynamic dependant into the static dependants set
+                if !other.accesses_archetypes().is_disjoint(archetypes) {
+                    static_dep.push(dep);
+                    dyn_dep.swap_remove(i);
+                    static_dependency_counts[dep].fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        });
+
+        // initialize dependency tracking
+        for (i, count) in static_dependency_counts.iter().enumerate() {
+            awaiting[i].store(count.load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+
+        trace!(?awaiting, "Initialized await counts");
+    }
+
+    /// Prepares the systems of a budgeted execution which have not yet run, and promotes any
+    /// dynamic dependencies between them whose archetype accesses now intersect.
+    #[cfg(feature = "par-schedule")]
+    fn prepare_pending(&mut self, world: &World) {
+        let systems = &self.systems;
+        let awaiting = &self.awaiting;
+        let pending = |i: usize| awaiting[i].load(Ordering::Relaxed) != usize::MAX;
+
+        for (i, sys) in systems.iter().enumerate() {
+            if pending(i) {
+                // safety: systems is held exclusively
+                unsafe { sys.get_mut() }.prepare(world);
+            }
+        }
+
+        for i in (0..systems.len()).filter(|i| pending(*i)) {
+            // safety: systems is held exclusively, and we are only reading each system
+            let archetypes = unsafe { systems[i].get() }.accesses_archetypes();
+            let dyn_dep = &mut self.dynamic_dependants[i];
+            for j in (0..dyn_dep.len()).rev() {
+                let dep = dyn_dep[j];
+                let other = unsafe { systems[dep].get() };
+                if pending(dep) && !other.accesses_archetypes().is_disjoint(archetypes) {
+                    self.static_dependants[i].push(dep);
+                    dyn_dep.swap_remove(j);
+                    self.static_dependency_counts[dep].fetch_add(1, Ordering::Relaxed);
+                    awaiting[dep].fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+
     /// Flushes the recorded command buffers for all systems.
     pub fn flush_command_buffers(&mut self, world: &mut World) {
         self.systems.iter().for_each(|system| {
             // safety: systems are exlcusive due to &mut self
             let system = unsafe { system.get_mut() };
-            if let Some(mut cmd) = system.command_buffer_mut(world.id()) {
+            for mut cmd in system.command_buffers_mut(world.id()) {
                 cmd.write(world);
             }
         });
@@ -409,23 +892,234 @@ impl Executor {
 
     /// Recursively execute through the generated depedency cascade and exhaust it.
     ///
+    /// Dependants which must run on the main thread are sent to `main_thread` instead.
+    ///
     /// # Safety
     ///
     /// Ensure the system indexed by `i` is only accessed once.
     #[cfg(feature = "par-schedule")]
-    unsafe fn run_recursive(&self, i: usize, world: &World, resources: &Resources) {
+    unsafe fn run_recursive(
+        &self,
+        i: usize,
+        gated_by: Option<usize>,
+        world: &World,
+        resources: &Resources,
+        main_thread: Option<&Sender<(usize, Option<usize>)>>,
+    ) {
         // safety: the caller ensures nothing else is accessing systems[i]
-        self.systems[i].get_mut().run_unsafe(world, resources);
+        self.run_system(i, gated_by, world, resources);
 
         self.static_dependants[i].par_iter().for_each(|dep| {
             if self.awaiting[*dep].fetch_sub(1, Ordering::Relaxed) == 1 {
-                // safety: each dependency is unique, so run_recursive is safe to call
-                self.run_recursive(*dep, world, resources);
+                match main_thread {
+                    Some(sender) if self.systems[*dep].get().on_main_thread() => {
+                        sender.send((*dep, Some(i))).unwrap()
+                    }
+                    // safety: each dependency is unique, so run_recursive is safe to call
+                    _ => self.run_recursive(*dep, Some(i), world, resources, main_thread),
+                }
             }
         });
     }
 }
 
+/// An entry in the list of systems provided to a `PhasedExecutor`.
+pub enum StageEntry {
+    /// A system to be executed.
+    System(Box<dyn Schedulable>),
+    /// A barrier, which waits for all preceding systems to complete and flushes their
+    /// command buffers before any following system is started.
+    Barrier,
+}
+
+impl From<Box<dyn Schedulable>> for StageEntry {
+    fn from(system: Box<dyn Schedulable>) -> Self { StageEntry::System(system) }
+}
+
+/// Executes the systems of a single stage in phases separated by barriers.
+///
+/// The systems within each phase are executed as by an `Executor`. Between phases, all
+/// outstanding work is completed and command buffers are flushed, such that systems after a barrier
+/// observe all effects of the systems before it. This is lighter-weight than splitting the stage
+/// into multiple schedule steps.
+///
+/// # Examples
+///
+/// ```rust
+/// # use legion_core::prelude::*;
+/// # use legion_systems::prelude::*;
+/// # use legion_systems::schedule::StageEntry;
+/// # let spawn = SystemBuilder::new("spawn").build(|_,_,_,_| {});
+/// # let update = SystemBuilder::new("update").build(|_,_,_,_| {});
+/// let mut world = World::new();
+/// let mut resources = Resources::default();
+/// let mut executor = PhasedExecutor::new(vec![
+///     StageEntry::System(spawn),
+///     StageEntry::Barrier,
+///     StageEntry::System(update),
+/// ]);
+///
+/// executor.execute(&mut world, &mut resources);
+/// ```
+pub struct PhasedExecutor {
+    phases: Vec<Executor>,
+}
+
+impl PhasedExecutor {
+    /// Constructs a new executor for the given systems and barriers.
+    ///
+    /// Systems are provided in the order in which side-effects (e.g. writes to resources or entities)
+    /// are to be observed.
+    pub fn new(entries: Vec<StageEntry>) -> Self {
+        let mut phases = Vec::new();
+        let mut systems = Vec::new();
+        for entry in entries {
+            match entry {
+                StageEntry::System(system) => systems.push(system),
+                StageEntry::Barrier => {
+                    if !systems.is_empty() {
+                        phases.push(Executor::new(std::mem::take(&mut systems)));
+                    }
+                }
+            }
+        }
+
+        if !systems.is_empty() {
+            phases.push(Executor::new(systems));
+        }
+
+        Self { phases }
+    }
+
+    /// Gets the number of barrier-separated phases in the executor.
+    pub fn phases(&self) -> usize { self.phases.len() }
+
+    /// Converts this executor into a vector of its component systems.
+    pub fn into_vec(self) -> Vec<Box<dyn Schedulable>> {
+        self.phases
+            .into_iter()
+            .flat_map(|phase| phase.into_vec())
+            .collect()
+    }
+
+    /// Executes all phases in order, flushing command buffers after each phase.
+    pub fn execute(&mut self, world: &mut World, resources: &mut Resources) {
+        for phase in &mut self.phases {
+            phase.execute(world, resources);
+        }
+    }
+}
+
+/// A group of systems which is scheduled as a single unit.
+///
+/// The group's data access is the union of the access of its members, and its members are
+/// always run sequentially, in the order provided, with no other system interleaved between them.
+///
+/// # Examples
+///
+/// ```rust
+/// # use legion_core::prelude::*;
+/// # use legion_systems::prelude::*;
+/// # let integrate = SystemBuilder::new("integrate").build(|_,_,_,_| {});
+/// # let resolve_collisions = SystemBuilder::new("resolve_collisions").build(|_,_,_,_| {});
+/// let physics = SystemGroup::new("physics", vec![integrate, resolve_collisions]);
+///
+/// let mut schedule = Schedule::builder().add_system(physics).build();
+/// ```
+pub struct SystemGroup {
+    name: SystemId,
+    systems: Vec<Box<dyn Schedulable>>,
+    resources: Permissions<ResourceTypeId>,
+    components: Permissions<ComponentTypeId>,
+    archetypes: ArchetypeAccess,
+}
+
+impl SystemGroup {
+    /// Constructs a new group from the given systems, which will be run in the order provided.
+    pub fn new<T: Into<SystemId>>(name: T, systems: Vec<Box<dyn Schedulable>>) -> Self {
+        let mut resources = Permissions::new();
+        let mut components = Permissions::new();
+        for system in &systems {
+            let (read_res, read_comp) = system.reads();
+            let (write_res, write_comp) = system.writes();
+
+            read_res.iter().for_each(|res| resources.push_read(*res));
+            write_res.iter().for_each(|res| resources.push_write(*res));
+            read_comp
+                .iter()
+                .for_each(|comp| components.push_read(*comp));
+            write_comp
+                .iter()
+                .for_each(|comp| components.push_write(*comp));
+        }
+
+        Self {
+            name: name.into(),
+            systems,
+            resources,
+            components,
+            archetypes: ArchetypeAccess::Some(BitSet::default()),
+        }
+    }
+
+    /// Converts this group into a vector of its member systems.
+    pub fn into_vec(self) -> Vec<Box<dyn Schedulable>> { self.systems }
+}
+
+impl Runnable for SystemGroup {
+    fn name(&self) -> &SystemId { &self.name }
+
+    fn reads(&self) -> (&[ResourceTypeId], &[ComponentTypeId]) {
+        (self.resources.reads(), self.components.reads())
+    }
+
+    fn writes(&self) -> (&[ResourceTypeId], &[ComponentTypeId]) {
+        (self.resources.writes(), self.components.writes())
+    }
+
+    fn prepare(&mut self, world: &World) {
+        let mut archetypes = BitSet::default();
+        let mut all = false;
+        for system in &mut self.systems {
+            system.prepare(world);
+            match system.accesses_archetypes() {
+                ArchetypeAccess::All => all = true,
+                ArchetypeAccess::Some(bitset) => archetypes.union_with(bitset),
+            }
+        }
+
+        self.archetypes = if all {
+            ArchetypeAccess::All
+        } else {
+            ArchetypeAccess::Some(archetypes)
+        };
+    }
+
+    fn accesses_archetypes(&self) -> &ArchetypeAccess { &self.archetypes }
+
+    fn on_main_thread(&self) -> bool { self.systems.iter().any(|s| s.on_main_thread()) }
+
+    unsafe fn run_unsafe(&mut self, world: &World, resources: &Resources) {
+        for system in &mut self.systems {
+            system.run_unsafe(world, resources);
+        }
+    }
+
+    /// Groups do not own a command buffer; see `command_buffers_mut`.
+    fn command_buffer_mut(&self, _: WorldId) -> Option<RefMut<CommandBuffer>> { None }
+
+    fn command_buffers_mut(&self, world: WorldId) -> Vec<RefMut<CommandBuffer>> {
+        self.systems
+            .iter()
+            .flat_map(|system| system.command_buffers_mut(world))
+            .collect()
+    }
+}
+
+impl From<SystemGroup> for Box<dyn Schedulable> {
+    fn from(group: SystemGroup) -> Self { Box::new(group) }
+}
+
 /// A factory for `Schedule`.
 pub struct Builder {
     steps: Vec<Step>,
@@ -554,7 +1248,7 @@ impl Schedule {
                 Step::ThreadLocalSystem(system) => {
                     system.prepare(world);
                     system.run(world, resources);
-                    if let Some(cmd) = system.command_buffer_mut(world.id()) {
+                    for cmd in system.command_buffers_mut(world.id()) {
                         waiting_flush.push(ToFlush::System(cmd));
                     }
                 }
@@ -625,6 +1319,307 @@ mod tests {
         assert_eq!(*order, sorted);
     }
 
+    #[test]
+    fn execute_traced() {
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+
+        struct A;
+        struct B;
+
+        let mut resources = Resources::default();
+        resources.insert(A);
+        resources.insert(B);
+
+        let mut executor = Executor::new(vec![
+            SystemBuilder::new("write_a")
+                .write_resource::<A>()
+                .build(|_, _, _, _| {}),
+            SystemBuilder::new("write_b")
+                .write_resource::<B>()
+                .build(|_, _, _, _| {}),
+            SystemBuilder::new("read_a")
+                .read_resource::<A>()
+                .build(|_, _, _, _| {}),
+            SystemBuilder::new("read_b")
+                .read_resource::<B>()
+                .build(|_, _, _, _| {}),
+            SystemBuilder::new("write_both")
+                .write_resource::<A>()
+                .write_resource::<B>()
+                .build(|_, _, _, _| {}),
+        ]);
+
+        for _ in 0..10 {
+            let trace = executor.execute_traced(&mut world, &mut resources);
+            assert_eq!(5, trace.systems().len());
+            assert_eq!(10, trace.events().len());
+            assert_eq!(
+                vec![0, 1, 2, 3, 4],
+                sorted(trace.start_order()).collect::<Vec<_>>()
+            );
+
+            let mut finished = Vec::new();
+            for event in trace.events() {
+                match *event {
+                    TraceEvent::Started { system, gated_by } => {
+                        for dep in trace.dependencies(system) {
+                            assert!(finished.contains(dep));
+                        }
+                        if let Some(gate) = gated_by {
+                            assert!(trace.dependencies(system).contains(&gate));
+                        }
+                    }
+                    TraceEvent::Finished { system } => finished.push(system),
+                }
+            }
+
+            assert!(trace.dependencies(4).contains(&2));
+            assert!(trace.dependencies(4).contains(&3));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "par-schedule")]
+    fn execute_with_stats() {
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+        let mut resources = Resources::default();
+
+        // each system waits for the other to start, so that they must overlap
+        let started = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let system = |name: &'static str| {
+            let started = started.clone();
+            SystemBuilder::new(name).build(move |_, _, _, _| {
+                use std::sync::atomic::Ordering;
+                started.fetch_add(1, Ordering::SeqCst);
+                let timeout = Instant::now() + Duration::from_secs(5);
+                while started.load(Ordering::SeqCst) < 2 && Instant::now() < timeout {
+                    std::thread::yield_now();
+                }
+            })
+        };
+
+        let mut executor = Executor::new(vec![system("one"), system("two")]);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap();
+        let stats = pool.install(|| executor.execute_with_stats(&mut world, &mut resources));
+
+        assert_eq!(2, stats.max_concurrency);
+        assert!(stats.total_system_time <= stats.wall_time * 2);
+        assert!(stats.parallelism() <= 2.0);
+
+        // statistics are only recorded when requested
+        executor.execute(&mut world, &mut resources);
+        assert!(executor.stats.is_none());
+    }
+
+    #[test]
+    fn execute_budgeted() {
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+
+        struct A;
+        struct B;
+
+        let mut resources = Resources::default();
+        resources.insert(A);
+        resources.insert(B);
+
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        struct Marker;
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let system = |name: &'static str, id: usize, write_a: bool| {
+            let order = order.clone();
+            let builder = SystemBuilder::new(name);
+            if write_a {
+                builder.write_resource::<A>().build(move |cmd, _, _, _| {
+                    order.lock().unwrap().push(id);
+                    cmd.insert((), vec![(Marker,)]);
+                })
+            } else {
+                builder.write_resource::<B>().build(move |cmd, _, _, _| {
+                    order.lock().unwrap().push(id);
+                    cmd.insert((), vec![(Marker,)]);
+                })
+            }
+        };
+
+        let mut executor = Executor::new(vec![
+            system("one", 1, true),
+            system("two", 2, true),
+            system("three", 3, false),
+            system("four", 4, false),
+        ]);
+
+        // a zero budget only allows a single wave to run per call
+        let query = Read::<Marker>::query();
+        let mut calls = Vec::new();
+        loop {
+            let done =
+                executor.execute_budgeted(&mut world, &mut resources, Duration::from_secs(0));
+            calls.push(sorted(order.lock().unwrap().drain(..)).collect::<Vec<_>>());
+            if done {
+                break;
+            }
+            assert_eq!(0, query.iter(&world).count());
+        }
+        assert_eq!(4, query.iter(&world).count());
+
+        #[cfg(feature = "par-schedule")]
+        assert_eq!(vec![vec![1, 3], vec![2, 4]], calls);
+        #[cfg(not(feature = "par-schedule"))]
+        assert_eq!(vec![vec![1], vec![2], vec![3], vec![4]], calls);
+
+        // an unlimited budget runs every system in a single call
+        assert!(executor.execute_budgeted(&mut world, &mut resources, Duration::from_secs(60)));
+        assert_eq!(
+            vec![1, 2, 3, 4],
+            sorted(order.lock().unwrap().drain(..)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "par-schedule")]
+    fn execute_budgeted_new_archetype() {
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+
+        struct Gate;
+
+        let mut resources = Resources::default();
+        resources.insert(Gate);
+
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        struct Pos(f32);
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        struct Vel(f32);
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        struct Acc(f32);
+
+        world.insert((), vec![(Pos(0.), Vel(0.))]);
+        world.insert((), vec![(Pos(0.), Acc(0.))]);
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let gate = {
+            let order = order.clone();
+            SystemBuilder::new("gate")
+                .write_resource::<Gate>()
+                .build(move |_, _, _, _| order.lock().unwrap().push((0, 0)))
+        };
+        let with_vel = {
+            let order = order.clone();
+            SystemBuilder::new("with_vel")
+                .read_resource::<Gate>()
+                .with_query(Write::<Pos>::query().filter(component::<Vel>()))
+                .build(move |_, world, _, query| {
+                    let count = query.iter_mut(world).map(|mut pos| pos.0 += 1.).count();
+                    order.lock().unwrap().push((1, count));
+                })
+        };
+        let with_acc = {
+            let order = order.clone();
+            SystemBuilder::new("with_acc")
+                .read_resource::<Gate>()
+                .with_query(Write::<Pos>::query().filter(component::<Acc>()))
+                .build(move |_, world, _, query| {
+                    let count = query.iter_mut(world).map(|mut pos| pos.0 += 1.).count();
+                    order.lock().unwrap().push((2, count));
+                })
+        };
+
+        let mut executor = Executor::new(vec![gate, with_vel, with_acc]);
+        assert!(!executor.execute_budgeted(&mut world, &mut resources, Duration::from_secs(0)));
+        assert_eq!(vec![(0, 0)], *order.lock().unwrap());
+
+        // both remaining systems write to the new archetype, so can no longer run together
+        world.insert((), vec![(Pos(0.), Vel(0.), Acc(0.))]);
+        assert!(!executor.execute_budgeted(&mut world, &mut resources, Duration::from_secs(0)));
+        assert!(executor.execute_budgeted(&mut world, &mut resources, Duration::from_secs(0)));
+        assert_eq!(vec![(0, 0), (1, 2), (2, 2)], *order.lock().unwrap());
+    }
+
+    #[test]
+    fn system_group_runs_atomically() {
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+        let mut resources = Resources::default();
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        let system = |name: &'static str, id: usize| {
+            let order = order.clone();
+            SystemBuilder::new(name).build(move |_, _, _, _| {
+                order.lock().unwrap().push(id);
+            })
+        };
+
+        let group = SystemGroup::new("group", vec![system("two", 2), system("three", 3)]);
+
+        let mut schedule = Schedule::builder()
+            .add_system(system("one", 1))
+            .add_system(group)
+            .add_system(system("four", 4))
+            .add_system(system("five", 5))
+            .build();
+
+        for _ in 0..100 {
+            schedule.execute(&mut world, &mut resources);
+        }
+
+        let order = order.lock().unwrap();
+        assert_eq!(500, order.len());
+        for (i, id) in order.iter().enumerate() {
+            if *id == 2 {
+                assert_eq!(3, order[i + 1]);
+            }
+        }
+    }
+
+    #[test]
+    fn resource_writers_never_overlap() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+
+        #[derive(Default)]
+        struct Resource;
+
+        let mut resources = Resources::default();
+        resources.insert(Resource);
+
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+
+        let system = |name: &'static str| {
+            let running = running.clone();
+            let max_running = max_running.clone();
+            SystemBuilder::new(name)
+                .write_resource::<Resource>()
+                .build(move |_, _, _, _| {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_running.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(std::time::Duration::from_millis(1));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                })
+        };
+
+        let mut schedule = Schedule::builder()
+            .add_system(system("one"))
+            .add_system(system("two"))
+            .build();
+
+        for _ in 0..20 {
+            schedule.execute(&mut world, &mut resources);
+        }
+
+        assert_eq!(1, max_running.load(Ordering::SeqCst));
+    }
+
     #[test]
     fn flush() {
         let universe = Universe::new();
@@ -658,6 +1653,147 @@ mod tests {
         schedule.execute(&mut world, &mut resources);
     }
 
+    #[test]
+    fn phased_executor_barrier() {
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+        let mut resources = Resources::default();
+
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        struct TestComp(f32, f32, f32);
+
+        let counts = Arc::new(Mutex::new(Vec::new()));
+
+        let spawn = SystemBuilder::new("spawn").build(move |cmd, _, _, _| {
+            cmd.insert((), vec![(TestComp(0., 0., 0.),)]);
+        });
+        let count = |name: &'static str| {
+            let counts = counts.clone();
+            SystemBuilder::new(name)
+                .with_query(Read::<TestComp>::query())
+                .build(move |_, world, _, query| {
+                    counts.lock().unwrap().push(query.iter(world).count());
+                })
+        };
+
+        let mut executor = PhasedExecutor::new(vec![
+            StageEntry::System(spawn),
+            StageEntry::System(count("before")),
+            StageEntry::Barrier,
+            StageEntry::Barrier,
+            StageEntry::System(count("after")),
+        ]);
+        assert_eq!(2, executor.phases());
+
+        executor.execute(&mut world, &mut resources);
+        assert_eq!(vec![0, 1], *counts.lock().unwrap());
+        assert_eq!(3, executor.into_vec().len());
+    }
+
+    #[test]
+    fn main_thread_system() {
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+        let mut resources = Resources::default();
+
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        struct TestComp(f32, f32, f32);
+        #[derive(Default)]
+        struct Counter(usize);
+        resources.insert(Counter::default());
+
+        world.insert((), vec![(TestComp(0., 0., 0.),); 10]);
+
+        let threads = Arc::new(Mutex::new(Vec::new()));
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        let write = |name: &'static str| {
+            let order = order.clone();
+            SystemBuilder::new(name)
+                .with_query(Write::<TestComp>::query())
+                .build(move |_, world, _, query| {
+                    for mut comp in query.iter_mut(world) {
+                        comp.0 += 1.;
+                    }
+                    order.lock().unwrap().push(name);
+                })
+        };
+        let main = {
+            let threads = threads.clone();
+            let order = order.clone();
+            SystemBuilder::new("main")
+                .write_resource::<Counter>()
+                .with_query(Read::<TestComp>::query())
+                .on_main_thread()
+                .build(move |_, world, counter, query| {
+                    counter.0 += query.iter(world).count();
+                    threads.lock().unwrap().push(std::thread::current().id());
+                    order.lock().unwrap().push("main");
+                })
+        };
+        let other = SystemBuilder::new("other")
+            .read_resource::<Counter>()
+            .build(move |_, _, _, _| {});
+
+        let mut executor = Executor::new(vec![write("before"), main, other, write("after")]);
+        for _ in 0..3 {
+            executor.execute(&mut world, &mut resources);
+        }
+
+        let this_thread = std::thread::current().id();
+        assert_eq!(vec![this_thread; 3], *threads.lock().unwrap());
+        assert_eq!(30, resources.get::<Counter>().unwrap().0);
+        assert_eq!(
+            vec!["before", "main", "after"],
+            order.lock().unwrap()[..3].to_vec()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "par-schedule")]
+    fn main_thread_system_single_thread_pool() {
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+        let mut resources = Resources::default();
+
+        #[derive(Default)]
+        struct Counter(usize);
+        resources.insert(Counter::default());
+
+        let threads = Arc::new(Mutex::new(Vec::new()));
+
+        let write = |name: &'static str| {
+            SystemBuilder::new(name)
+                .write_resource::<Counter>()
+                .build(move |_, _, counter, _| counter.0 += 1)
+        };
+        let main = {
+            let threads = threads.clone();
+            SystemBuilder::new("main")
+                .write_resource::<Counter>()
+                .on_main_thread()
+                .build(move |_, _, counter, _| {
+                    counter.0 += 1;
+                    threads.lock().unwrap().push(std::thread::current().id());
+                })
+        };
+
+        // the main thread system depends on a system spawned onto the pool, which can only run
+        // on the thread executing the schedule
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let mut executor = Executor::new(vec![write("before"), main, write("after")]);
+        let pool_thread = pool.install(|| {
+            executor.execute(&mut world, &mut resources);
+            std::thread::current().id()
+        });
+
+        assert_eq!(vec![pool_thread], *threads.lock().unwrap());
+        assert_eq!(3, resources.get::<Counter>().unwrap().0);
+    }
+
     #[test]
     fn flush_thread_local() {
         let universe = Universe::new();
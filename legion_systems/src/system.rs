@@ -27,6 +27,16 @@ pub struct SystemAccess {
     pub resources: Permissions<ResourceTypeId>,
     pub components: Permissions<ComponentTypeId>,
     pub tags: Permissions<TagTypeId>,
+    pub queries: Vec<QueryAccess>,
+}
+
+/// Describes the component access of one of a system's queries, for diagnostic output.
+#[derive(Debug, Clone)]
+pub struct QueryAccess {
+    /// The name given to the query with `SystemBuilder::with_named_query`, if any.
+    pub name: Option<Cow<'static, str>>,
+    /// The component types read and written by the query.
+    pub components: Permissions<ComponentTypeId>,
 }
 
 /// This trait is for providing abstraction across tuples of queries for populating the type
@@ -168,6 +178,8 @@ where
 
     // We pre-allocate a command buffer for ourself. Writes are self-draining so we never have to rellocate.
     command_buffer: FxHashMap<WorldId, AtomicRefCell<CommandBuffer>>,
+
+    main_thread: bool,
 }
 
 impl<R, Q, F> Runnable for System<R, Q, F>
@@ -199,6 +211,10 @@ where
 
     fn accesses_archetypes(&self) -> &ArchetypeAccess { &self.archetypes }
 
+    fn on_main_thread(&self) -> bool { self.main_thread }
+
+    fn queries(&self) -> &[QueryAccess] { &self.access.queries }
+
     fn command_buffer_mut(&self, world: WorldId) -> Option<RefMut<CommandBuffer>> {
         self.command_buffer.get(&world).map(|cmd| cmd.get_mut())
     }
@@ -312,7 +328,9 @@ pub struct SystemBuilder<Q = (), R = ()> {
 
     resource_access: Permissions<ResourceTypeId>,
     component_access: Permissions<ComponentTypeId>,
+    query_access: Vec<QueryAccess>,
     access_all_archetypes: bool,
+    main_thread: bool,
 }
 
 impl SystemBuilder<(), ()> {
@@ -327,7 +345,9 @@ impl SystemBuilder<(), ()> {
             resources: (),
             resource_access: Permissions::default(),
             component_access: Permissions::default(),
+            query_access: Vec::new(),
             access_all_archetypes: false,
+            main_thread: false,
         }
     }
 }
@@ -352,6 +372,10 @@ where
         Q: ConsAppend<Query<V, F>>,
     {
         self.component_access.add(V::requires_permissions());
+        self.query_access.push(QueryAccess {
+            name: None,
+            components: V::requires_permissions(),
+        });
 
         SystemBuilder {
             name: self.name,
@@ -359,8 +383,30 @@ where
             resources: self.resources,
             resource_access: self.resource_access,
             component_access: self.component_access,
+            query_access: self.query_access,
             access_all_archetypes: self.access_all_archetypes,
+            main_thread: self.main_thread,
+        }
+    }
+
+    /// Defines a query to provide this system for its execution, as with `with_query`, labelled
+    /// with a name which identifies it in diagnostic output such as `Runnable::describe`.
+    pub fn with_named_query<V, F, N>(
+        self,
+        name: N,
+        query: Query<V, F>,
+    ) -> SystemBuilder<<Q as ConsAppend<Query<V, F>>>::Output, R>
+    where
+        V: for<'a> View<'a>,
+        F: 'static + EntityFilter,
+        Q: ConsAppend<Query<V, F>>,
+        N: Into<Cow<'static, str>>,
+    {
+        let mut builder = self.with_query(query);
+        if let Some(access) = builder.query_access.last_mut() {
+            access.name = Some(name.into());
         }
+        builder
     }
 
     /// Flag this resource type as being read by this system.
@@ -381,7 +427,9 @@ where
             resources: ConsAppend::append(self.resources, Read::<T>::default()),
             resource_access: self.resource_access,
             component_access: self.component_access,
+            query_access: self.query_access,
             access_all_archetypes: self.access_all_archetypes,
+            main_thread: self.main_thread,
         }
     }
 
@@ -403,7 +451,9 @@ where
             resources: ConsAppend::append(self.resources, Write::<T>::default()),
             resource_access: self.resource_access,
             component_access: self.component_access,
+            query_access: self.query_access,
             access_all_archetypes: self.access_all_archetypes,
+            main_thread: self.main_thread,
         }
     }
 
@@ -447,6 +497,17 @@ where
         self
     }
 
+    /// Marks the system as needing to run on the thread which executes the schedule, rather than
+    /// on a rayon worker thread.
+    ///
+    /// Other systems continue to run in parallel around it, so long as they do not conflict with
+    /// the system's data access. This is useful for systems which interact with APIs which are
+    /// bound to the main thread, such as windowing or graphics.
+    pub fn on_main_thread(mut self) -> Self {
+        self.main_thread = true;
+        self
+    }
+
     /// Builds a standard legion `System`. A system is considered a closure for all purposes. This
     /// closure is `FnMut`, allowing for capture of variables for tracking state for this system.
     /// Instead of the classic OOP architecture of a system, this lets you still maintain state
@@ -481,8 +542,10 @@ where
                 resources: self.resource_access,
                 components: self.component_access,
                 tags: Permissions::default(),
+                queries: self.query_access,
             },
             command_buffer: FxHashMap::default(),
+            main_thread: self.main_thread,
         })
     }
 
@@ -516,8 +579,10 @@ where
                 resources: self.resource_access,
                 components: self.component_access,
                 tags: Permissions::default(),
+                queries: self.query_access,
             },
             command_buffer: FxHashMap::default(),
+            main_thread: self.main_thread,
         })
     }
 }
@@ -828,6 +893,56 @@ mod tests {
         system.run(&mut world, &mut resources);
     }
 
+    #[test]
+    fn system_spawn_buffered_entity() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+        let mut resources = Resources::default();
+
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        struct Parent(Entity);
+
+        let spawned = Arc::new(Mutex::new(Vec::new()));
+        let spawned_copy = spawned.clone();
+        let mut system = SystemBuilder::<()>::new("TestSystem").build(move |cmd, _, _, _| {
+            let parent = cmd.insert((), vec![(Pos(1., 2., 3.),)])[0];
+
+            // the id is usable straight away, before the buffer is flushed
+            let child = cmd.insert((), vec![(Parent(parent),)])[0];
+            cmd.add_component(parent, Vel(0.1, 0.2, 0.3));
+            spawned_copy.lock().unwrap().extend(vec![parent, child]);
+        });
+
+        system.prepare(&world);
+        system.run(&mut world, &mut resources);
+
+        let (parent, child) = {
+            let spawned = spawned.lock().unwrap();
+            (spawned[0], spawned[1])
+        };
+        assert!(world.get_component::<Pos>(parent).is_none());
+
+        system
+            .command_buffer_mut(world.id())
+            .unwrap()
+            .write(&mut world);
+
+        assert_eq!(
+            Pos(1., 2., 3.),
+            *world.get_component::<Pos>(parent).unwrap()
+        );
+        assert_eq!(
+            Vel(0.1, 0.2, 0.3),
+            *world.get_component::<Vel>(parent).unwrap()
+        );
+        assert_eq!(
+            Parent(parent),
+            *world.get_component::<Parent>(child).unwrap()
+        );
+    }
+
     #[test]
     #[cfg(feature = "par-schedule")]
     fn par_res_write() {
@@ -1174,6 +1289,81 @@ mod tests {
         schedule.execute(&mut world, &mut resources);
     }
 
+    #[test]
+    fn adhoc_read_query() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+        let mut resources = Resources::default();
+
+        world.insert(
+            (),
+            vec![
+                (Pos(1., 2., 3.), Vel(0.1, 0.2, 0.3)),
+                (Pos(4., 5., 6.), Vel(0.4, 0.5, 0.6)),
+            ],
+        );
+
+        let count = Arc::new(Mutex::new(0));
+        let count_clone = count.clone();
+
+        let mut system = SystemBuilder::new("TestSystem")
+            .with_query(<(Read<Pos>, Write<Vel>)>::query())
+            .build(move |_, world, _, _| {
+                let query = world.query::<Read<Pos>>();
+                *count_clone.lock().unwrap() = query.iter(world).count();
+            });
+
+        system.prepare(&world);
+        system.run(&mut world, &mut resources);
+
+        assert_eq!(2, *count.lock().unwrap());
+    }
+
+    #[test]
+    fn double_buffered_resource() {
+        let _ = tracing_subscriber::fmt::try_init();
+
+        use crate::resource::DoubleBuffered;
+
+        let universe = Universe::new();
+        let mut world = universe.create_world();
+        let mut resources = Resources::default();
+        resources.insert_double_buffered::<Vec<usize>>();
+
+        let mut frame = 0;
+        let producer = SystemBuilder::new("producer")
+            .write_resource::<DoubleBuffered<Vec<usize>>>()
+            .build(move |_, _, events, _| {
+                events.next().push(frame);
+                frame += 1;
+            });
+
+        let observed = Arc::new(Mutex::new(Vec::new()));
+        let observed_clone = observed.clone();
+        let consumer = SystemBuilder::new("consumer")
+            .read_resource::<DoubleBuffered<Vec<usize>>>()
+            .build(move |_, _, events, _| {
+                observed_clone
+                    .lock()
+                    .unwrap()
+                    .push(events.current().clone());
+            });
+
+        let mut schedule = Schedule::builder()
+            .add_system(producer)
+            .add_system(consumer)
+            .add_thread_local_fn(|_, resources| resources.swap_double_buffers())
+            .build();
+
+        for _ in 0..3 {
+            schedule.execute(&mut world, &mut resources);
+        }
+
+        assert_eq!(vec![vec![], vec![0], vec![1]], *observed.lock().unwrap());
+    }
+
     #[test]
     fn thread_local_query() {
         let mut world = World::default();
@@ -1186,4 +1376,25 @@ mod tests {
         let mut schedule = Schedule::builder().add_thread_local(system).build();
         schedule.execute(&mut world, &mut Resources::default());
     }
+
+    #[test]
+    fn describe_named_queries() {
+        let system = SystemBuilder::new("movement")
+            .with_named_query("movers", <(Write<Pos>, Read<Vel>)>::query())
+            .with_query(<Read<Pos>>::query())
+            .with_named_query("velocities", <Read<Vel>>::query())
+            .build(|_, _, _, _| {});
+
+        let queries = system.queries();
+        assert_eq!(3, queries.len());
+        assert_eq!(Some("movers"), queries[0].name.as_deref());
+        assert_eq!(None, queries[1].name.as_deref());
+        assert_eq!(Some("velocities"), queries[2].name.as_deref());
+
+        let description = system.describe();
+        assert!(description.starts_with("movement"));
+        assert!(description.contains("query \"movers\""));
+        assert!(description.contains("query 1"));
+        assert!(description.contains("query \"velocities\""));
+    }
 }
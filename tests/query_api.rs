@@ -1,6 +1,8 @@
 #![allow(clippy::map_clone)]
 
+use legion::filter::{EntityFilterTuple, Or, Passthrough};
 use legion::prelude::*;
+use legion::storage::{current_version, ComponentTypeId};
 use std::collections::HashMap;
 
 #[cfg(feature = "par-iter")]
@@ -20,6 +22,8 @@ struct Accel(f32, f32, f32);
 struct Model(u32);
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 struct Static;
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+struct ZOrder(i32);
 
 #[test]
 fn query_read_entity_data() {
@@ -93,6 +97,483 @@ fn query_try_write_entity_data() {
     );
 }
 
+#[test]
+fn query_is_empty() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let query = Read::<Pos>::query();
+    assert!(query.is_empty(&world));
+
+    let entities = world
+        .insert((Static,), vec![(Pos(1., 2., 3.), Rot(0.1, 0.2, 0.3))])
+        .to_vec();
+    world.insert((Model(1),), vec![(Vel(1., 2., 3.),)]);
+    assert!(!query.is_empty(&world));
+    let static_vel = <Read<Vel>>::query().filter(tag::<Static>());
+    assert!(static_vel.is_empty(&world));
+    let model_vel = <Read<Vel>>::query().filter(tag::<Model>());
+    assert!(!model_vel.is_empty(&world));
+
+    // the archetype still exists, but all of its chunks are empty
+    for entity in entities {
+        world.delete(entity);
+    }
+    assert!(query.is_empty(&world));
+}
+
+#[test]
+fn query_disabled_entities() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .insert((), vec![(Pos(1., 2., 3.),), (Pos(4., 5., 6.),)])
+        .to_vec();
+
+    world.set_enabled(entities[0], false).unwrap();
+    assert!(!world.is_enabled(entities[0]));
+    assert!(world.is_alive(entities[0]));
+
+    let query = Read::<Pos>::query();
+    let found = query
+        .iter_entities(&world)
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+    assert_eq!(found, vec![entities[1]]);
+
+    let all = Read::<Pos>::query().include_disabled();
+    assert_eq!(all.iter(&world).count(), 2);
+
+    world.set_enabled(entities[0], true).unwrap();
+    assert!(world.is_enabled(entities[0]));
+    assert_eq!(query.iter(&world).count(), 2);
+    assert_eq!(
+        Pos(1., 2., 3.),
+        *world.get_component::<Pos>(entities[0]).unwrap()
+    );
+}
+
+#[test]
+fn query_entities_only() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert((Static,), vec![(Pos(1., 2., 3.), Rot(0.1, 0.2, 0.3))]);
+    world.insert((Model(1),), vec![(Pos(4., 5., 6.), Rot(0.4, 0.5, 0.6)); 3]);
+    world.insert((Model(1),), vec![(Vel(1., 2., 3.),)]);
+
+    let query = <(Write<Pos>, Read<Rot>)>::query().filter(tag::<Model>());
+    let ids = query.entities(&world).collect::<Vec<_>>();
+    let expected = query
+        .iter_entities_mut(&mut world)
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+
+    assert_eq!(3, ids.len());
+    assert_eq!(expected, ids);
+}
+
+#[test]
+fn query_collect_chunks_threads() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    for i in 0..8 {
+        world.insert((Model(i),), (0..100).map(|j| (Pos(j as f32, 0., 0.),)));
+    }
+
+    let query = Read::<Pos>::query();
+    let chunks = query.collect_chunks(&world);
+    assert_eq!(query.iter_chunks(&world).count(), chunks.len());
+
+    let expected: f32 = query.iter(&world).map(|pos| pos.0).sum();
+    let total: f32 = std::thread::scope(|scope| {
+        let workers = chunks
+            .chunks(3)
+            .map(|batch| {
+                scope.spawn(move || {
+                    batch
+                        .iter()
+                        .map(|chunk| {
+                            chunk
+                                .components::<Pos>()
+                                .unwrap()
+                                .iter()
+                                .map(|p| p.0)
+                                .sum::<f32>()
+                        })
+                        .sum::<f32>()
+                })
+            })
+            .collect::<Vec<_>>();
+        workers.into_iter().map(|w| w.join().unwrap()).sum()
+    });
+
+    assert_eq!(expected, total);
+}
+
+#[test]
+fn query_zip_indexed() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .insert((), (0..10).map(|i| (Pos(i as f32, 0., 0.),)))
+        .to_vec();
+
+    // only covers the first 5 entities
+    let names = entities.iter().take(5).fold(Vec::new(), |mut names, e| {
+        let index = e.index() as usize;
+        names.resize(names.len().max(index + 1), String::new());
+        names[index] = format!("entity {}", index);
+        names
+    });
+
+    let query = Read::<Pos>::query();
+    let pairs = query
+        .zip_indexed(&world, &names)
+        .map(|(pos, name)| (pos.0, name.clone()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(5, pairs.len());
+    for (x, name) in pairs {
+        let entity = entities[x as usize];
+        assert_eq!(format!("entity {}", entity.index()), name);
+    }
+
+    let offsets = vec![1.0f32; names.len()];
+    for (mut pos, offset) in Write::<Pos>::query().zip_indexed_mut(&mut world, &offsets) {
+        pos.1 = *offset;
+    }
+
+    for (i, entity) in entities.iter().enumerate() {
+        let expected = if i < 5 { 1. } else { 0. };
+        assert_eq!(expected, world.get_component::<Pos>(*entity).unwrap().1);
+    }
+}
+
+#[test]
+fn query_changed_since() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .insert((), (0..4).map(|i| (Pos(i as f32, 0., 0.), Rot(0., 0., 0.))))
+        .to_vec();
+
+    let reader = <Read<Pos>>::query();
+    let version = current_version();
+    assert_eq!(0, reader.changed_since::<Pos, _>(&world, version).count());
+
+    // until the chunk is tracked, any change marks the whole chunk as changed
+    world.get_component_mut::<Pos>(entities[0]).unwrap().2 = 1.;
+    assert_eq!(4, reader.changed_since::<Pos, _>(&world, version).count());
+
+    // only the components which are mutably dereferenced are marked changed
+    let version = current_version();
+    let tracked = <(Tracked<Pos>, Read<Rot>)>::query();
+    for (mut pos, _) in tracked.iter_mut(&mut world) {
+        if pos.0 >= 2. {
+            pos.1 = 1.;
+        }
+    }
+
+    let changed = reader
+        .changed_since::<Pos, _>(&world, version)
+        .collect::<Vec<_>>();
+    assert_eq!(&entities[2..], &changed[..]);
+
+    let version = current_version();
+    world.get_component_mut::<Pos>(entities[0]).unwrap().2 = 1.;
+    let changed = reader
+        .changed_since::<Pos, _>(&world, version)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![entities[0]], changed);
+
+    // a plain write marks the whole chunk as changed
+    let version = current_version();
+    for _ in <Write<Pos>>::query().iter_mut(&mut world) {}
+    assert_eq!(4, reader.changed_since::<Pos, _>(&world, version).count());
+}
+
+#[test]
+fn query_in_archetype() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert((), vec![(Pos(1., 2., 3.),); 3]);
+    let entities = world
+        .insert((), vec![(Pos(4., 5., 6.), Rot(0.4, 0.5, 0.6)); 2])
+        .to_vec();
+
+    let archetype = world.get_entity_location(entities[0]).unwrap().archetype();
+    let query = Read::<Pos>::query().in_archetype(archetype);
+    let found = query
+        .iter_entities(&world)
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+
+    assert_eq!(entities, found);
+}
+
+#[test]
+fn query_component_slice_alignment() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    #[repr(align(32))]
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Simd([f32; 8]);
+    #[repr(align(128))]
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Wide(u8);
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert((), vec![(Pos(1., 2., 3.), Simd([1.; 8])); 100]);
+    world.insert((), vec![(Model(1), Simd([2.; 8]), Wide(3)); 100]);
+
+    let query = <Read<Simd>>::query();
+    for chunk in query.iter_chunks(&world) {
+        let slice = chunk.components::<Simd>().unwrap();
+        assert_eq!(0, slice.as_ptr() as usize % 32);
+    }
+
+    let query = <(Read<Simd>, Read<Wide>)>::query();
+    for chunk in query.iter_chunks(&world) {
+        let slice = chunk.components::<Wide>().unwrap();
+        assert_eq!(0, slice.as_ptr() as usize % 128);
+    }
+}
+
+#[test]
+fn query_single() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let query = <Read<Model>>::query();
+    assert!(query.single(&world).is_none());
+
+    world.insert((), vec![(Pos(1., 2., 3.),); 5]);
+    world.insert((), vec![(Model(7), Pos(4., 5., 6.))]);
+    assert_eq!(Model(7), *query.single(&world).unwrap());
+
+    *<Write<Model>>::query().single_mut(&mut world).unwrap() = Model(8);
+    assert_eq!(Model(8), *query.single(&world).unwrap());
+}
+
+#[test]
+#[should_panic(expected = "more than one entity")]
+fn query_single_multiple_matches() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert((), vec![(Model(1),), (Model(2),)]);
+    <Read<Model>>::query().single(&world);
+}
+
+#[test]
+fn query_overwrite_components() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .insert(
+            (),
+            (0..10).map(|i| (Pos(i as f32, 0., 0.), Rot(0., 0., 0.))),
+        )
+        .to_vec();
+
+    // copy the column out, and write it back after processing it elsewhere
+    let query = <(Write<Pos>, Read<Rot>)>::query();
+    for chunk in query.iter_chunks_mut(&mut world) {
+        let mut data = chunk.components::<Pos>().unwrap().to_vec();
+        for pos in data.iter_mut() {
+            pos.1 = pos.0 * 2.;
+        }
+        assert!(!chunk.overwrite_components(&data[1..]));
+        assert!(chunk.overwrite_components(&data));
+    }
+
+    for (i, pos) in <Read<Pos>>::query().iter(&world).enumerate() {
+        assert_eq!(Pos(i as f32, i as f32 * 2., 0.), *pos);
+    }
+
+    let location = world.get_entity_location(entities[0]).unwrap();
+    let data = (0..10).map(|_| Pos(1., 1., 1.)).collect::<Vec<_>>();
+    assert!(world.overwrite_chunk_components(location, &data));
+    assert!(!world.overwrite_chunk_components(location, &[Vel(0., 0., 0.); 10]));
+    assert!(<Read<Pos>>::query()
+        .iter(&world)
+        .all(|pos| *pos == Pos(1., 1., 1.)));
+}
+
+#[test]
+fn query_dynamic_tag_value() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    // ids as they might be assigned while loading content
+    let forest = DynamicTag(1);
+    let desert = DynamicTag(2);
+
+    let trees = world
+        .insert((forest,), vec![(Pos(1., 0., 0.),), (Pos(2., 0., 0.),)])
+        .to_vec();
+    let cacti = world.insert((desert,), vec![(Pos(3., 0., 0.),)]).to_vec();
+
+    let query = <Read<Pos>>::query().filter(tag_value(&forest));
+    let found = query
+        .iter_entities(&world)
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+    assert_eq!(trees, found);
+
+    let query = <Read<Pos>>::query().filter(tag_value(&desert));
+    let found = query
+        .iter_entities(&world)
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+    assert_eq!(cacti, found);
+
+    world.add_tag(trees[0], desert).unwrap();
+    assert_eq!(2, query.iter(&world).count());
+    assert_eq!(Some(&desert), world.get_tag::<DynamicTag>(trees[0]));
+}
+
+#[test]
+fn query_access_stats() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    assert!(world.access_stats().is_none());
+
+    let entities = world
+        .insert(
+            (Static,),
+            vec![
+                (Pos(1., 2., 3.), Rot(0.1, 0.2, 0.3)),
+                (Pos(4., 5., 6.), Rot(0.4, 0.5, 0.6)),
+                (Pos(7., 8., 9.), Rot(0.7, 0.8, 0.9)),
+            ],
+        )
+        .to_vec();
+    world.insert((), vec![(Pos(1., 1., 1.),), (Pos(2., 2., 2.),)]);
+
+    world.enable_access_stats();
+
+    let query = <(Read<Pos>, Write<Rot>)>::query();
+    for (_, mut rot) in query.iter_mut(&mut world) {
+        rot.0 = 0.;
+    }
+
+    let query = Read::<Pos>::query();
+    assert_eq!(5, query.iter(&world).count());
+
+    world.get_component::<Pos>(entities[0]).unwrap();
+    world.get_component_mut::<Rot>(entities[1]).unwrap();
+
+    let stats = world.access_stats().unwrap();
+    let pos = stats.get(ComponentTypeId::of::<Pos>());
+    let rot = stats.get(ComponentTypeId::of::<Rot>());
+    assert_eq!(9, pos.reads);
+    assert_eq!(0, pos.writes);
+    assert_eq!(0, rot.reads);
+    assert_eq!(4, rot.writes);
+    assert_eq!(2, stats.counts().len());
+
+    world.reset_access_stats();
+    let stats = world.access_stats().unwrap();
+    assert_eq!(0, stats.get(ComponentTypeId::of::<Pos>()).reads);
+    assert!(stats.counts().is_empty());
+}
+
+#[test]
+fn query_try_map() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Health(i32);
+
+    #[derive(Debug, PartialEq)]
+    struct NegativeHealth(i32);
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .insert(
+            (),
+            vec![
+                (Health(10), Pos(0., 0., 0.)),
+                (Health(-3), Pos(1., 0., 0.)),
+                (Health(7), Pos(2., 0., 0.)),
+                (Health(-1), Pos(3., 0., 0.)),
+            ],
+        )
+        .to_vec();
+
+    let validate = |_: Entity, health: legion::borrow::Ref<Health>| {
+        if health.0 >= 0 {
+            Ok(health.0)
+        } else {
+            Err(NegativeHealth(health.0))
+        }
+    };
+
+    let query = Read::<Health>::query();
+    let (valid, invalid) = query.try_map(&world, validate);
+    assert_eq!(vec![10, 7], valid);
+    assert_eq!(
+        vec![
+            (entities[1], NegativeHealth(-3)),
+            (entities[3], NegativeHealth(-1))
+        ],
+        invalid
+    );
+
+    // repair invalid entities while validating
+    let query = <(Write<Health>, Read<Pos>)>::query();
+    let (valid, invalid) = query.try_map_mut(&mut world, |_, (mut health, _)| {
+        if health.0 < 0 {
+            health.0 = 0;
+            Err(())
+        } else {
+            Ok(health.0)
+        }
+    });
+    assert_eq!(vec![10, 7], valid);
+    assert_eq!(2, invalid.len());
+    assert_eq!(
+        Health(0),
+        *world.get_component::<Health>(entities[1]).unwrap()
+    );
+}
+
 #[test]
 fn query_cached_read_entity_data() {
     let _ = tracing_subscriber::fmt::try_init();
@@ -190,6 +671,115 @@ fn query_read_entity_data_par_foreach() {
     assert_eq!(components.len(), count.load(Ordering::SeqCst));
 }
 
+#[test]
+#[cfg(feature = "par-iter")]
+fn query_par_for_each_mut_matches_sequential() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert(
+        (),
+        (0..10_000).map(|i| {
+            let i = i as f32;
+            (Pos(i, 0., -i), Vel(0.5, i, 2.))
+        }),
+    );
+
+    let expected = <(Read<Pos>, Read<Vel>)>::query()
+        .iter_entities(&world)
+        .map(|(entity, (pos, vel))| (entity, Pos(pos.0 + vel.0, pos.1 + vel.1, pos.2 + vel.2)))
+        .collect::<HashMap<_, _>>();
+    assert_eq!(10_000, expected.len());
+
+    let query = <(Write<Pos>, Read<Vel>)>::query();
+    query.par_for_each_mut(&mut world, |(mut pos, vel)| {
+        pos.0 += vel.0;
+        pos.1 += vel.1;
+        pos.2 += vel.2;
+    });
+
+    let updated = Read::<Pos>::query()
+        .iter_entities(&world)
+        .map(|(entity, pos)| (entity, *pos))
+        .collect::<HashMap<_, _>>();
+    assert_eq!(expected, updated);
+}
+
+#[test]
+#[cfg(feature = "par-iter")]
+fn query_par_for_each_deterministic() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    // one chunk per model
+    for model in 0..10 {
+        world.insert(
+            (Model(model),),
+            (0..5).map(|_| (Pos(model as f32, 0., 0.),)),
+        );
+    }
+
+    let query = Read::<Pos>::query();
+    let run = || {
+        let assignments = std::sync::Mutex::new(Vec::new());
+        query.par_for_each_deterministic(&world, 4, |thread, pos| {
+            assignments.lock().unwrap().push((thread, pos.0 as usize));
+        });
+        let mut assignments = assignments.into_inner().unwrap();
+        assignments.sort();
+        assignments
+    };
+
+    let first = run();
+    assert_eq!(50, first.len());
+
+    // chunks are split into contiguous groups of 3, 2, 3 and 2 chunks
+    for (thread, model) in &first {
+        assert_eq!(model * 4 / 10, *thread);
+    }
+
+    for _ in 0..10 {
+        assert_eq!(first, run());
+    }
+}
+
+#[test]
+#[cfg(feature = "par-iter")]
+fn query_par_for_each_init() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    // spread entities over many chunks so that the work is split between threads
+    for model in 0..20 {
+        world.insert(
+            (Model(model),),
+            (0..50).map(|i| (Pos((i % 10) as f32, 0., 0.),)),
+        );
+    }
+
+    let query = Read::<Pos>::query();
+    let bins = query.par_for_each_init(
+        &world,
+        || vec![0usize; 10],
+        |bins, pos| bins[pos.0 as usize] += 1,
+    );
+
+    let mut merged = vec![0usize; 10];
+    for bins in bins {
+        for (total, count) in merged.iter_mut().zip(bins) {
+            *total += count;
+        }
+    }
+
+    assert_eq!(vec![100; 10], merged);
+}
+
 #[test]
 fn query_read_entity_data_tuple() {
     let _ = tracing_subscriber::fmt::try_init();
@@ -224,7 +814,7 @@ fn query_read_entity_data_tuple() {
 }
 
 #[test]
-fn query_write_entity_data() {
+fn query_read_entity_data_triple() {
     let _ = tracing_subscriber::fmt::try_init();
 
     let universe = Universe::new();
@@ -232,33 +822,36 @@ fn query_write_entity_data() {
 
     let shared = (Static, Model(5));
     let components = vec![
-        (Pos(1., 2., 3.), Rot(0.1, 0.2, 0.3)),
-        (Pos(4., 5., 6.), Rot(0.4, 0.5, 0.6)),
+        (Pos(1., 2., 3.), Rot(0.1, 0.2, 0.3), Vel(0.7, 0.8, 0.9)),
+        (Pos(4., 5., 6.), Rot(0.4, 0.5, 0.6), Vel(1.0, 1.1, 1.2)),
     ];
 
-    let mut expected = HashMap::<Entity, (Pos, Rot)>::new();
+    let mut expected = HashMap::<Entity, (Pos, Rot, Vel)>::new();
 
     for (i, e) in world.insert(shared, components.clone()).iter().enumerate() {
-        if let Some((pos, rot)) = components.get(i) {
-            expected.insert(*e, (*pos, *rot));
+        if let Some(data) = components.get(i) {
+            expected.insert(*e, *data);
         }
     }
 
-    let query = Write::<Pos>::query();
+    // entities without all three components are not matched
+    world.insert(shared, vec![(Pos(0., 0., 0.), Rot(0., 0., 0.))]);
+
+    let query = <(Read<Pos>, Read<Rot>, Read<Vel>)>::query();
 
     let mut count = 0;
-    for (entity, mut pos) in query.iter_entities_mut(&mut world) {
+    for (entity, (pos, rot, vel)) in query.iter_entities(&world) {
         assert_eq!(expected.get(&entity).unwrap().0, *pos);
+        assert_eq!(expected.get(&entity).unwrap().1, *rot);
+        assert_eq!(expected.get(&entity).unwrap().2, *vel);
         count += 1;
-
-        pos.0 = 0.0;
     }
 
     assert_eq!(components.len(), count);
 }
 
 #[test]
-fn query_write_entity_data_tuple() {
+fn query_write_entity_data() {
     let _ = tracing_subscriber::fmt::try_init();
 
     let universe = Universe::new();
@@ -278,10 +871,44 @@ fn query_write_entity_data_tuple() {
         }
     }
 
-    let query = <(Write<Pos>, Write<Rot>)>::query();
+    let query = Write::<Pos>::query();
 
     let mut count = 0;
-    for (entity, (mut pos, mut rot)) in query.iter_entities_mut(&mut world) {
+    for (entity, mut pos) in query.iter_entities_mut(&mut world) {
+        assert_eq!(expected.get(&entity).unwrap().0, *pos);
+        count += 1;
+
+        pos.0 = 0.0;
+    }
+
+    assert_eq!(components.len(), count);
+}
+
+#[test]
+fn query_write_entity_data_tuple() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let shared = (Static, Model(5));
+    let components = vec![
+        (Pos(1., 2., 3.), Rot(0.1, 0.2, 0.3)),
+        (Pos(4., 5., 6.), Rot(0.4, 0.5, 0.6)),
+    ];
+
+    let mut expected = HashMap::<Entity, (Pos, Rot)>::new();
+
+    for (i, e) in world.insert(shared, components.clone()).iter().enumerate() {
+        if let Some((pos, rot)) = components.get(i) {
+            expected.insert(*e, (*pos, *rot));
+        }
+    }
+
+    let query = <(Write<Pos>, Write<Rot>)>::query();
+
+    let mut count = 0;
+    for (entity, (mut pos, mut rot)) in query.iter_entities_mut(&mut world) {
         assert_eq!(expected.get(&entity).unwrap().0, *pos);
         assert_eq!(expected.get(&entity).unwrap().1, *rot);
         count += 1;
@@ -502,6 +1129,33 @@ fn query_on_changed_self_changes() {
     assert_eq!(components.len(), count);
 }
 
+#[test]
+fn query_on_changed_single_entity() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let changed_chunk = world
+        .insert((Model(1),), (0..2).map(|i| (Pos(i as f32, 0., 0.),)))
+        .to_vec();
+    world.insert((Model(2),), (0..2).map(|i| (Pos(i as f32, 0., 0.),)));
+
+    let query = Read::<Pos>::query().filter(changed::<Pos>());
+    assert_eq!(4, query.iter_entities(&world).count());
+    assert_eq!(0, query.iter_entities(&world).count());
+
+    world.get_component_mut::<Pos>(changed_chunk[1]).unwrap().1 = 1.;
+
+    // the filter has chunk granularity, so the whole chunk is yielded exactly once
+    let entities = query
+        .iter_entities(&world)
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+    assert_eq!(changed_chunk, entities);
+    assert_eq!(0, query.iter_entities(&world).count());
+}
+
 #[test]
 fn query_try_with_changed_filter() {
     let _ = tracing_subscriber::fmt::try_init();
@@ -607,6 +1261,36 @@ fn query_iter_tag() {
     }
 }
 
+#[test]
+fn query_read_entity_data_with_tag() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    for model in 0..3 {
+        world.insert(
+            (Model(model),),
+            (0..4).map(|i| (Pos(model as f32, i as f32, 0.),)),
+        );
+    }
+
+    let query = <(Read<Pos>, Tagged<Model>)>::query();
+
+    let mut count = 0;
+    for mut chunk in query.iter_chunks(&world) {
+        let tag = *chunk.tag::<Model>().unwrap();
+        for (entity, (pos, model)) in chunk.iter_entities_mut() {
+            assert_eq!(tag, *model);
+            assert_eq!(Some(model), world.get_tag::<Model>(entity));
+            assert_eq!(model.0 as f32, pos.0);
+            count += 1;
+        }
+    }
+
+    assert_eq!(12, count);
+}
+
 #[test]
 fn query_get_all_components() {
     let _ = tracing_subscriber::fmt::try_init();
@@ -630,3 +1314,533 @@ fn query_get_all_components() {
     assert_eq!(2, query_pos.components_mut::<Pos, World>(&mut world).len());
     assert_eq!(1, query_rot.components_mut::<Rot, World>(&mut world).len());
 }
+
+#[test]
+fn query_sort_chunks_by() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert(
+        (Model(1),),
+        vec![
+            (Pos(1., 0., 0.), ZOrder(3)),
+            (Pos(2., 0., 0.), ZOrder(-1)),
+            (Pos(3., 0., 0.), ZOrder(7)),
+            (Pos(4., 0., 0.), ZOrder(0)),
+        ],
+    );
+    world.insert(
+        (Model(2),),
+        vec![(Pos(5., 0., 0.), ZOrder(2)), (Pos(6., 0., 0.), ZOrder(1))],
+    );
+
+    let query = <(Read<ZOrder>, Tagged<Model>)>::query().sort_chunks_by::<ZOrder>();
+
+    let mut orders = HashMap::<Model, Vec<i32>>::new();
+    for (_, (z, model)) in query.iter_entities(&world) {
+        orders.entry(*model).or_default().push(z.0);
+    }
+
+    assert_eq!(vec![-1, 0, 3, 7], orders[&Model(1)]);
+    assert_eq!(vec![1, 2], orders[&Model(2)]);
+
+    let query = <(Write<Pos>, Read<ZOrder>)>::query().sort_chunks_by::<ZOrder>();
+    for (_, (mut pos, z)) in query.iter_entities_mut(&mut world) {
+        pos.1 = z.0 as f32;
+    }
+
+    for (pos, z) in <(Read<Pos>, Read<ZOrder>)>::query().iter(&world) {
+        assert_eq!(z.0 as f32, pos.1);
+    }
+}
+
+#[test]
+fn query_skip_take_across_chunks() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    for model in 0..4 {
+        world.insert(
+            (Model(model),),
+            (0..10).map(|i| (Pos((model * 10 + i) as f32, 0., 0.),)),
+        );
+    }
+
+    let query = Read::<Pos>::query();
+    let all = query.iter(&world).map(|pos| pos.0).collect::<Vec<_>>();
+    assert_eq!(40, all.len());
+
+    let window = query
+        .iter(&world)
+        .skip(15)
+        .take(10)
+        .map(|pos| pos.0)
+        .collect::<Vec<_>>();
+    assert_eq!(&all[15..25], &window[..]);
+
+    let entities = query
+        .iter_entities(&world)
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+    let window = query
+        .iter_entities(&world)
+        .skip(25)
+        .take(10)
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+    assert_eq!(&entities[25..35], &window[..]);
+
+    let mut iter = query.iter_entities(&world);
+    assert_eq!(Some(entities[3]), iter.nth(3).map(|(e, _)| e));
+    assert_eq!(Some(entities[14]), iter.nth(10).map(|(e, _)| e));
+    assert!(iter.nth(30).is_none());
+
+    let mut iter = query.iter(&world);
+    assert_eq!(Some(all[3]), iter.nth(3).map(|pos| pos.0));
+    assert_eq!(Some(all[14]), iter.nth(10).map(|pos| pos.0));
+    assert!(iter.nth(30).is_none());
+}
+
+#[test]
+fn query_project() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert(
+        (),
+        (0..5).map(|i| (Pos(i as f32, 0., 0.), Vel(0., 0., 0.), Scale(1., 1., 1.))),
+    );
+    world.insert(
+        (),
+        (0..5).map(|i| (Pos(i as f32, 0., 0.), Vel(0., 0., 0.), Rot(0., 0., 0.))),
+    );
+    world.insert((), (0..5).map(|i| (Pos(i as f32, 0., 0.), Vel(0., 0., 0.))));
+
+    let query = <(Read<Pos>, Read<Vel>, Read<Scale>)>::query().filter(!component::<Rot>());
+    let projected = query.project::<(Read<Pos>, Read<Vel>)>();
+
+    let expected = query
+        .iter_entities(&world)
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+    let matched = projected
+        .iter_entities(&world)
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+
+    assert_eq!(5, matched.len());
+    assert_eq!(expected, matched);
+}
+
+#[test]
+#[should_panic(expected = "not accessible via this query")]
+fn query_project_write_from_read() {
+    let query = <(Read<Pos>, Read<Vel>)>::query();
+    let _ = query.project::<Write<Pos>>();
+}
+
+#[test]
+fn query_chunk_metadata() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert((), (0..10).map(|i| (Pos(i as f32, 0., 0.),)));
+
+    let read = Read::<Pos>::query();
+    for chunk in read.iter_chunks(&world) {
+        assert_eq!(None, chunk.metadata::<f32>());
+        let max = chunk
+            .components::<Pos>()
+            .unwrap()
+            .iter()
+            .fold(0f32, |max, pos| max.max(pos.0));
+        chunk.set_metadata(max);
+    }
+
+    // reading does not invalidate the cached data
+    assert_eq!(10, read.iter(&world).count());
+    for chunk in read.iter_chunks(&world) {
+        assert_eq!(Some(9.), chunk.metadata::<f32>());
+        assert_eq!(None, chunk.metadata::<u32>());
+    }
+
+    for mut pos in Write::<Pos>::query().iter_mut(&mut world) {
+        pos.0 += 1.;
+    }
+
+    for chunk in read.iter_chunks(&world) {
+        assert_eq!(None, chunk.metadata::<f32>());
+    }
+}
+
+#[test]
+fn query_entities_in_range() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .insert((), (0..20).map(|i| (Pos(i as f32, 0., 0.),)))
+        .to_vec();
+    let lo = entities[5].index();
+    let hi = entities[15].index();
+
+    let query = Read::<Pos>::query();
+    let mut shard = query
+        .iter_entities_in_range(&world, lo..hi)
+        .map(|(e, pos)| {
+            assert!(e.index() >= lo && e.index() < hi);
+            pos.0 as usize
+        })
+        .collect::<Vec<_>>();
+    shard.sort();
+    assert_eq!((5..15).collect::<Vec<_>>(), shard);
+
+    for (_, mut pos) in Write::<Pos>::query().iter_entities_in_range_mut(&mut world, hi..u32::MAX) {
+        pos.1 = 1.;
+    }
+
+    for (i, entity) in entities.iter().enumerate() {
+        let expected = if i >= 15 { 1. } else { 0. };
+        assert_eq!(expected, world.get_component::<Pos>(*entity).unwrap().1);
+    }
+}
+
+#[test]
+fn query_component_bytes() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert((), (0..10).map(|i| (i as f32, i as u64)));
+
+    let query = <(Read<f32>, Read<u64>)>::query();
+    for chunk in query.iter_chunks(&world) {
+        let typed = chunk.components::<f32>().unwrap();
+        let bytes = unsafe { chunk.component_bytes(ComponentTypeId::of::<f32>()) }.unwrap();
+        let (bytes, stride) = *bytes;
+        assert_eq!(std::mem::size_of::<f32>(), stride);
+        assert_eq!(bytemuck::cast_slice::<f32, u8>(&typed), bytes);
+
+        let typed = chunk.components::<u64>().unwrap();
+        let bytes = unsafe { chunk.component_bytes(ComponentTypeId::of::<u64>()) }.unwrap();
+        assert_eq!(8, bytes.1);
+        assert_eq!(bytemuck::cast_slice::<u64, u8>(&typed), bytes.0);
+    }
+}
+
+#[test]
+fn query_pairwise() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert((Static,), (0..4).map(|i| (Pos(i as f32, 0., 0.),)));
+    world.insert((Model(0),), vec![(Pos(10., 0., 0.),)]);
+    world.insert((Model(1),), (0..2).map(|i| (Pos(20. + i as f32, 0., 0.),)));
+
+    let query = Read::<Pos>::query();
+    let mut pairs = query
+        .pairwise(&world)
+        .map(|(a, b)| (a.0, b.0))
+        .collect::<Vec<_>>();
+    pairs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    // pairs do not cross chunk boundaries, and single-entity chunks yield no pairs
+    assert_eq!(vec![(0., 1.), (1., 2.), (2., 3.), (20., 21.)], pairs);
+}
+
+#[test]
+fn query_iter_with_archetype() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let a = world.insert((), (0..3).map(|i| (Pos(i as f32, 0., 0.),)))[0];
+    let b = world.insert(
+        (),
+        (0..2).map(|i| (Pos(10. + i as f32, 0., 0.), Rot(0., 0., 0.))),
+    )[0];
+
+    let archetype_of = |entity| {
+        let location = world.get_entity_location(entity).unwrap();
+        world.storage().archetypes()[*location.archetype()].id()
+    };
+    let archetype_a = archetype_of(a);
+    let archetype_b = archetype_of(b);
+    assert_ne!(archetype_a, archetype_b);
+
+    let query = Read::<Pos>::query();
+    let mut count = 0;
+    for (archetype, pos) in query.iter_with_archetype(&world) {
+        let expected = if pos.0 < 10. {
+            archetype_a
+        } else {
+            archetype_b
+        };
+        assert_eq!(expected, archetype);
+        count += 1;
+    }
+    assert_eq!(5, count);
+
+    let query = Write::<Pos>::query();
+    for (archetype, mut pos) in query.iter_with_archetype_mut(&mut world) {
+        if archetype == archetype_b {
+            pos.1 = 1.;
+        }
+    }
+    assert_eq!(Pos(0., 0., 0.), *world.get_component::<Pos>(a).unwrap());
+    assert_eq!(Pos(10., 1., 0.), *world.get_component::<Pos>(b).unwrap());
+}
+
+#[test]
+fn query_collect_sorted_by_key() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    // spread depths across several chunks and archetypes, out of order
+    world.insert_with_chunk_limit(
+        (),
+        (0..30).map(|i| (Pos(0., 0., ((i * 7) % 30) as f32),)),
+        8,
+    );
+    world.insert(
+        (Static,),
+        (0..10).map(|i| (Pos(0., 0., i as f32 + 0.5), Rot(0., 0., 0.))),
+    );
+
+    let query = Read::<Pos>::query();
+    assert!(query.iter_chunks(&world).count() > 2);
+
+    let sorted = query.collect_sorted_by_key(&world, |pos| (pos.2 * 10.) as i32);
+    assert_eq!(40, sorted.len());
+    assert!(sorted.windows(2).all(|pair| pair[0].1 .2 <= pair[1].1 .2));
+    for (entity, pos) in &sorted {
+        assert_eq!(**pos, *world.get_component::<Pos>(*entity).unwrap());
+    }
+}
+
+#[test]
+fn query_for_each_with_index() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert_with_chunk_limit((), (0..250).map(|i| (Pos(i as f32, 0., 0.),)), 100);
+    world.insert((Static,), (0..100).map(|i| (Pos(i as f32, 0., 0.),)));
+
+    let query = Read::<Pos>::query();
+    let chunk_lengths = query
+        .iter_chunks(&world)
+        .map(|chunk| chunk.entities().len())
+        .collect::<Vec<_>>();
+    assert!(chunk_lengths.len() > 2);
+
+    let visited = std::sync::Mutex::new(Vec::new());
+    query.for_each_with_index(&world, |(entity, index, _)| {
+        visited.lock().unwrap().push((entity, index));
+    });
+    let visited = visited.into_inner().unwrap();
+
+    let mut expected = Vec::new();
+    for len in chunk_lengths {
+        expected.extend(0..len);
+    }
+    assert_eq!(
+        expected,
+        visited.iter().map(|(_, index)| *index).collect::<Vec<_>>()
+    );
+    for (entity, index) in visited {
+        let location = world.get_entity_location(entity).unwrap();
+        assert_eq!(index, *location.component());
+    }
+
+    Write::<Pos>::query().for_each_with_index_mut(&mut world, |(_, index, mut pos)| {
+        pos.1 = index as f32;
+    });
+    for (entity, pos) in query.iter_entities(&world) {
+        let location = world.get_entity_location(entity).unwrap();
+        assert_eq!(*location.component() as f32, pos.1);
+    }
+}
+
+#[test]
+fn query_join_on() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Parent(Entity);
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let parents = world
+        .insert((), (0..3).map(|i| (Pos(i as f32, 0., 0.),)))
+        .to_vec();
+    let no_pos = world.insert((), vec![(Rot(0., 0., 0.),)])[0];
+
+    world.insert(
+        (),
+        (0..6).map(|i| (Pos(0., i as f32, 0.), Parent(parents[i % 3]))),
+    );
+    world.insert((), vec![(Pos(0., 6., 0.), Parent(no_pos))]);
+    world.delete(parents[2]);
+
+    let query = <(Read<Pos>, Read<Parent>)>::query();
+    let mut pairs = query
+        .join_on::<_, Parent, Read<Pos>, _>(&world, |parent| parent.0)
+        .map(|((child, _), parent)| (child.1 as usize, parent.0 as usize))
+        .collect::<Vec<_>>();
+    pairs.sort();
+
+    assert_eq!(vec![(0, 0), (1, 1), (3, 0), (4, 1)], pairs);
+}
+
+#[test]
+fn query_join_on_without_access() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Parent(Entity);
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let parent = world.insert((), vec![(Pos(1., 0., 0.),)])[0];
+    world.insert((), vec![(Rot(0., 0., 0.), Parent(parent))]);
+
+    // the sub-world can not read the parent's `Pos`, so nothing is joined
+    let (left, _) = world.split::<Read<Parent>>();
+    let query = Read::<Parent>::query();
+    assert_eq!(1, query.iter(&left).count());
+    assert_eq!(
+        0,
+        query
+            .join_on::<_, Parent, Read<Pos>, _>(&left, |parent| parent.0)
+            .count()
+    );
+}
+
+#[test]
+fn query_or_filter() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert((), vec![(Pos(0., 0., 0.),)]);
+    world.insert((), (0..2).map(|_| (Vel(0., 0., 0.),)));
+    world.insert((), (0..3).map(|_| (Pos(0., 0., 0.), Vel(0., 0., 0.))));
+    world.insert((), (0..4).map(|_| (Rot(0., 0., 0.),)));
+
+    let query = TryRead::<Pos>::query().filter(component::<Pos>() | component::<Vel>());
+    let mut lens = query
+        .iter_chunks(&world)
+        .map(|chunk| chunk.entities().len())
+        .collect::<Vec<_>>();
+    lens.sort();
+    assert_eq!(vec![1, 2, 3], lens);
+
+    let or = Or {
+        filters: (
+            component::<Pos>().arch_filter,
+            component::<Vel>().arch_filter,
+        ),
+    };
+    let query =
+        TryRead::<Pos>::query().filter(EntityFilterTuple::new(or, Passthrough, Passthrough));
+    let mut lens = query
+        .iter_chunks(&world)
+        .map(|chunk| chunk.entities().len())
+        .collect::<Vec<_>>();
+    lens.sort();
+    assert_eq!(vec![1, 2, 3], lens);
+}
+
+#[test]
+fn query_chain() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Player;
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Enemy(u32);
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert((), (0..2).map(|i| (Pos(i as f32, 0., 0.), Player)));
+    world.insert(
+        (),
+        (0..3).map(|i| (Pos(0., i as f32, 0.), Vel(0., 0., 0.), Enemy(i))),
+    );
+    world.insert((), vec![(Pos(0., 0., 0.),)]);
+
+    let players = Read::<Pos>::query().filter(component::<Player>());
+    let enemies = Read::<Pos>::query().filter(component::<Enemy>());
+
+    let player_count = players.iter(&world).count();
+    let enemy_count = enemies.iter(&world).count();
+    assert_eq!(2, player_count);
+    assert_eq!(3, enemy_count);
+
+    let positions = players
+        .chain(&enemies, &world)
+        .map(|pos| *pos)
+        .collect::<Vec<_>>();
+    assert_eq!(player_count + enemy_count, positions.len());
+    assert_eq!(
+        vec![
+            Pos(0., 0., 0.),
+            Pos(1., 0., 0.),
+            Pos(0., 0., 0.),
+            Pos(0., 1., 0.),
+            Pos(0., 2., 0.),
+        ],
+        positions
+    );
+}
+
+#[test]
+fn query_count() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert((), (0..3).map(|_| (Pos(0., 0., 0.),)));
+    world.insert(
+        (Static,),
+        (0..4).map(|_| (Pos(0., 0., 0.), Vel(0., 0., 0.))),
+    );
+    world.insert((), (0..5).map(|_| (Vel(0., 0., 0.),)));
+    world.insert((Model(1),), (0..6).map(|_| (Pos(0., 0., 0.),)));
+
+    assert_eq!(13, Read::<Pos>::query().count(&world));
+    assert_eq!(
+        Read::<Pos>::query().iter(&world).count(),
+        Read::<Pos>::query().count(&world)
+    );
+    assert_eq!(9, Write::<Vel>::query().count(&world));
+    assert_eq!(4, <(Write<Pos>, Read<Vel>)>::query().count(&world));
+    assert_eq!(
+        9,
+        Read::<Pos>::query().filter(!tag::<Static>()).count(&world)
+    );
+    assert_eq!(0, Read::<Accel>::query().count(&world));
+}
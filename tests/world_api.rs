@@ -1,7 +1,13 @@
 use legion::prelude::*;
+use legion::storage::ArchetypeDescription;
 use legion::storage::ComponentTypeId;
 use legion::storage::TagTypeId;
+use legion::world::ComponentVisitor;
+use legion::world::EntityMutationError;
+use std::any::Any;
+use std::collections::HashMap;
 use std::collections::HashSet;
+use std::sync::Mutex;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 struct Pos(f32, f32, f32);
@@ -32,6 +38,33 @@ fn insert() {
     assert_eq!(2, entities.len());
 }
 
+#[test]
+fn spawn_n() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .spawn_n((Static,), 1000, |i| {
+            (Pos(i as f32, 0., 0.), Vel(0., i as f32 * 2., 0.))
+        })
+        .to_vec();
+
+    assert_eq!(1000, entities.len());
+    for (i, entity) in entities.iter().enumerate() {
+        assert_eq!(
+            Pos(i as f32, 0., 0.),
+            *world.get_component::<Pos>(*entity).unwrap()
+        );
+        assert_eq!(
+            Vel(0., i as f32 * 2., 0.),
+            *world.get_component::<Vel>(*entity).unwrap()
+        );
+        assert_eq!(Some(&Static), world.get_tag::<Static>(*entity));
+    }
+}
+
 #[test]
 fn get_component() {
     let _ = tracing_subscriber::fmt::try_init();
@@ -110,6 +143,327 @@ fn get_shared_wrong_type() {
     assert!(world.get_tag::<Model>(entity).is_none());
 }
 
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "not equal to itself")]
+fn insert_nan_tag() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    struct Radius(f32);
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert((Radius(1.0),), vec![(Pos(1., 2., 3.),)]);
+    world.insert((Radius(f32::NAN),), vec![(Pos(4., 5., 6.),)]);
+}
+
+#[test]
+fn describe_entity() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.register_component_debug::<Pos>();
+    world.register_tag_debug::<Model>();
+
+    let entity = world.insert(
+        (Model(5), Static),
+        vec![(Pos(1., 2., 3.), Rot(0.5, 0.5, 0.5))],
+    )[0];
+
+    let description = world.describe_entity(entity).unwrap();
+    assert!(description.contains("Pos(1.0, 2.0, 3.0)"));
+    assert!(description.contains("Model(5)"));
+    assert!(description.contains("Rot"));
+    assert!(!description.contains("Rot(0.5"));
+    assert!(description.contains("Static"));
+
+    world.delete(entity);
+    assert!(world.describe_entity(entity).is_none());
+}
+
+#[test]
+fn len() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    assert!(world.is_empty());
+
+    let entities = world
+        .insert((), (0..10).map(|i| (Pos(i as f32, 0., 0.),)))
+        .to_vec();
+    assert_eq!(10, world.len());
+
+    for entity in &entities[..3] {
+        assert!(world.delete(*entity));
+    }
+    assert!(!world.delete(entities[0]));
+    assert_eq!(7, world.len());
+    assert!(!world.is_empty());
+
+    world.delete_all();
+    assert_eq!(0, world.len());
+    assert!(world.is_empty());
+}
+
+#[test]
+fn iter_entities_and_contains() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let mut live = world
+        .insert((), (0..5).map(|i| (Pos(i as f32, 0., 0.),)))
+        .iter()
+        .copied()
+        .collect::<HashSet<_>>();
+    live.extend(world.insert((Static,), (0..5).map(|_| (Rot(0., 0., 0.),))));
+
+    let removed = live.iter().copied().step_by(3).collect::<Vec<_>>();
+    for entity in &removed {
+        world.delete(*entity);
+        live.remove(entity);
+    }
+    live.extend(world.insert((), vec![(Pos(0., 0., 0.), Rot(0., 0., 0.))]));
+
+    let iterated = world.iter_entities().collect::<Vec<_>>();
+    assert_eq!(live.len(), iterated.len());
+    assert_eq!(live, iterated.into_iter().collect::<HashSet<_>>());
+    assert!(live.iter().all(|entity| world.contains(*entity)));
+    assert!(removed.iter().all(|entity| !world.contains(*entity)));
+
+    // entities allocated by a command buffer are alive, but not yet stored in the world
+    let mut cmd = CommandBuffer::new(&world);
+    let pending = cmd.insert((), vec![(Pos(0., 0., 0.),)])[0];
+    assert!(world.is_alive(pending));
+    assert!(!world.contains(pending));
+    cmd.write(&mut world);
+    assert!(world.contains(pending));
+}
+
+#[test]
+fn export_column() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert((), (0..5).map(|i| (Pos(i as f32, 0., 0.),)));
+    world.insert(
+        (),
+        (0..5).map(|i| (Pos(10. + i as f32, 0., 0.), Rot(0., 0., 0.))),
+    );
+    world.insert((), (0..3).map(|_| (Rot(0., 0., 0.),)));
+
+    let mut entities = Vec::new();
+    let mut column = Vec::new();
+    for (chunk_entities, components) in world.export_column::<Pos>() {
+        assert_eq!(chunk_entities.len(), components.len());
+        entities.extend_from_slice(chunk_entities);
+        column.extend_from_slice(&components);
+    }
+
+    let expected = Read::<Pos>::query()
+        .iter_entities(&world)
+        .map(|(entity, pos)| (entity, *pos))
+        .collect::<Vec<_>>();
+    assert_eq!(10, expected.len());
+    assert_eq!(
+        expected,
+        entities.into_iter().zip(column).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn clear() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .insert((), (0..10).map(|i| (Pos(i as f32, 0., 0.),)))
+        .to_vec();
+    world.insert(
+        (Static,),
+        (0..5).map(|_| (Pos(0., 0., 0.), Rot(0., 0., 0.))),
+    );
+
+    world.clear();
+    assert_eq!(0, world.len());
+    assert!(world.storage().archetypes().is_empty());
+    assert!(entities.iter().all(|entity| !world.is_alive(*entity)));
+
+    let inserted = world
+        .insert((), (0..3).map(|i| (Pos(i as f32, 0., 0.),)))
+        .to_vec();
+    assert_eq!(3, world.len());
+    assert_eq!(1, world.storage().archetypes().len());
+    for (i, entity) in inserted.iter().enumerate() {
+        assert_eq!(
+            Pos(i as f32, 0., 0.),
+            *world.get_component::<Pos>(*entity).unwrap()
+        );
+    }
+    assert_eq!(3, Read::<Pos>::query().iter(&world).count());
+}
+
+#[test]
+fn reserve_archetype() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+
+    let mut moving = ArchetypeDescription::default();
+    moving.register_component::<Pos>();
+    moving.register_component::<Vel>();
+    let mut fixed = ArchetypeDescription::default();
+    fixed.register_tag::<Static>();
+    fixed.register_component::<Pos>();
+
+    let mut a = universe.create_world();
+    let mut b = universe.create_world();
+    let reserved_a = [
+        a.reserve_archetype(moving.clone()),
+        a.reserve_archetype(fixed.clone()),
+    ];
+    let reserved_b = [
+        b.reserve_archetype(moving.clone()),
+        b.reserve_archetype(fixed.clone()),
+    ];
+    assert_eq!(
+        reserved_a.iter().map(|id| id.index()).collect::<Vec<_>>(),
+        reserved_b.iter().map(|id| id.index()).collect::<Vec<_>>()
+    );
+    assert_eq!(reserved_a[0], a.reserve_archetype(moving.clone()));
+
+    let a_moving = a.insert((), vec![(Pos(0., 0., 0.), Vel(0., 0., 0.))])[0];
+    let a_fixed = a.insert((Static,), vec![(Pos(0., 0., 0.),)])[0];
+    let b_fixed = b.insert((Static,), vec![(Pos(0., 0., 0.),)])[0];
+    let b_moving = b.insert((), vec![(Vel(0., 0., 0.), Pos(0., 0., 0.))])[0];
+
+    let archetype = |world: &World, entity| world.get_entity_location(entity).unwrap().archetype();
+    assert_eq!(reserved_a[0].index(), archetype(&a, a_moving));
+    assert_eq!(reserved_a[1].index(), archetype(&a, a_fixed));
+    assert_eq!(reserved_b[0].index(), archetype(&b, b_moving));
+    assert_eq!(reserved_b[1].index(), archetype(&b, b_fixed));
+    assert_eq!(2, a.storage().archetypes().len());
+    assert_eq!(2, b.storage().archetypes().len());
+}
+
+#[test]
+fn gc_archetypes() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let before = world
+        .insert((), (0..3).map(|i| (Pos(i as f32, 0., 0.),)))
+        .to_vec();
+    let transient = world
+        .insert((), (0..4).map(|_| (Pos(0., 0., 0.), Accel(0., 0., 0.))))
+        .to_vec();
+    let after = world
+        .insert(
+            (Static,),
+            (0..5).map(|i| (Pos(0., i as f32, 0.), Rot(0., 0., 0.))),
+        )
+        .to_vec();
+    assert_eq!(3, world.storage().archetypes().len());
+
+    assert_eq!(0, world.gc_archetypes());
+
+    for entity in &transient {
+        world.delete(*entity);
+    }
+    assert_eq!(1, world.gc_archetypes());
+    assert_eq!(2, world.storage().archetypes().len());
+    assert_eq!(0, Read::<Accel>::query().iter(&world).count());
+
+    // the archetype after the removed one has moved down to fill its index
+    for (i, archetype) in world.storage().archetypes().iter().enumerate() {
+        assert_eq!(i, *archetype.id().index());
+        for chunk in archetype.chunksets().iter().flat_map(|set| set.occupied()) {
+            assert_eq!(archetype.id(), chunk.id().archetype_id());
+        }
+    }
+
+    for (i, entity) in before.iter().enumerate() {
+        assert_eq!(
+            Pos(i as f32, 0., 0.),
+            *world.get_component::<Pos>(*entity).unwrap()
+        );
+    }
+    for (i, entity) in after.iter().enumerate() {
+        assert_eq!(
+            Pos(0., i as f32, 0.),
+            *world.get_component::<Pos>(*entity).unwrap()
+        );
+        assert_eq!(Some(&Static), world.get_tag::<Static>(*entity));
+        assert_eq!(1, *world.get_entity_location(*entity).unwrap().archetype());
+    }
+    assert_eq!(8, Read::<Pos>::query().iter(&world).count());
+    assert_eq!(5, Read::<Rot>::query().iter(&world).count());
+
+    // entities can be moved between the remaining archetypes after collection
+    world.add_component(after[0], Accel(1., 1., 1.)).unwrap();
+    world.delete(before[1]);
+    assert_eq!(
+        Pos(0., 0., 0.),
+        *world.get_component::<Pos>(after[0]).unwrap()
+    );
+    assert_eq!(7, Read::<Pos>::query().iter(&world).count());
+}
+
+#[test]
+fn visit_entity() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    #[derive(Default)]
+    struct Counter {
+        visited: HashMap<&'static str, usize>,
+        unregistered: Vec<&'static str>,
+        pos: Option<Pos>,
+    }
+
+    impl ComponentVisitor for Counter {
+        fn visit(&mut self, name: &'static str, value: &dyn Any) {
+            *self.visited.entry(name).or_insert(0) += 1;
+            if let Some(pos) = value.downcast_ref::<Pos>() {
+                self.pos = Some(*pos);
+            }
+        }
+
+        fn visit_unregistered(&mut self, name: &'static str) { self.unregistered.push(name); }
+    }
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.register_visit::<Pos>();
+    world.register_visit::<Rot>();
+
+    let entity = world.insert(
+        (Static,),
+        vec![(Pos(1., 2., 3.), Rot(0.5, 0.5, 0.5), Vel(0., 0., 0.))],
+    )[0];
+
+    let mut counter = Counter::default();
+    assert!(world.visit_entity(entity, &mut counter));
+    assert_eq!(2, counter.visited.len());
+    assert!(counter.visited.values().all(|count| *count == 1));
+    assert_eq!(Some(Pos(1., 2., 3.)), counter.pos);
+    assert_eq!(1, counter.unregistered.len());
+    assert!(counter.unregistered[0].ends_with("Vel"));
+
+    world.delete(entity);
+    assert!(!world.visit_entity(entity, &mut Counter::default()));
+}
+
 #[test]
 fn delete() {
     let _ = tracing_subscriber::fmt::try_init();
@@ -138,6 +492,545 @@ fn delete() {
     }
 }
 
+#[test]
+fn delete_twice() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .insert((), (0..3).map(|i| (Pos(i as f32, 0., 0.),)))
+        .to_vec();
+
+    assert!(world.delete(entities[0]));
+    assert!(!world.delete(entities[0]));
+
+    // the deleted entity's id may be reused without being affected by stale deletes
+    let replacement = world.insert((), vec![(Pos(3., 0., 0.),)])[0];
+    assert!(!world.delete(entities[0]));
+    assert!(world.is_alive(replacement));
+
+    let query = Read::<Pos>::query();
+    let mut remaining = query
+        .iter_entities(&world)
+        .map(|(e, pos)| {
+            assert_eq!(Some(*pos), world.get_component::<Pos>(e).map(|p| *p));
+            pos.0 as usize
+        })
+        .collect::<Vec<_>>();
+    remaining.sort();
+    assert_eq!(vec![1, 2, 3], remaining);
+}
+
+#[test]
+fn are_alive() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .insert((), (0..3).map(|i| (Pos(i as f32, 0., 0.),)))
+        .to_vec();
+    world.delete(entities[1]);
+
+    // an entity allocated by another world of the same universe was never allocated here
+    let mut other = universe.create_world();
+    let foreign = other.insert((), vec![(Pos(0., 0., 0.),)])[0];
+
+    let query = vec![entities[0], entities[1], foreign, entities[2], entities[1]];
+    assert_eq!(
+        vec![true, false, false, true, false],
+        world.are_alive(&query)
+    );
+    assert_eq!(vec![entities[0], entities[2]], world.retain_alive(&query));
+    assert!(world.are_alive(&[]).is_empty());
+}
+
+#[test]
+fn despawn_deferred() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .insert((), (0..10).map(|i| (Pos(i as f32, 0., 0.),)))
+        .to_vec();
+
+    let query = Read::<Pos>::query();
+    let mut visited = 0;
+    for (entity, pos) in query.iter_entities(&world) {
+        visited += 1;
+        if pos.0 < 5. {
+            world.despawn_deferred(entity);
+            world.despawn_deferred(entity);
+        }
+    }
+
+    // all entities remain alive until the flush
+    assert_eq!(10, visited);
+    assert!(world.are_alive(&entities).into_iter().all(|alive| alive));
+
+    assert_eq!(5, world.flush_despawns());
+    for (i, entity) in entities.iter().enumerate() {
+        assert_eq!(i >= 5, world.is_alive(*entity));
+    }
+
+    let mut remaining = query
+        .iter(&world)
+        .map(|pos| pos.0 as usize)
+        .collect::<Vec<_>>();
+    remaining.sort();
+    assert_eq!(vec![5, 6, 7, 8, 9], remaining);
+
+    // the pending set is cleared by the flush
+    assert_eq!(0, world.flush_despawns());
+}
+
+#[test]
+fn sparse_components() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Colliding(usize);
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let migrations = std::sync::Arc::new(Mutex::new(0));
+    let counter = migrations.clone();
+    world.on_migrate(move |_, _, _| *counter.lock().unwrap() += 1);
+
+    let entities = world
+        .insert((), (0..3).map(|i| (Pos(i as f32, 0., 0.),)))
+        .to_vec();
+    let locations = entities
+        .iter()
+        .map(|e| world.get_entity_location(*e).unwrap())
+        .collect::<Vec<_>>();
+
+    for frame in 0..10 {
+        world
+            .sparse_insert(entities[frame % 3], Colliding(frame))
+            .unwrap();
+        assert_eq!(
+            Some(&Colliding(frame)),
+            world.sparse_get::<Colliding>(entities[frame % 3])
+        );
+        assert_eq!(1, world.sparse_iter::<Colliding>().count());
+        assert_eq!(
+            Some(Colliding(frame)),
+            world.sparse_remove::<Colliding>(entities[frame % 3])
+        );
+        assert_eq!(None, world.sparse_get::<Colliding>(entities[frame % 3]));
+    }
+
+    // no entity ever moved between archetypes
+    assert_eq!(0, *migrations.lock().unwrap());
+    for (entity, location) in entities.iter().zip(locations) {
+        assert_eq!(Some(location), world.get_entity_location(*entity));
+    }
+
+    // sparse components are dropped along with their entity
+    world.sparse_insert(entities[0], Colliding(0)).unwrap();
+    world.sparse_get_mut::<Colliding>(entities[0]).unwrap().0 = 5;
+    assert_eq!(
+        vec![(entities[0], Colliding(5))],
+        world
+            .sparse_iter::<Colliding>()
+            .map(|(e, c)| (e, *c))
+            .collect::<Vec<_>>()
+    );
+    world.delete(entities[0]);
+    assert_eq!(None, world.sparse_get::<Colliding>(entities[0]));
+    assert!(world.sparse_insert(entities[0], Colliding(0)).is_err());
+}
+
+#[test]
+fn recently_spawned() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    assert!(world.recently_spawned().is_empty());
+
+    // by default only the latest insert is retained
+    world.insert((), (0..3).map(|i| (Pos(i as f32, 0., 0.),)));
+    let latest = world
+        .insert((), (0..2).map(|i| (Rot(i as f32, 0., 0.),)))
+        .to_vec();
+    assert_eq!(&latest[..], world.recently_spawned());
+
+    // replacing components does not spawn new entities
+    world.replace_components(latest[0], (), (Scale(0., 0., 0.),));
+    assert_eq!(&latest[..], world.recently_spawned());
+
+    // when accumulating, every insert is retained until cleared
+    world.clear_recently_spawned();
+    world.set_accumulate_spawned(true);
+    let mut spawned = world.insert((), vec![(Pos(0., 0., 0.),)]).to_vec();
+    spawned.extend_from_slice(world.insert((Static,), vec![(Rot(0., 0., 0.),)]));
+    assert_eq!(&spawned[..], world.recently_spawned());
+
+    world.clear_recently_spawned();
+    assert!(world.recently_spawned().is_empty());
+}
+
+#[test]
+fn archetype_histogram() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    world.insert((), (0..3).map(|_| (Pos(0., 0., 0.),)));
+    world.insert((), (0..5).map(|_| (Pos(0., 0., 0.), Rot(0., 0., 0.))));
+    world.insert(
+        (),
+        (0..2).map(|_| (Rot(0., 0., 0.), Pos(0., 0., 0.), Vel(0., 0., 0.))),
+    );
+
+    // archetypes which differ only by tags are grouped together
+    world.insert((Static,), (0..4).map(|_| (Pos(0., 0., 0.),)));
+
+    let sorted = |mut types: Vec<ComponentTypeId>| {
+        types.sort();
+        types
+    };
+    let pos = ComponentTypeId::of::<Pos>();
+    let rot = ComponentTypeId::of::<Rot>();
+    let vel = ComponentTypeId::of::<Vel>();
+
+    assert_eq!(
+        vec![
+            (vec![pos], 7),
+            (sorted(vec![pos, rot]), 5),
+            (sorted(vec![pos, rot, vel]), 2),
+        ],
+        world.archetype_histogram()
+    );
+}
+
+#[test]
+fn get_or_spawn_singleton() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    // entities with a superset of the layout are not singletons
+    world.insert((), vec![(Pos(0., 0., 0.), Rot(0., 0., 0.))]);
+
+    let first = world.get_or_spawn_singleton((Model(1),), || (Pos(1., 1., 1.),));
+    let second = world.get_or_spawn_singleton((Model(1),), || -> (Pos,) {
+        panic!("singleton spawned twice")
+    });
+    assert_eq!(first, second);
+    assert_eq!(Pos(1., 1., 1.), *world.get_component::<Pos>(first).unwrap());
+
+    // a different tag value is a different singleton
+    let other = world.get_or_spawn_singleton((Model(2),), || (Pos(2., 2., 2.),));
+    assert_ne!(first, other);
+
+    let query = Read::<Pos>::query().filter(!component::<Rot>());
+    assert_eq!(2, query.iter(&world).count());
+}
+
+#[test]
+fn clone_entity() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Name(String);
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.register_clone::<Pos>();
+    world.register_clone::<Name>();
+
+    let entities = world
+        .insert(
+            (Model(3),),
+            (0..3).map(|i| (Pos(i as f32, 0., 0.), Name(format!("entity {}", i)))),
+        )
+        .to_vec();
+
+    let clone = world.clone_entity(entities[1]).unwrap();
+    assert_ne!(entities[1], clone);
+    assert!(!entities.contains(&clone));
+    assert_eq!(Pos(1., 0., 0.), *world.get_component::<Pos>(clone).unwrap());
+    assert_eq!(
+        Name("entity 1".to_string()),
+        *world.get_component::<Name>(clone).unwrap()
+    );
+    assert_eq!(Some(&Model(3)), world.get_tag::<Model>(clone));
+
+    // the clone is independent of the original
+    world.get_component_mut::<Name>(clone).unwrap().0 = "clone".to_string();
+    assert_eq!(
+        "entity 1",
+        world.get_component::<Name>(entities[1]).unwrap().0
+    );
+
+    world.delete(entities[0]);
+    assert_eq!(None, world.clone_entity(entities[0]));
+    assert_eq!(3, Read::<Name>::query().iter(&world).count());
+}
+
+#[test]
+fn clone_entity_panic() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Debug)]
+    struct Counted(Arc<AtomicUsize>);
+
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Counted(self.0.clone())
+        }
+    }
+
+    impl Drop for Counted {
+        fn drop(&mut self) { self.0.fetch_sub(1, Ordering::SeqCst); }
+    }
+
+    #[derive(Debug)]
+    struct Bomb;
+
+    impl Clone for Bomb {
+        fn clone(&self) -> Self { panic!("bomb cloned") }
+    }
+
+    let live = Arc::new(AtomicUsize::new(1));
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.register_clone::<Pos>();
+    world.register_clone::<Counted>();
+    world.register_clone::<Bomb>();
+
+    let entity = world.insert((), vec![(Pos(1., 2., 3.), Counted(live.clone()), Bomb)])[0];
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        world.clone_entity(entity);
+    }));
+    assert!(result.is_err());
+
+    // any partially written clone has been removed and dropped
+    assert_eq!(1, live.load(Ordering::SeqCst));
+    let query = <(Read<Pos>, Read<Counted>, Read<Bomb>)>::query();
+    for chunk in query.iter_chunks(&world) {
+        assert_eq!(vec![entity], chunk.entities().to_vec());
+        assert_eq!(1, chunk.components::<Pos>().unwrap().len());
+        assert_eq!(1, chunk.components::<Counted>().unwrap().len());
+        assert_eq!(1, chunk.components::<Bomb>().unwrap().len());
+    }
+    assert_eq!(1, query.iter(&world).count());
+    assert_eq!(
+        Pos(1., 2., 3.),
+        *world.get_component::<Pos>(entity).unwrap()
+    );
+
+    drop(world);
+    assert_eq!(0, live.load(Ordering::SeqCst));
+}
+
+#[test]
+fn component_drop_hook() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Debug)]
+    struct Handle(usize, Arc<AtomicUsize>);
+
+    impl Drop for Handle {
+        fn drop(&mut self) { self.1.fetch_add(1, Ordering::SeqCst); }
+    }
+
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let released = Arc::new(Mutex::new(Vec::new()));
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let released_clone = released.clone();
+    world.on_component_drop::<Handle, _>(move |handle| {
+        released_clone.lock().unwrap().push(handle.0);
+    });
+
+    let entities = world
+        .insert(
+            (),
+            (0..5).map(|i| (Handle(i, dropped.clone()), Pos(0., 0., 0.))),
+        )
+        .to_vec();
+
+    // migrations which keep the component do not release it
+    world.add_tag(entities[0], Static).unwrap();
+    world.add_component(entities[0], Vel(0., 0., 0.)).unwrap();
+    world.remove_component::<Pos>(entities[0]).unwrap();
+    assert!(released.lock().unwrap().is_empty());
+
+    world.delete(entities[0]);
+    world.remove_component::<Handle>(entities[1]).unwrap();
+    world
+        .add_component(entities[2], Handle(10, dropped.clone()))
+        .unwrap();
+    world.replace_components(entities[3], (), (Pos(1., 1., 1.),));
+    assert_eq!(vec![0, 1, 2, 3], *released.lock().unwrap());
+    assert_eq!(4, dropped.load(Ordering::SeqCst));
+
+    world.delete_all();
+    let mut released = released.lock().unwrap().clone();
+    released.sort();
+    assert_eq!(vec![0, 1, 2, 3, 4, 10], released);
+    assert_eq!(6, dropped.load(Ordering::SeqCst));
+}
+
+#[test]
+fn reserve_entity_and_populate() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Parent(Entity);
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    // reuse an index, so that the reserved entity could observe a stale location
+    let deleted = world.insert((), vec![(Pos(1., 1., 1.),)])[0];
+    world.delete(deleted);
+
+    let parent = world.reserve_entity();
+    let child = world.insert((), vec![(Vel(0., 0., 0.), Parent(parent))])[0];
+
+    assert!(world.is_alive(parent));
+    assert!(!world.contains(parent));
+    assert!(world.get_component::<Pos>(parent).is_none());
+    assert_eq!(0, Read::<Pos>::query().iter(&world).count());
+    assert!(matches!(
+        world.add_component(parent, Rot(0., 0., 0.)),
+        Err(EntityMutationError::DoesNotExist)
+    ));
+
+    assert!(world.populate(parent, (Model(1),), (Pos(1., 2., 3.),)));
+    assert!(!world.populate(parent, (), (Pos(0., 0., 0.),)));
+    assert!(world.contains(parent));
+
+    let query = <(Read<Pos>, Tagged<Model>)>::query();
+    let found = query
+        .iter_entities(&world)
+        .map(|(entity, (pos, model))| (entity, *pos, *model))
+        .collect::<Vec<_>>();
+    assert_eq!(vec![(parent, Pos(1., 2., 3.), Model(1))], found);
+    assert_eq!(
+        Parent(parent),
+        *world.get_component::<Parent>(child).unwrap()
+    );
+
+    // deleting a reserved entity which was never populated leaves the world untouched
+    let unused = world.reserve_entity();
+    assert!(world.delete(unused));
+    assert!(!world.is_alive(unused));
+    assert!(!world.populate(unused, (), (Pos(0., 0., 0.),)));
+    assert_eq!(2, world.len());
+}
+
+#[test]
+fn apply_component_patch() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let migrations = std::sync::Arc::new(Mutex::new(0));
+    let counter = migrations.clone();
+    world.on_migrate(move |_, _, _| *counter.lock().unwrap() += 1);
+
+    let entity = world.insert((), vec![(Pos(1., 2., 3.),)])[0];
+    let location = world.get_entity_location(entity).unwrap();
+
+    assert!(world.apply_component_patch(entity, Pos(4., 5., 6.)));
+    assert_eq!(
+        Pos(4., 5., 6.),
+        *world.get_component::<Pos>(entity).unwrap()
+    );
+    assert_eq!(Some(location), world.get_entity_location(entity));
+
+    // absent components are not added
+    assert!(!world.apply_component_patch(entity, Vel(0., 0., 0.)));
+    assert!(world.get_component::<Vel>(entity).is_none());
+
+    world.delete(entity);
+    assert!(!world.apply_component_patch(entity, Pos(0., 0., 0.)));
+
+    assert_eq!(0, *migrations.lock().unwrap());
+}
+
+#[test]
+fn on_migrate() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    type Migration = (Entity, HashSet<ComponentTypeId>, HashSet<ComponentTypeId>);
+    let migrations = std::sync::Arc::new(Mutex::new(Vec::<Migration>::new()));
+    let migrations_clone = migrations.clone();
+    world.on_migrate(move |entity, before, after| {
+        migrations_clone.lock().unwrap().push((
+            entity,
+            before.iter().copied().collect(),
+            after.iter().copied().collect(),
+        ));
+    });
+
+    let entity = world.insert((), vec![(Pos(1., 2., 3.),)])[0];
+    assert!(migrations.lock().unwrap().is_empty());
+
+    let pos = ComponentTypeId::of::<Pos>();
+    let vel = ComponentTypeId::of::<Vel>();
+    let rot = ComponentTypeId::of::<Rot>();
+
+    world.add_component(entity, Vel(0., 0., 0.)).unwrap();
+    world.remove_component::<Pos>(entity).unwrap();
+    world.replace_components(entity, (), (Rot(0., 0., 0.),));
+
+    // setting an existing component or replacing with the same layout does not migrate
+    world.add_component(entity, Rot(1., 1., 1.)).unwrap();
+    world.replace_components(entity, (), (Rot(0., 0., 0.),));
+
+    let migrations = migrations.lock().unwrap();
+    assert_eq!(
+        vec![
+            (
+                entity,
+                [pos].iter().copied().collect(),
+                [pos, vel].iter().copied().collect()
+            ),
+            (
+                entity,
+                [pos, vel].iter().copied().collect(),
+                [vel].iter().copied().collect()
+            ),
+            (
+                entity,
+                [vel].iter().copied().collect(),
+                [rot].iter().copied().collect()
+            ),
+        ],
+        *migrations
+    );
+}
+
 #[test]
 fn delete_all() {
     let _ = tracing_subscriber::fmt::try_init();
@@ -283,6 +1176,122 @@ fn move_from() {
     }
 }
 
+#[test]
+fn universe_entity_owner() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut persistent = universe.create_world();
+    let mut scene = universe.create_world();
+
+    let a = persistent.insert((), vec![(Pos(1., 2., 3.),)])[0];
+    let b = scene.insert((), vec![(Pos(4., 5., 6.),)])[0];
+
+    assert_eq!(Some(persistent.id()), universe.entity_owner(a));
+    assert_eq!(Some(scene.id()), universe.entity_owner(b));
+
+    // merged entities are owned by the destination world
+    persistent.move_from(scene);
+    assert_eq!(Some(persistent.id()), universe.entity_owner(b));
+    assert!(persistent.is_alive(b));
+}
+
+#[test]
+fn spawned_despawned_this_frame() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let before = world.insert((), vec![(Pos(1., 2., 3.),); 2]).to_vec();
+    world.enable_lifecycle_tracking();
+    assert_eq!(0, world.spawned_this_frame().count());
+
+    let spawned = world
+        .insert((Static,), vec![(Pos(4., 5., 6.),); 3])
+        .to_vec();
+    world.delete(before[0]);
+    world.delete(spawned[1]);
+
+    let expected_spawned = spawned.iter().copied().collect::<HashSet<_>>();
+    let expected_despawned = vec![before[0], spawned[1]]
+        .into_iter()
+        .collect::<HashSet<_>>();
+    assert_eq!(
+        expected_spawned,
+        world.spawned_this_frame().collect::<HashSet<_>>()
+    );
+    assert_eq!(
+        expected_despawned,
+        world.despawned_this_frame().collect::<HashSet<_>>()
+    );
+
+    world.end_frame();
+    assert_eq!(0, world.spawned_this_frame().count());
+    assert_eq!(0, world.despawned_this_frame().count());
+
+    world.delete_all();
+    let expected_despawned = vec![before[1], spawned[0], spawned[2]]
+        .into_iter()
+        .collect::<HashSet<_>>();
+    assert_eq!(
+        expected_despawned,
+        world.despawned_this_frame().collect::<HashSet<_>>()
+    );
+}
+
+#[test]
+fn insert_with_chunk_limit() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .insert_with_chunk_limit((), (0..100).map(|i| (Pos(i as f32, 0., 0.),)), 10)
+        .to_vec();
+    assert_eq!(100, entities.len());
+
+    let query = Read::<Pos>::query();
+    let sizes = query
+        .iter_chunks(&world)
+        .map(|chunk| chunk.entities().len())
+        .collect::<Vec<_>>();
+    assert_eq!(vec![10; 10], sizes);
+
+    for (i, entity) in entities.iter().enumerate() {
+        assert_eq!(
+            Pos(i as f32, 0., 0.),
+            *world.get_component::<Pos>(*entity).unwrap()
+        );
+    }
+}
+
+#[test]
+fn has_component_and_tag() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let a = world.insert((Static,), vec![(Pos(1., 2., 3.),)])[0];
+    let b = world.insert((Model(1),), vec![(Pos(1., 2., 3.), Rot(0., 0., 0.))])[0];
+
+    assert!(world.has_component::<Pos>(a));
+    assert!(!world.has_component::<Rot>(a));
+    assert!(world.has_component::<Pos>(b));
+    assert!(world.has_component::<Rot>(b));
+
+    assert!(world.has_tag::<Static>(a));
+    assert!(!world.has_tag::<Model>(a));
+    assert!(!world.has_tag::<Static>(b));
+    assert!(world.has_tag::<Model>(b));
+
+    world.delete(a);
+    assert!(!world.has_component::<Pos>(a));
+    assert!(!world.has_tag::<Static>(a));
+}
+
 #[test]
 fn mutate_add_component() {
     let _ = tracing_subscriber::fmt::try_init();
@@ -313,6 +1322,61 @@ fn mutate_add_component() {
     assert_eq!(1, query_with_scale.iter(&world).count());
 }
 
+#[test]
+fn mutate_add_component_promotes_entity() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entity = world.insert((), vec![(Pos(1., 2., 3.),)])[0];
+    let query = <(Read<Pos>, Read<Vel>)>::query();
+    assert_eq!(0, query.iter(&world).count());
+
+    world.add_component(entity, Vel(0.1, 0.2, 0.3)).unwrap();
+    let matched = query
+        .iter_entities(&world)
+        .map(|(e, (pos, vel))| (e, *pos, *vel))
+        .collect::<Vec<_>>();
+    assert_eq!(vec![(entity, Pos(1., 2., 3.), Vel(0.1, 0.2, 0.3))], matched);
+
+    // adding a component the entity already has overwrites it without moving the entity
+    let location = world.get_entity_location(entity);
+    world.add_component(entity, Vel(1., 1., 1.)).unwrap();
+    assert_eq!(location, world.get_entity_location(entity));
+    assert_eq!(
+        Vel(1., 1., 1.),
+        *world.get_component::<Vel>(entity).unwrap()
+    );
+}
+
+#[test]
+fn mutate_add_component_default() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    struct Health(u32);
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .insert((), vec![(Pos(1., 2., 3.),), (Pos(4., 5., 6.),)])
+        .to_vec();
+
+    world.add_component_default::<Health>(entities[0]).unwrap();
+
+    assert_eq!(
+        Health::default(),
+        *world.get_component::<Health>(entities[0]).unwrap()
+    );
+    assert!(world.get_component::<Health>(entities[1]).is_none());
+    assert_eq!(
+        Pos(1., 2., 3.),
+        *world.get_component::<Pos>(entities[0]).unwrap()
+    );
+}
+
 #[test]
 fn mutate_remove_component() {
     let _ = tracing_subscriber::fmt::try_init();
@@ -343,6 +1407,281 @@ fn mutate_remove_component() {
     assert_eq!(2, query_with_rot.iter(&world).count());
 }
 
+#[test]
+fn mutate_remove_component_keeps_entity() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entity = world.insert((), vec![(Pos(1., 2., 3.), Vel(0.1, 0.2, 0.3))])[0];
+    world.remove_component::<Vel>(entity).unwrap();
+
+    // the entity keeps its handle, and so its version, and its remaining data
+    assert!(world.is_alive(entity));
+    assert_eq!(
+        Pos(1., 2., 3.),
+        *world.get_component::<Pos>(entity).unwrap()
+    );
+    assert!(world.get_component::<Vel>(entity).is_none());
+
+    let with_pos = Read::<Pos>::query()
+        .iter_entities(&world)
+        .map(|(e, _)| e)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![entity], with_pos);
+    assert_eq!(0, Read::<Vel>::query().iter(&world).count());
+}
+
+#[test]
+fn mutate_remove_component_global() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .insert(
+            (),
+            vec![
+                (Pos(1., 2., 3.), Vel(0.1, 0.2, 0.3)),
+                (Pos(4., 5., 6.), Vel(0.4, 0.5, 0.6)),
+            ],
+        )
+        .to_vec();
+    world.insert((Model(5),), vec![(Pos(7., 8., 9.), Vel(0.7, 0.8, 0.9))]);
+    world.insert((), vec![(Pos(1., 1., 1.), Rot(0.1, 0.1, 0.1))]);
+
+    assert_eq!(3, world.remove_component_global::<Vel>());
+
+    assert_eq!(0, Read::<Vel>::query().iter(&world).count());
+    assert_eq!(4, Read::<Pos>::query().iter(&world).count());
+    for entity in world.iter_entities() {
+        assert!(!world
+            .entity_component_types(entity)
+            .unwrap()
+            .iter()
+            .any(|(type_id, _)| *type_id == ComponentTypeId::of::<Vel>()));
+    }
+
+    assert_eq!(
+        Pos(4., 5., 6.),
+        *world.get_component::<Pos>(entities[1]).unwrap()
+    );
+    assert_eq!(0, world.remove_component_global::<Vel>());
+}
+
+#[test]
+fn mutate_remove_component_global_bulk() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let counter = dropped.clone();
+    world.on_component_drop::<Vel, _>(move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+
+    // enough entities to span several chunks in each source archetype
+    let existing = world.insert((), (0..10).map(|i| (Pos(i as f32, 0., 0.),)))[0];
+    let moving = world
+        .insert(
+            (),
+            (0..3000).map(|i| (Pos(i as f32, 1., 0.), Vel(i as f32, 0., 0.))),
+        )
+        .to_vec();
+    let tagged = world
+        .insert(
+            (Model(1),),
+            (0..2000).map(|i| (Pos(i as f32, 2., 0.), Vel(i as f32, 0., 0.))),
+        )
+        .to_vec();
+    let sources = [moving[0], tagged[0]]
+        .iter()
+        .map(|entity| world.get_entity_location(*entity).unwrap().archetype())
+        .collect::<Vec<_>>();
+
+    assert_eq!(5000, world.remove_component_global::<Vel>());
+    assert_eq!(5000, dropped.load(Ordering::SeqCst));
+
+    // the source archetypes are left empty
+    for source in sources {
+        let archetype = &world.storage().archetypes()[source.0];
+        let len = archetype
+            .chunksets()
+            .iter()
+            .flat_map(|set| set.occupied())
+            .map(|chunk| chunk.len())
+            .sum::<usize>();
+        assert_eq!(0, len);
+    }
+
+    // entities without tags join the existing archetype without `Vel`
+    let target = world.get_entity_location(existing).unwrap().archetype();
+    for (i, entity) in moving.iter().enumerate() {
+        assert_eq!(
+            target,
+            world.get_entity_location(*entity).unwrap().archetype()
+        );
+        assert_eq!(
+            Pos(i as f32, 1., 0.),
+            *world.get_component::<Pos>(*entity).unwrap()
+        );
+        assert!(world.get_component::<Vel>(*entity).is_none());
+    }
+
+    let tagged_target = world.get_entity_location(tagged[0]).unwrap().archetype();
+    for (i, entity) in tagged.iter().enumerate() {
+        assert_eq!(
+            tagged_target,
+            world.get_entity_location(*entity).unwrap().archetype()
+        );
+        assert_eq!(
+            Pos(i as f32, 2., 0.),
+            *world.get_component::<Pos>(*entity).unwrap()
+        );
+        assert_eq!(Some(&Model(1)), world.get_tag::<Model>(*entity));
+    }
+    assert_eq!(5010, Read::<Pos>::query().iter(&world).count());
+}
+
+#[test]
+fn double_buffered_update() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Neighbour(Entity);
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .insert((), (0..4).map(|i| (Pos(i as f32, 0., 0.), Vel(1., 0., 0.))))
+        .to_vec();
+
+    // link the entities into a ring, each following the next
+    for (i, entity) in entities.iter().enumerate() {
+        let next = entities[(i + 1) % entities.len()];
+        world.add_component(*entity, Neighbour(next)).unwrap();
+    }
+
+    world.double_buffered_update::<Pos, (Read<Vel>, Read<Neighbour>), _>(
+        |_, pos, (vel, neighbour), snapshot| {
+            let target = snapshot.get(neighbour.0).unwrap();
+            *pos = Pos(target.0 + vel.0, target.1, target.2);
+        },
+    );
+
+    // every entity sees its neighbour's pre-update position, regardless of iteration order
+    for (i, entity) in entities.iter().enumerate() {
+        let expected = ((i + 1) % entities.len()) as f32 + 1.;
+        assert_eq!(
+            Pos(expected, 0., 0.),
+            *world.get_component::<Pos>(*entity).unwrap()
+        );
+    }
+}
+
+#[test]
+fn double_buffered_update_tuple_view() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Mass(f32);
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let moving = world
+        .insert(
+            (),
+            vec![
+                (Pos(0., 0., 0.), Vel(2., 0., 0.), Mass(1.)),
+                (Pos(1., 0., 0.), Vel(2., 0., 0.), Mass(2.)),
+            ],
+        )
+        .to_vec();
+    let unpositioned = world.insert((), vec![(Vel(2., 0., 0.), Mass(1.))]).to_vec();
+
+    let mut updated = 0;
+    world.double_buffered_update::<Pos, (Read<Vel>, Read<Mass>), _>(
+        |entity, pos, (vel, mass), snapshot| {
+            assert_eq!(snapshot.get(entity), Some(&*pos));
+            pos.0 += vel.0 / mass.0;
+            updated += 1;
+        },
+    );
+
+    assert_eq!(2, updated);
+    assert_eq!(
+        Pos(2., 0., 0.),
+        *world.get_component::<Pos>(moving[0]).unwrap()
+    );
+    assert_eq!(
+        Pos(2., 0., 0.),
+        *world.get_component::<Pos>(moving[1]).unwrap()
+    );
+    assert!(world.get_component::<Pos>(unpositioned[0]).is_none());
+}
+
+#[test]
+#[should_panic(expected = "must not access the updated component")]
+fn double_buffered_update_view_reads_target() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+    world.insert((), vec![(Pos(0., 0., 0.), Vel(1., 0., 0.))]);
+
+    world.double_buffered_update::<Pos, (Read<Vel>, Read<Pos>), _>(|_, _, _, _| {});
+}
+
+#[test]
+fn mutate_replace_components() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Seed(u32);
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Plant(u32);
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Growth(f32);
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .insert((Static,), vec![(Seed(1),), (Seed(2),), (Seed(3),)])
+        .to_vec();
+
+    assert!(world.replace_components(entities[0], (Model(4),), (Plant(1), Growth(0.5))));
+
+    assert!(world.is_alive(entities[0]));
+    assert!(world.get_component::<Seed>(entities[0]).is_none());
+    assert!(world.get_tag::<Static>(entities[0]).is_none());
+    assert_eq!(Some(&Model(4)), world.get_tag::<Model>(entities[0]));
+
+    let query = <(Read<Plant>, Read<Growth>)>::query();
+    let plants = query
+        .iter_entities(&world)
+        .map(|(e, (plant, growth))| (e, *plant, *growth))
+        .collect::<Vec<_>>();
+    assert_eq!(vec![(entities[0], Plant(1), Growth(0.5))], plants);
+
+    // the remaining seeds are unaffected
+    assert_eq!(Seed(2), *world.get_component::<Seed>(entities[1]).unwrap());
+    assert_eq!(Seed(3), *world.get_component::<Seed>(entities[2]).unwrap());
+    assert_eq!(2, Read::<Seed>::query().iter(&world).count());
+
+    world.delete(entities[1]);
+    assert!(!world.replace_components(entities[1], (), (Plant(2),)));
+}
+
 #[test]
 fn mutate_add_tag() {
     let _ = tracing_subscriber::fmt::try_init();
@@ -589,3 +1928,113 @@ fn entity_tag_types() {
     assert_eq!(1, tag_type_ids.len());
     assert!(tag_type_ids.contains(&TagTypeId::of::<Model>()));
 }
+
+#[test]
+fn for_each_chunk_mut() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::new();
+    let mut world = universe.create_world();
+
+    let entities = world
+        .insert(
+            (),
+            (0..10).map(|i| (Pos(i as f32, 0., 0.), Vel(1., 0., 0.), Rot(0., 0., 0.))),
+        )
+        .to_vec();
+    // a second archetype, and one which does not match the view
+    world.insert(
+        (Static,),
+        (0..5).map(|_| (Pos(0., 0., 0.), Vel(2., 0., 0.))),
+    );
+    world.insert((), (0..3).map(|_| (Pos(0., 0., 0.),)));
+
+    let mut visited = 0;
+    world.for_each_chunk_mut::<(Write<Pos>, Write<Vel>), _>(|chunk_entities, (pos, vel)| {
+        assert_eq!(chunk_entities.len(), pos.len());
+        assert_eq!(chunk_entities.len(), vel.len());
+        for (pos, vel) in pos.iter_mut().zip(vel.iter_mut()) {
+            pos.0 += vel.0;
+            vel.0 *= 2.;
+        }
+        visited += chunk_entities.len();
+    });
+    assert_eq!(15, visited);
+
+    for (i, entity) in entities.iter().enumerate() {
+        assert_eq!(
+            Pos(i as f32 + 1., 0., 0.),
+            *world.get_component::<Pos>(*entity).unwrap()
+        );
+        assert_eq!(
+            Vel(2., 0., 0.),
+            *world.get_component::<Vel>(*entity).unwrap()
+        );
+    }
+
+    let query = <(Read<Pos>, Read<Vel>)>::query().filter(tag::<Static>());
+    for (pos, vel) in query.iter(&world) {
+        assert_eq!(Pos(2., 0., 0.), *pos);
+        assert_eq!(Vel(4., 0., 0.), *vel);
+    }
+}
+
+#[test]
+fn universe_block_size() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::with_block_size(16);
+    let mut world = universe.create_world();
+    let entities = world
+        .insert((), (0..40).map(|_| (Pos(0., 0., 0.),)))
+        .to_vec();
+
+    // 40 entities fill two blocks of 16 and part of a third
+    let blocks = entities
+        .iter()
+        .map(|entity| entity.index() / 16)
+        .collect::<HashSet<_>>();
+    assert_eq!(3, blocks.len());
+    assert!(entities.iter().all(|entity| entity.index() < 48));
+
+    // the next world starts from a fresh block
+    let mut other = universe.create_world();
+    let entity = other.insert((), vec![(Pos(0., 0., 0.),)])[0];
+    assert_eq!(48, entity.index());
+    assert_eq!(Some(world.id()), universe.entity_owner(entities[39]));
+    assert_eq!(Some(other.id()), universe.entity_owner(entity));
+}
+
+#[test]
+fn deterministic_block_allocation() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    // inserts batches into two worlds in the given order, returning the IDs assigned by each
+    let run = |order: &[usize]| {
+        let universe = Universe::with_deterministic_blocks(16, 2);
+        let mut worlds = [universe.create_world(), universe.create_world()];
+        let mut entities = [Vec::new(), Vec::new()];
+        for world in order {
+            let inserted = worlds[*world].insert((), (0..10).map(|_| (Pos(0., 0., 0.),)));
+            entities[*world].extend_from_slice(inserted);
+        }
+        entities
+    };
+
+    let interleaved = run(&[0, 1, 0, 1, 0, 1]);
+    assert_eq!(interleaved, run(&[1, 1, 1, 0, 0, 0]));
+    assert_eq!(interleaved, run(&[0, 0, 1, 1, 1, 0]));
+
+    let first = interleaved[0].iter().collect::<HashSet<_>>();
+    assert!(interleaved[1].iter().all(|entity| !first.contains(entity)));
+}
+
+#[test]
+#[should_panic(expected = "at most 1 worlds")]
+fn deterministic_lanes_not_reclaimed() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let universe = Universe::with_deterministic_blocks(16, 1);
+    drop(universe.create_world());
+    universe.create_world();
+}